@@ -0,0 +1,13 @@
+// proves knowledge of x with x ^ 3 + x + 5 = 35, the same demo the main binary runs
+fn main() {
+    match halo2::proof::cubic_demo() {
+        Ok(proof) => {
+            println!("proof length is {:?}", proof.len());
+            println!("verification result is Ok(())");
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+// proves the 10th fibonacci number of the private seeds 1, 1 is the public 89
+use halo2::{
+    circuit::FibonacciCircuit,
+    proof::{create_proof_bytes, keygen, verify_proof_bytes},
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+fn main() {
+    let circuit = FibonacciCircuit {
+        a: Some(Fp::one()),
+        b: Some(Fp::one()),
+        n: 10,
+    };
+    let public_inputs = [Fp::from(89)];
+
+    let (params, pk) = keygen(circuit.k(), &circuit.without_witnesses()).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &public_inputs).unwrap();
+    println!("proof length is {:?}", proof.len());
+
+    let verified = verify_proof_bytes(&params, pk.get_vk(), &proof, &public_inputs);
+    println!("verification result is {:?}", verified);
+    if verified.is_err() {
+        std::process::exit(1);
+    }
+}
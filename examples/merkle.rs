@@ -0,0 +1,36 @@
+// proves a private leaf is in a depth 4 merkle tree with the public root
+use halo2::{
+    chip::mimc_hash,
+    circuit::MerklePathCircuit,
+    proof::{create_proof_bytes, keygen, verify_proof_bytes},
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+fn main() {
+    let leaf = Fp::from(42);
+    // sibling at every level and whether the node on the path is the right child
+    let path = [(7, 0), (8, 1), (9, 1), (10, 0)].map(|(s, d)| (Fp::from(s), Fp::from(d)));
+    // the root worked out natively, the same way the circuit hashes up the path
+    let root = path.iter().fold(leaf, |node, &(sibling, direction)| {
+        if direction == Fp::one() {
+            mimc_hash(sibling, node)
+        } else {
+            mimc_hash(node, sibling)
+        }
+    });
+    let circuit = MerklePathCircuit {
+        leaf: Some(leaf),
+        path: path.iter().map(|&(s, d)| (Some(s), Some(d))).collect(),
+    };
+    let public_inputs = [root];
+
+    let (params, pk) = keygen(circuit.k(), &circuit.without_witnesses()).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &public_inputs).unwrap();
+    println!("proof length is {:?}", proof.len());
+
+    let verified = verify_proof_bytes(&params, pk.get_vk(), &proof, &public_inputs);
+    println!("verification result is {:?}", verified);
+    if verified.is_err() {
+        std::process::exit(1);
+    }
+}
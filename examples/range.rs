@@ -0,0 +1,23 @@
+// proves the private 1990 lies in the public interval [1970, 2005]
+use halo2::{
+    circuit::IntervalCircuit,
+    proof::{create_proof_bytes, keygen, verify_proof_bytes},
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+fn main() {
+    let circuit = IntervalCircuit {
+        x: Some(Fp::from(1990)),
+    };
+    let public_inputs = [Fp::from(1970), Fp::from(2005)];
+
+    let (params, pk) = keygen(IntervalCircuit::k(), &circuit.without_witnesses()).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &public_inputs).unwrap();
+    println!("proof length is {:?}", proof.len());
+
+    let verified = verify_proof_bytes(&params, pk.get_vk(), &proof, &public_inputs);
+    println!("verification result is {:?}", verified);
+    if verified.is_err() {
+        std::process::exit(1);
+    }
+}
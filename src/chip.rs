@@ -0,0 +1,3747 @@
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{AssignedCell, Chip, Layouter},
+    pasta::group::ff::PrimeField,
+    pasta::Fp,
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector, TableColumn,
+    },
+    poly::Rotation,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+//instruction set must be implemented by our circuit
+pub trait Ops {
+    type Num;
+    // a Num already constrained to be 0 or 1
+    type Bit;
+    // Api between your chip with outside
+    // layouter helps manage circuit to be more moduler, flexible and help places value to its proper place
+    fn load_private(&self, layouter: impl Layouter<Fp>, x: Option<Fp>) -> Result<Self::Num, Error>;
+    // loads all private values in one region, down the rows of a single column
+    fn load_private_many(
+        &self,
+        layouter: impl Layouter<Fp>,
+        values: &[Option<Fp>],
+    ) -> Result<Vec<Self::Num>, Error>;
+    // this is for constant similar as Api
+    fn load_constant(&self, layouter: impl Layouter<Fp>, x: Fp) -> Result<Self::Num, Error>;
+    // copies the value on the given instance row into an advice cell, so a public input can be
+    // used in the circuit without baking it into the keys
+    fn load_public(&self, layouter: impl Layouter<Fp>, row: usize) -> Result<Self::Num, Error>;
+    // loads many constants in one region, equal values share the same cell
+    fn load_constants(
+        &self,
+        layouter: impl Layouter<Fp>,
+        values: &[Fp],
+    ) -> Result<Vec<Self::Num>, Error>;
+    // multiplication on fields,
+    fn mul(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // does addition on fields
+    fn add(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // does subtraction on fields, a - b
+    fn sub(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // divides fields, a / b, division by zero is a synthesis error
+    fn div(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // squares the field element, a * a with a single copy of a
+    fn square(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error>;
+    // witnesses y with y * y = x on the square gate, proving a square root of x is known.
+    // x having no square root at all is a synthesis error
+    fn sqrt_witness(
+        &self,
+        layouter: impl Layouter<Fp>,
+        y_value: Option<Fp>,
+        x: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // cubes the field element, a * a * a in one row with a single copy of a
+    fn cube(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error>;
+    // doubles the field element, 2 * a with a single copy of a
+    fn double(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error>;
+    // raises a to a public power n using square and multiply, a^0 is the constant 1
+    fn pow(&self, layouter: impl Layouter<Fp>, a: Self::Num, n: u64) -> Result<Self::Num, Error>;
+    // x^e for a private e given as little endian bits, constrained boolean. every bit costs
+    // the same square and multiplexed multiply, so the layout doesn't depend on e
+    fn pow_private(
+        &self,
+        layouter: impl Layouter<Fp>,
+        x: Self::Num,
+        e_bits: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+    // [x, x^2, ..., x^n] from a running product in one region
+    fn powers(
+        &self,
+        layouter: impl Layouter<Fp>,
+        x: Self::Num,
+        n: usize,
+    ) -> Result<Vec<Self::Num>, Error>;
+    // multiplies a by a constant k that is baked into the fixed coefficient column
+    fn mul_by_constant(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: Fp,
+    ) -> Result<Self::Num, Error>;
+    // adds a constant k from the fixed coefficient column, saves loading k as its own cell
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: Fp,
+    ) -> Result<Self::Num, Error>;
+    // a + k * b for a constant k from the fixed coefficient column
+    fn scaled_add(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        k: Fp,
+    ) -> Result<Self::Num, Error>;
+    // fused multiply and add, a * b + c in a single row
+    fn mul_add(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // two products summed, a * b + c * d in a single row
+    fn mul_mul_add(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+        d: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // weighted sum of the terms, sum(k_i * a_i), laid out as a running sum in one region
+    fn linear_combination(
+        &self,
+        layouter: impl Layouter<Fp>,
+        terms: &[(Fp, Self::Num)],
+    ) -> Result<Self::Num, Error>;
+    // sum of all values in one region, the empty sum is the constant 0
+    fn sum(&self, layouter: impl Layouter<Fp>, values: &[Self::Num]) -> Result<Self::Num, Error>;
+    // every prefix sum of the inputs, n inputs take n + 1 rows of one region
+    fn running_sum(
+        &self,
+        layouter: impl Layouter<Fp>,
+        inputs: &[Self::Num],
+    ) -> Result<Vec<Self::Num>, Error>;
+    // product of all values in one region, the empty product is the constant 1
+    fn product(
+        &self,
+        layouter: impl Layouter<Fp>,
+        values: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+    // every prefix product of the inputs, n inputs take n + 1 rows of one region
+    fn running_product(
+        &self,
+        layouter: impl Layouter<Fp>,
+        inputs: &[Self::Num],
+    ) -> Result<Vec<Self::Num>, Error>;
+    // multiplication chain over all values, same layout as product
+    fn mul_many(
+        &self,
+        layouter: impl Layouter<Fp>,
+        values: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+    // dot product sum(a_i * b_i) in one region, slices of different length are a synthesis error
+    fn dot_product(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: &[Self::Num],
+        b: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+    // returns a if cond is 1 and b if cond is 0, cond is constrained to be boolean
+    fn select(
+        &self,
+        layouter: impl Layouter<Fp>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // returns a + b if cond is 1 and a if cond is 0, cond is constrained to be boolean
+    fn cond_add(
+        &self,
+        layouter: impl Layouter<Fp>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // returns 1 if a is zero and 0 otherwise
+    fn is_zero(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error>;
+    // returns 1 if a == b and 0 otherwise, same as is_zero(a - b) but in one region
+    fn is_equal(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // returns (a, b) if cond is 0 and (b, a) if cond is 1, cond is constrained to be boolean
+    fn cond_swap(
+        &self,
+        layouter: impl Layouter<Fp>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+    // boolean and, a * b, both inputs are constrained to be 0 or 1
+    fn and(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // boolean or, a + b - a * b, both inputs are constrained to be 0 or 1
+    fn or(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // boolean not, 1 - a, the input is constrained to be 0 or 1
+    fn not(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error>;
+    // little endian bits of a, each constrained boolean and their weighted sum constrained to a,
+    // a value not fitting in n_bits or n_bits > MAX_BITS is a synthesis error
+    fn decompose(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        n_bits: usize,
+    ) -> Result<Vec<Self::Num>, Error>;
+    // constrains a < 2^n_bits without handing out the bits, a value not fitting or
+    // n_bits > MAX_BITS is a synthesis error
+    fn assert_fits(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        n_bits: usize,
+    ) -> Result<(), Error>;
+    // sum(bits[i] * 2^i) for little endian bits, each bit is constrained boolean again on the way in.
+    // more than MAX_BITS bits is a synthesis error
+    fn pack_bits(
+        &self,
+        layouter: impl Layouter<Fp>,
+        bits: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+    // (a << k) mod 2^n_bits for a fitting in n_bits, the top k bits are dropped but still
+    // constrained boolean by the decomposition. k >= n_bits gives a constant zero
+    fn shl(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // a >> k for a fitting in n_bits, k >= n_bits gives a constant zero
+    fn shr(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // rotates the n_bits bits of a left by k, the bits wrapping around keep their weight
+    // in the repacked value so nothing is lost. k is taken mod n_bits
+    fn rotl(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // rotates the n_bits bits of a right by k
+    fn rotr(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // constrains a < 2^bits using the byte table, wider values are checked in byte sized chunks.
+    // the table has to be loaded once with ByteTableChip::load before any range check
+    fn range_check(&self, layouter: impl Layouter<Fp>, a: Self::Num, bits: u8)
+        -> Result<(), Error>;
+    // little endian limbs of a, each range checked to limb_bits and recombined with weights
+    // 2^(i * limb_bits) into a. needs the byte table loaded, a value not fitting, limb_bits
+    // outside 1..=255 or more than 256 bits in total is a synthesis error. past MAX_BITS in total
+    // the limbs of a + p recompose to a too, so only up to MAX_BITS is the split unique
+    fn decompose_limbs(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        limb_bits: usize,
+        n_limbs: usize,
+    ) -> Result<Vec<Self::Num>, Error>;
+    // lowest bit of a, witnessed as a = 2 * half + bit with half range checked to n_bits - 1.
+    // needs the byte table loaded, n_bits outside 1..=MAX_BITS is a synthesis error
+    fn parity(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // returns 1 if a < b and 0 otherwise, a and b are assumed to fit in n_bits
+    fn lt(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // smaller of a and b, both assumed to fit in n_bits. the select gate forces the output
+    // to equal one of the two inputs, so it is never a free witness
+    fn min(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // larger of a and b, both assumed to fit in n_bits
+    fn max(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // |a - b| for a and b assumed to fit in n_bits
+    fn abs_diff(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // u64 addition, returns (a + b mod 2^64, carry) with a + b = sum + carry * 2^64.
+    // a, b and the sum are range checked to 64 bits, so the byte table has to be loaded
+    fn add_u64(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+    // u64 division, returns (q, r) with a = q * b + r and r < b. everything is range checked
+    // to 64 bits so the byte table has to be loaded, b = 0 is a synthesis error
+    fn divrem_u64(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error>;
+    // floor(sqrt(n)) for n fitting in n_bits, with s * s <= n < (s + 1) * (s + 1) enforced.
+    // needs the byte table loaded, n_bits > 64 is a synthesis error
+    fn isqrt(
+        &self,
+        layouter: impl Layouter<Fp>,
+        n: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // fixed point product of a and b with scale_bits fractional bits, (a * b) >> scale_bits
+    // rounded by truncation. a and b are range checked to 64 bits so the product can't wrap,
+    // the byte table has to be loaded and scale_bits > 64 is a synthesis error
+    fn mul_fixed_point(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        scale_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // a mod m for a public m, witnessed as a = q * m + r with q range checked to n_bits and
+    // r < m. needs the byte table loaded, m = 0 or n_bits > 64 is a synthesis error
+    fn mod_const(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        m: u64,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error>;
+    // signed values are kept as field elements, -v being p - v. returns magnitude when sign_bit
+    // is 0 and -magnitude when it is 1, with magnitude range checked to 64 bits and sign_bit
+    // boolean, so positive and negative values can never overlap. needs the byte table loaded
+    fn to_signed(
+        &self,
+        layouter: impl Layouter<Fp>,
+        magnitude: Self::Num,
+        sign_bit: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // returns 1 if a < b as signed values and 0 otherwise. both are constrained to lie in
+    // [-2^64, 2^64), the range to_signed produces, and the byte table has to be loaded
+    fn signed_lt(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // evaluates sum(coeffs[i] * x^i) for public coefficients with horner's rule in one region
+    fn eval_poly(
+        &self,
+        layouter: impl Layouter<Fp>,
+        coeffs: &[Fp],
+        x: Self::Num,
+    ) -> Result<Self::Num, Error>;
+    // negates the field element, -a
+    fn neg(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error>;
+    // constrains two already assigned cells to hold the same value
+    fn assert_equal(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(), Error>;
+    // constrains a to equal the constant k, which sits in the coefficient column next to a
+    fn assert_equals_constant(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: Fp,
+    ) -> Result<(), Error>;
+    // constrains a to be nonzero by witnessing its inverse, a zero value is a synthesis error
+    fn assert_nonzero(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<(), Error>;
+    // inverses of all values in one region, each row constrained with v * inv = 1. the witness
+    // uses a single field inversion for the whole batch, a zero value is a synthesis error
+    fn batch_invert(
+        &self,
+        layouter: impl Layouter<Fp>,
+        values: &[Self::Num],
+    ) -> Result<Vec<Self::Num>, Error>;
+    // constrains a to be 0 or 1
+    fn assert_boolean(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<(), Error>;
+    // same check as assert_boolean but hands back the checked cell, so boolean gadgets
+    // taking a Bit don't have to constrain it again
+    #[allow(clippy::wrong_self_convention)]
+    fn into_bit(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Bit, Error>;
+    // exposes the public value/result to verify if it matches the end of operation
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<Fp>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+    // exposes the cells on consecutive instance rows from start_row and returns the next free row
+    fn expose_public_many(
+        &self,
+        layouter: impl Layouter<Fp>,
+        cells: &[Self::Num],
+        start_row: usize,
+    ) -> Result<usize, Error>;
+}
+
+// a cell that has gone through the boolean gate, only built by into_bit
+#[derive(Clone, Debug)]
+pub struct Bit(AssignedCell<Fp, Fp>);
+
+impl Bit {
+    pub fn cell(&self) -> &AssignedCell<Fp, Fp> {
+        &self.0
+    }
+}
+
+// width of the byte table, values are range checked in chunks of this many bits
+pub const RANGE_TABLE_BITS: usize = 8;
+
+// the largest k any circuit can have, Params::new asserts k < 32
+pub const MAX_K: u32 = 31;
+
+// the pasta modulus p is a little over 2^254, so a weighted sum of at most this many bits stays
+// below p and pins down a single value. with 255 bits both a and a + p can be written out
+pub const MAX_BITS: usize = 254;
+
+// a single table column of 0..256 that any chip can register lookups against,
+// so range checks and other byte gadgets share one table instead of loading their own
+#[derive(Clone, Debug)]
+pub struct ByteTableConfig {
+    pub table: TableColumn,
+}
+
+#[derive(Debug)]
+pub struct ByteTableChip {
+    config: ByteTableConfig,
+}
+
+impl ByteTableChip {
+    pub fn new(config: ByteTableConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fp>) -> ByteTableConfig {
+        ByteTableConfig {
+            table: meta.lookup_table_column(),
+        }
+    }
+
+    // fills the table, circuits using it call this once at the start of synthesize
+    // so the table exists before any lookup row is laid out
+    pub fn load(&self, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let config = self.config();
+        layouter.assign_table(
+            || "byte table",
+            |mut table| {
+                for v in 0..(1 << RANGE_TABLE_BITS) {
+                    table.assign_cell(|| "byte", config.table, v, || Ok(Fp::from(v as u64)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl Chip<Fp> for ByteTableChip {
+    type Config = ByteTableConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[derive(Debug)]
+pub struct MyChip {
+    config: MyConfig,
+    // constants already assigned during this synthesis, keyed by their byte representation,
+    // so loading the same constant again hands back the existing cell
+    constants: RefCell<HashMap<[u8; 32], AssignedCell<Fp, Fp>>>,
+}
+
+impl MyChip {
+    pub fn new(config: MyConfig) -> Self {
+        Self {
+            config,
+            constants: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: [Column<Advice>; 4],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+        coeff: Column<Fixed>,
+        byte_table: ByteTableConfig,
+    ) -> MyConfig {
+        meta.enable_constant(constant);
+        meta.enable_equality(instance);
+        for adv in advice.iter() {
+            meta.enable_equality(*adv);
+        }
+        let s_mul = meta.selector();
+        let s_add = meta.selector();
+        let s_sub = meta.selector();
+        let s_neg = meta.selector();
+        let s_div = meta.selector();
+        let s_square = meta.selector();
+        let s_mulc = meta.selector();
+        let s_addc = meta.selector();
+        let s_muladd = meta.selector();
+        let s_lc = meta.selector();
+        let s_dot = meta.selector();
+        let s_select = meta.selector();
+        let s_is_zero = meta.selector();
+        let s_is_eq = meta.selector();
+        let s_nonzero = meta.selector();
+        let s_swap = meta.selector();
+        let s_and = meta.selector();
+        let s_or = meta.selector();
+        let s_not = meta.selector();
+        let s_bits = meta.selector();
+        let s_range = meta.complex_selector();
+        let s_range_acc = meta.selector();
+        let s_range_shift = meta.selector();
+        let s_horner = meta.selector();
+        let s_double = meta.selector();
+        let s_cube = meta.selector();
+        let s_mma = meta.selector();
+        let s_cond_add = meta.selector();
+        let s_bool = meta.selector();
+        let s_pack = meta.selector();
+        let s_pow_step = meta.selector();
+        let s_eq_const = meta.selector();
+        meta.create_gate("mul/add", |meta| {
+            // lhs, advice column for first row
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            // rhs. advice column for first row
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            // output is first column for next row
+            let out = meta.query_advice(advice[0], Rotation::next());
+            // pickup the selectors to add these columns
+            let s_mul = meta.query_selector(s_mul);
+            let s_add = meta.query_selector(s_add);
+            // condition for gate is
+            // if s_mul == 0 then first condition is nothing
+            // if its 1 then the next value (lhs * rhs - out) must be 0
+            // similar with add so both of the values should be 0 as a constraint
+            // either through selector or the operation
+            // we enable s_mul in add/mul function of circuit using config.s_mul.enable(region, row);
+            // if both are 0 or off we dont care about the value then
+            vec![
+                s_mul * (lhs.clone() * rhs.clone() - out.clone()),
+                s_add * (lhs + rhs - out),
+            ]
+        });
+        meta.create_gate("sub", |meta| {
+            // same layout as mul/add, out = lhs - rhs on the next row
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_sub = meta.query_selector(s_sub);
+            vec![s_sub * (lhs - rhs - out)]
+        });
+        meta.create_gate("neg", |meta| {
+            // only one input, so rhs column is left unused on this row
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_neg = meta.query_selector(s_neg);
+            // lhs + out == 0 means out is -lhs
+            vec![s_neg * (lhs + out)]
+        });
+        meta.create_gate("div", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            // quotient goes to the next row like other outputs
+            let out = meta.query_advice(advice[0], Rotation::next());
+            // inverse of rhs sits next to the quotient, it only exists if rhs != 0
+            let rhs_inv = meta.query_advice(advice[1], Rotation::next());
+            let s_div = meta.query_selector(s_div);
+            vec![
+                s_div.clone() * (rhs.clone() * out - lhs),
+                s_div * (rhs * rhs_inv - Expression::Constant(Fp::one())),
+            ]
+        });
+        meta.create_gate("square", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_square = meta.query_selector(s_square);
+            vec![s_square * (lhs.clone() * lhs - out)]
+        });
+        meta.create_gate("mul by constant", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            // coefficient is fixed at keygen, so it needs no copy constraint
+            let k = meta.query_fixed(coeff, Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mulc = meta.query_selector(s_mulc);
+            vec![s_mulc * (k * lhs - out)]
+        });
+        meta.create_gate("add constant", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_fixed(coeff, Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_addc = meta.query_selector(s_addc);
+            vec![s_addc * (lhs + k - out)]
+        });
+        meta.create_gate("mul add", |meta| {
+            // the third advice column holds the addend on the same row
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let addend = meta.query_advice(advice[2], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_muladd = meta.query_selector(s_muladd);
+            vec![s_muladd * (lhs * rhs + addend - out)]
+        });
+        meta.create_gate("linear combination", |meta| {
+            // advice[0] carries the accumulator down the rows, each row adds k * term
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let term = meta.query_advice(advice[1], Rotation::cur());
+            let k = meta.query_fixed(coeff, Rotation::cur());
+            let acc_next = meta.query_advice(advice[0], Rotation::next());
+            let s_lc = meta.query_selector(s_lc);
+            vec![s_lc * (acc + k * term - acc_next)]
+        });
+        meta.create_gate("dot product", |meta| {
+            // accumulator in advice[0], the pair being multiplied in advice[1] and advice[2]
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let a = meta.query_advice(advice[1], Rotation::cur());
+            let b = meta.query_advice(advice[2], Rotation::cur());
+            let acc_next = meta.query_advice(advice[0], Rotation::next());
+            let s_dot = meta.query_selector(s_dot);
+            vec![s_dot * (acc + a * b - acc_next)]
+        });
+        meta.create_gate("select", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let cond = meta.query_advice(advice[2], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_select = meta.query_selector(s_select);
+            let one = Expression::Constant(Fp::one());
+            vec![
+                // cond is either 0 or 1
+                s_select.clone() * (cond.clone() * (one - cond.clone())),
+                // out = cond * a + (1 - cond) * b, rearranged
+                s_select * (b.clone() + cond * (a - b) - out),
+            ]
+        });
+        meta.create_gate("is zero", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            // inverse of a, or anything when a is zero
+            let a_inv = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_is_zero = meta.query_selector(s_is_zero);
+            let one = Expression::Constant(Fp::one());
+            vec![
+                // if a != 0 the prover has to give the real inverse, so out is 0
+                s_is_zero.clone() * (one - a.clone() * a_inv - out.clone()),
+                // if a == 0 the product above is 0, so out is forced to 1 by the first rule,
+                // and a nonzero a can't claim out = 1 because of this one
+                s_is_zero * (a * out),
+            ]
+        });
+        meta.create_gate("is equal", |meta| {
+            // the is zero gate applied to a - b, these two rules already force out to be 0 or 1
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let diff_inv = meta.query_advice(advice[2], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_is_eq = meta.query_selector(s_is_eq);
+            let one = Expression::Constant(Fp::one());
+            let diff = a - b;
+            vec![
+                s_is_eq.clone() * (one - diff.clone() * diff_inv - out.clone()),
+                s_is_eq * (diff * out),
+            ]
+        });
+        meta.create_gate("nonzero", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let a_inv = meta.query_advice(advice[1], Rotation::cur());
+            let s_nonzero = meta.query_selector(s_nonzero);
+            // only satisfiable when a has an inverse
+            vec![s_nonzero * (a * a_inv - Expression::Constant(Fp::one()))]
+        });
+        meta.create_gate("equals constant", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_fixed(coeff, Rotation::cur());
+            let s_eq_const = meta.query_selector(s_eq_const);
+            vec![s_eq_const * (a - k)]
+        });
+        meta.create_gate("boolean", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let s_bool = meta.query_selector(s_bool);
+            vec![s_bool * bool_check(a)]
+        });
+        meta.create_gate("cond swap", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let cond = meta.query_advice(advice[2], Rotation::cur());
+            // both outputs go on the next row, in the same columns as the inputs
+            let a_out = meta.query_advice(advice[0], Rotation::next());
+            let b_out = meta.query_advice(advice[1], Rotation::next());
+            let s_swap = meta.query_selector(s_swap);
+            let one = Expression::Constant(Fp::one());
+            vec![
+                s_swap.clone() * (cond.clone() * (one - cond.clone())),
+                s_swap.clone() * (a.clone() + cond.clone() * (b.clone() - a.clone()) - a_out),
+                s_swap * (b.clone() + cond * (a - b) - b_out),
+            ]
+        });
+        meta.create_gate("and", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_and = meta.query_selector(s_and);
+            vec![
+                s_and.clone() * bool_check(a.clone()),
+                s_and.clone() * bool_check(b.clone()),
+                s_and * (a * b - out),
+            ]
+        });
+        meta.create_gate("or", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_or = meta.query_selector(s_or);
+            vec![
+                s_or.clone() * bool_check(a.clone()),
+                s_or.clone() * bool_check(b.clone()),
+                s_or * (a.clone() + b.clone() - a * b - out),
+            ]
+        });
+        meta.create_gate("not", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_not = meta.query_selector(s_not);
+            vec![
+                s_not.clone() * bool_check(a.clone()),
+                s_not * (Expression::Constant(Fp::one()) - a - out),
+            ]
+        });
+        meta.create_gate("decompose", |meta| {
+            // linear combination of bits with k = 2^i, plus the bit itself has to be boolean
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let bit = meta.query_advice(advice[1], Rotation::cur());
+            let k = meta.query_fixed(coeff, Rotation::cur());
+            let acc_next = meta.query_advice(advice[0], Rotation::next());
+            let s_bits = meta.query_selector(s_bits);
+            vec![
+                s_bits.clone() * bool_check(bit.clone()),
+                s_bits * (acc + k * bit - acc_next),
+            ]
+        });
+        meta.create_gate("pack", |meta| {
+            // bits come in most significant first, so the accumulator doubles every row
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let bit = meta.query_advice(advice[1], Rotation::cur());
+            let acc_next = meta.query_advice(advice[0], Rotation::next());
+            let s_pack = meta.query_selector(s_pack);
+            vec![
+                s_pack.clone() * bool_check(bit.clone()),
+                s_pack * (acc.clone() + acc + bit - acc_next),
+            ]
+        });
+        meta.create_gate("pow step", |meta| {
+            // acc^2 sits in advice[3] so the multiplexed multiply stays at degree 4
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let bit = meta.query_advice(advice[1], Rotation::cur());
+            let x = meta.query_advice(advice[2], Rotation::cur());
+            let sq = meta.query_advice(advice[3], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_pow_step = meta.query_selector(s_pow_step);
+            let one = Expression::Constant(Fp::one());
+            vec![
+                s_pow_step.clone() * bool_check(bit.clone()),
+                s_pow_step.clone() * (acc.clone() * acc - sq.clone()),
+                // out = sq * x when the bit is set and sq otherwise
+                s_pow_step * (sq.clone() + sq * bit * (x - one) - out),
+            ]
+        });
+        meta.lookup(|meta| {
+            // every chunk of a range check sits in advice[1] and has to be in the table
+            let s_range = meta.query_selector(s_range);
+            let chunk = meta.query_advice(advice[1], Rotation::cur());
+            vec![(s_range * chunk, byte_table.table)]
+        });
+        meta.create_gate("range check", |meta| {
+            // chunks are added most significant first, acc_next = acc * 2^8 + chunk
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let chunk = meta.query_advice(advice[1], Rotation::cur());
+            let acc_next = meta.query_advice(advice[0], Rotation::next());
+            let s_range_acc = meta.query_selector(s_range_acc);
+            let radix = Expression::Constant(Fp::from(1 << RANGE_TABLE_BITS));
+            vec![s_range_acc * (acc * radix - acc_next + chunk)]
+        });
+        meta.create_gate("range check shift", |meta| {
+            // the top chunk may be narrower than the table, so it is shifted up by k = 2^(8 - width)
+            // and the shifted value is also looked up, which only fits if the chunk is small enough
+            let shifted = meta.query_advice(advice[1], Rotation::cur());
+            let k = meta.query_fixed(coeff, Rotation::cur());
+            let chunk = meta.query_advice(advice[1], Rotation::next());
+            let s_range_shift = meta.query_selector(s_range_shift);
+            vec![s_range_shift * (chunk * k - shifted)]
+        });
+        meta.create_gate("horner", |meta| {
+            // one horner step, acc_next = acc * x + c with the coefficient c in the fixed column
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let x = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_fixed(coeff, Rotation::cur());
+            let acc_next = meta.query_advice(advice[0], Rotation::next());
+            let s_horner = meta.query_selector(s_horner);
+            vec![s_horner * (acc * x + c - acc_next)]
+        });
+        meta.create_gate("double", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_double = meta.query_selector(s_double);
+            vec![s_double * (lhs.clone() + lhs - out)]
+        });
+        meta.create_gate("cube", |meta| {
+            // degree 3 in the advice cell, 4 with the selector
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_cube = meta.query_selector(s_cube);
+            vec![s_cube * (lhs.clone() * lhs.clone() * lhs - out)]
+        });
+        meta.create_gate("mul mul add", |meta| {
+            // all four advice columns on one row, the output below the first one
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_advice(advice[2], Rotation::cur());
+            let d = meta.query_advice(advice[3], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mma = meta.query_selector(s_mma);
+            vec![s_mma * (a * b + c * d - out)]
+        });
+        meta.create_gate("cond add", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let cond = meta.query_advice(advice[2], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_cond_add = meta.query_selector(s_cond_add);
+            vec![
+                s_cond_add.clone() * bool_check(cond.clone()),
+                s_cond_add * (a + cond * b - out),
+            ]
+        });
+
+        MyConfig {
+            advice,
+            instance,
+            coeff,
+            s_mul,
+            s_add,
+            s_sub,
+            s_neg,
+            s_div,
+            s_square,
+            s_mulc,
+            s_addc,
+            s_muladd,
+            s_lc,
+            s_dot,
+            s_select,
+            s_is_zero,
+            s_is_eq,
+            s_nonzero,
+            s_swap,
+            s_and,
+            s_or,
+            s_not,
+            s_bits,
+            byte_table,
+            s_range,
+            s_range_acc,
+            s_range_shift,
+            s_horner,
+            s_double,
+            s_cube,
+            s_mma,
+            s_cond_add,
+            s_bool,
+            s_pack,
+            s_pow_step,
+            s_eq_const,
+        }
+    }
+}
+
+// little endian bits of a field element
+pub(crate) fn to_bits(v: &Fp) -> Vec<bool> {
+    v.to_repr()
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+        .collect()
+}
+
+// low 64 bits of a field element, anything above is dropped
+pub(crate) fn to_u64(v: &Fp) -> u64 {
+    let repr = v.to_repr();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&repr[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+// inverse of to_bits, little endian bits back into a field element
+pub(crate) fn from_bits(bits: &[bool]) -> Fp {
+    bits.iter().rev().fold(Fp::zero(), |acc, bit| {
+        acc.double() + if *bit { Fp::one() } else { Fp::zero() }
+    })
+}
+
+// v * (1 - v) is only zero when v is 0 or 1
+pub(crate) fn bool_check(v: Expression<Fp>) -> Expression<Fp> {
+    v.clone() * (Expression::Constant(Fp::one()) - v)
+}
+
+impl Chip<Fp> for MyChip {
+    type Config = MyConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl Ops for MyChip {
+    type Num = AssignedCell<Fp, Fp>;
+    type Bit = Bit;
+
+    fn load_public(&self, mut layouter: impl Layouter<Fp>, row: usize) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "load public",
+            |mut region| {
+                region.assign_advice_from_instance(
+                    || "public value",
+                    config.instance,
+                    row,
+                    config.advice[0],
+                    0,
+                )
+            },
+        )
+    }
+
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        v: Option<Fp>,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        // region basically eccompasses a set of cells it can be multiple cells or even multiple rows,
+        // region helps organize the circuit into logical sections
+        layouter.assign_region(
+            // naming helps in debugging purposes when something goes wrong
+            || "load private",
+            |mut region| {
+                region.assign_advice(
+                    || "private value",
+                    config.advice[0],
+                    0,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn load_private_many(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        values: &[Option<Fp>],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+        if values.is_empty() {
+            return Ok(vec![]);
+        }
+        layouter.assign_region(
+            || "load private many",
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        region.assign_advice(
+                            || format!("private value {}", i),
+                            config.advice[0],
+                            i,
+                            || v.ok_or(Error::Synthesis),
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn load_constant(&self, mut layouter: impl Layouter<Fp>, v: Fp) -> Result<Self::Num, Error> {
+        let config = self.config();
+        if let Some(cell) = self.constants.borrow().get(&v.to_repr()) {
+            return Ok(cell.clone());
+        }
+        let cell = layouter.assign_region(
+            || "load constant",
+            |mut region| region.assign_advice_from_constant(|| "constant", config.advice[0], 0, v),
+        )?;
+        self.constants
+            .borrow_mut()
+            .insert(v.to_repr(), cell.clone());
+        Ok(cell)
+    }
+
+    fn load_constants(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        values: &[Fp],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+        // only values that aren't loaded yet get a row, each of them once
+        let mut missing: Vec<Fp> = vec![];
+        for v in values {
+            if !self.constants.borrow().contains_key(&v.to_repr()) && !missing.contains(v) {
+                missing.push(*v);
+            }
+        }
+        if !missing.is_empty() {
+            let cells = layouter.assign_region(
+                || "load constants",
+                |mut region| {
+                    missing
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| {
+                            region.assign_advice_from_constant(
+                                || format!("constant {}", i),
+                                config.advice[0],
+                                i,
+                                *v,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, Error>>()
+                },
+            )?;
+            let mut constants = self.constants.borrow_mut();
+            for (v, cell) in missing.iter().zip(cells) {
+                constants.insert(v.to_repr(), cell);
+            }
+        }
+        let constants = self.constants.borrow();
+        Ok(values
+            .iter()
+            .map(|v| constants[&v.to_repr()].clone())
+            .collect())
+    }
+
+    fn mul(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "mul",
+            |mut region| {
+                // offset here is the row, and config.advice[0]/1 == column
+                // so we just multiply in the matrix or telling layouter which portion
+                // of circuit has to be taken and which value you want them to have or what relation you want between them
+                // and then store the value in row 1 with column 0 value in assign_advice
+                config.s_mul.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                let v = a.value().and_then(|a| b.value().map(|b| *a * *b));
+                region.assign_advice(
+                    || "a * b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn add(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        // this is config of the circuit not the chip
+        let config = self.config();
+        layouter.assign_region(
+            || "add",
+            |mut region| {
+                config.s_add.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                // this is the basic operation
+                let v = a.value().and_then(|a| b.value().map(|b| *a + *b));
+                // this basically assigns value to the region
+                region.assign_advice(
+                    || "a + b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn sub(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "sub",
+            |mut region| {
+                config.s_sub.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                let v = a.value().and_then(|a| b.value().map(|b| *a - *b));
+                region.assign_advice(
+                    || "a - b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn div(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        // zero has no inverse, so fail here instead of assigning a witness that can't satisfy the gate
+        let b_inv = match b.value() {
+            Some(b) => Some(Option::<Fp>::from(b.invert()).ok_or(Error::Synthesis)?),
+            None => None,
+        };
+        layouter.assign_region(
+            || "div",
+            |mut region| {
+                config.s_div.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                region.assign_advice(
+                    || "1 / b",
+                    config.advice[1],
+                    1,
+                    || b_inv.ok_or(Error::Synthesis),
+                )?;
+                let v = a.value().and_then(|a| b_inv.map(|b_inv| *a * b_inv));
+                region.assign_advice(
+                    || "a / b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn square(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "square",
+            |mut region| {
+                // unlike mul(a, a) only one copy constraint is needed
+                config.s_square.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                let v = a.value().map(|a| a.square());
+                region.assign_advice(
+                    || "a * a",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn sqrt_witness(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        y_value: Option<Fp>,
+        x: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        if let Some(x) = x.value() {
+            if bool::from(x.sqrt().is_none()) {
+                return Err(Error::Synthesis);
+            }
+        }
+        layouter.assign_region(
+            || "sqrt",
+            |mut region| {
+                config.s_square.enable(&mut region, 0)?;
+                let y = region.assign_advice(
+                    || "y",
+                    config.advice[0],
+                    0,
+                    || y_value.ok_or(Error::Synthesis),
+                )?;
+                x.copy_advice(|| "y * y", &mut region, config.advice[0], 1)?;
+                Ok(y)
+            },
+        )
+    }
+
+    fn cube(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "cube",
+            |mut region| {
+                config.s_cube.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                let v = a.value().map(|a| a.square() * a);
+                region.assign_advice(
+                    || "a * a * a",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn double(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "double",
+            |mut region| {
+                // unlike add(a, a) only one copy constraint is needed
+                config.s_double.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                let v = a.value().map(|a| a.double());
+                region.assign_advice(
+                    || "2 * a",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn pow(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        n: u64,
+    ) -> Result<Self::Num, Error> {
+        match n {
+            0 => return self.load_constant(layouter.namespace(|| "a^0"), Fp::one()),
+            1 => return Ok(a),
+            _ => {}
+        }
+        // walk the bits of n from the most significant one down,
+        // squaring every step and multiplying by a when the bit is set
+        let top = 63 - n.leading_zeros();
+        let mut acc = a.clone();
+        for i in (0..top).rev() {
+            acc = self.square(layouter.namespace(|| format!("square {}", i)), acc)?;
+            if (n >> i) & 1 == 1 {
+                acc = self.mul(layouter.namespace(|| format!("mul {}", i)), acc, a.clone())?;
+            }
+        }
+        Ok(acc)
+    }
+
+    fn pow_private(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        x: Self::Num,
+        e_bits: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        let mut acc = self.load_constant(layouter.namespace(|| "x^0"), Fp::one())?;
+        for (i, bit) in e_bits.iter().enumerate().rev() {
+            acc = layouter.assign_region(
+                || format!("pow step {}", i),
+                |mut region| {
+                    config.s_pow_step.enable(&mut region, 0)?;
+                    acc.copy_advice(|| "acc", &mut region, config.advice[0], 0)?;
+                    bit.copy_advice(|| "bit", &mut region, config.advice[1], 0)?;
+                    x.copy_advice(|| "x", &mut region, config.advice[2], 0)?;
+                    let sq = acc.value().map(|acc| acc.square());
+                    region.assign_advice(
+                        || "acc * acc",
+                        config.advice[3],
+                        0,
+                        || sq.ok_or(Error::Synthesis),
+                    )?;
+                    let v = sq.zip(bit.value()).zip(x.value()).map(|((sq, bit), x)| {
+                        if *bit == Fp::one() {
+                            sq * x
+                        } else {
+                            sq
+                        }
+                    });
+                    region.assign_advice(
+                        || "bit ? acc^2 * x : acc^2",
+                        config.advice[0],
+                        1,
+                        || v.ok_or(Error::Synthesis),
+                    )
+                },
+            )?;
+        }
+        Ok(acc)
+    }
+
+    fn mul_by_constant(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: Fp,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "mul by constant",
+            |mut region| {
+                config.s_mulc.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                region.assign_fixed(|| "k", config.coeff, 0, || Ok(k))?;
+                let v = a.value().map(|a| *a * k);
+                region.assign_advice(
+                    || "k * a",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn add_constant(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: Fp,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "add constant",
+            |mut region| {
+                config.s_addc.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                region.assign_fixed(|| "k", config.coeff, 0, || Ok(k))?;
+                let v = a.value().map(|a| *a + k);
+                region.assign_advice(
+                    || "a + k",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn scaled_add(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        k: Fp,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "scaled add",
+            |mut region| {
+                // a single linear combination step with a as the accumulator
+                config.s_lc.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                region.assign_fixed(|| "k", config.coeff, 0, || Ok(k))?;
+                let v = a.value().and_then(|a| b.value().map(|b| *a + k * *b));
+                region.assign_advice(
+                    || "a + k * b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn mul_add(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "mul add",
+            |mut region| {
+                config.s_muladd.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+                c.copy_advice(|| "addend", &mut region, config.advice[2], 0)?;
+                let v = a
+                    .value()
+                    .and_then(|a| b.value().and_then(|b| c.value().map(|c| *a * *b + *c)));
+                region.assign_advice(
+                    || "a * b + c",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn mul_mul_add(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        c: Self::Num,
+        d: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "mul mul add",
+            |mut region| {
+                config.s_mma.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                c.copy_advice(|| "c", &mut region, config.advice[2], 0)?;
+                d.copy_advice(|| "d", &mut region, config.advice[3], 0)?;
+                let v = a
+                    .value()
+                    .zip(b.value())
+                    .zip(c.value().zip(d.value()))
+                    .map(|((a, b), (c, d))| *a * *b + *c * *d);
+                region.assign_advice(
+                    || "a * b + c * d",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn linear_combination(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        terms: &[(Fp, Self::Num)],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "linear combination",
+            |mut region| {
+                // accumulator starts at a constant zero, so an empty slice just returns it
+                let mut acc = region.assign_advice_from_constant(
+                    || "zero",
+                    config.advice[0],
+                    0,
+                    Fp::zero(),
+                )?;
+                for (i, (k, term)) in terms.iter().enumerate() {
+                    config.s_lc.enable(&mut region, i)?;
+                    term.copy_advice(|| "term", &mut region, config.advice[1], i)?;
+                    region.assign_fixed(|| "k", config.coeff, i, || Ok(*k))?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| term.value().map(|term| *acc + *k * *term));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+
+    fn sum(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        values: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        let (first, rest) = match values.split_first() {
+            Some(split) => split,
+            None => return self.load_constant(layouter.namespace(|| "empty sum"), Fp::zero()),
+        };
+        layouter.assign_region(
+            || "sum",
+            |mut region| {
+                let mut acc = first.copy_advice(|| "first", &mut region, config.advice[0], 0)?;
+                // chained add rows, each output row is the lhs of the next addition
+                for (i, value) in rest.iter().enumerate() {
+                    config.s_add.enable(&mut region, i)?;
+                    value.copy_advice(|| "value", &mut region, config.advice[1], i)?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| value.value().map(|value| *acc + *value));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+
+    fn running_sum(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        inputs: &[Self::Num],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "running sum",
+            |mut region| {
+                let mut acc = region.assign_advice_from_constant(
+                    || "zero",
+                    config.advice[0],
+                    0,
+                    Fp::zero(),
+                )?;
+                let mut prefixes = Vec::with_capacity(inputs.len());
+                for (i, input) in inputs.iter().enumerate() {
+                    config.s_add.enable(&mut region, i)?;
+                    input.copy_advice(|| "input", &mut region, config.advice[1], i)?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| input.value().map(|input| *acc + *input));
+                    acc = region.assign_advice(
+                        || format!("prefix {}", i),
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                    prefixes.push(acc.clone());
+                }
+                Ok(prefixes)
+            },
+        )
+    }
+
+    fn product(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        values: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        let (first, rest) = match values.split_first() {
+            Some(split) => split,
+            None => return self.load_constant(layouter.namespace(|| "empty product"), Fp::one()),
+        };
+        layouter.assign_region(
+            || "product",
+            |mut region| {
+                let mut acc = first.copy_advice(|| "first", &mut region, config.advice[0], 0)?;
+                // chained mul rows, the running product lives in advice[0]
+                for (i, value) in rest.iter().enumerate() {
+                    config.s_mul.enable(&mut region, i)?;
+                    value.copy_advice(|| "value", &mut region, config.advice[1], i)?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| value.value().map(|value| *acc * *value));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+
+    fn running_product(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        inputs: &[Self::Num],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "running product",
+            |mut region| {
+                let mut acc =
+                    region.assign_advice_from_constant(|| "one", config.advice[0], 0, Fp::one())?;
+                let mut prefixes = Vec::with_capacity(inputs.len());
+                for (i, input) in inputs.iter().enumerate() {
+                    config.s_mul.enable(&mut region, i)?;
+                    input.copy_advice(|| "input", &mut region, config.advice[1], i)?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| input.value().map(|input| *acc * *input));
+                    acc = region.assign_advice(
+                        || format!("prefix {}", i),
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                    prefixes.push(acc.clone());
+                }
+                Ok(prefixes)
+            },
+        )
+    }
+
+    fn mul_many(
+        &self,
+        layouter: impl Layouter<Fp>,
+        values: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        // product already lays the chain out in a single region
+        self.product(layouter, values)
+    }
+
+    fn dot_product(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: &[Self::Num],
+        b: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        if a.len() != b.len() {
+            return Err(Error::Synthesis);
+        }
+        layouter.assign_region(
+            || "dot product",
+            |mut region| {
+                let mut acc = region.assign_advice_from_constant(
+                    || "zero",
+                    config.advice[0],
+                    0,
+                    Fp::zero(),
+                )?;
+                for (i, (a, b)) in a.iter().zip(b.iter()).enumerate() {
+                    config.s_dot.enable(&mut region, i)?;
+                    a.copy_advice(|| "a", &mut region, config.advice[1], i)?;
+                    b.copy_advice(|| "b", &mut region, config.advice[2], i)?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| a.value().and_then(|a| b.value().map(|b| *acc + *a * *b)));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+
+    fn select(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                config.s_select.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                cond.copy_advice(|| "cond", &mut region, config.advice[2], 0)?;
+                let v = cond.value().and_then(|cond| {
+                    a.value()
+                        .and_then(|a| b.value().map(|b| *b + *cond * (*a - *b)))
+                });
+                region.assign_advice(
+                    || "cond ? a : b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn cond_add(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "cond add",
+            |mut region| {
+                config.s_cond_add.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                cond.copy_advice(|| "cond", &mut region, config.advice[2], 0)?;
+                let v = cond
+                    .value()
+                    .and_then(|cond| a.value().and_then(|a| b.value().map(|b| *a + *cond * *b)));
+                region.assign_advice(
+                    || "a + cond * b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn is_zero(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "is zero",
+            |mut region| {
+                config.s_is_zero.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                // zero has no inverse, 0 is used by convention
+                let a_inv = a.value().map(|a| a.invert().unwrap_or(Fp::zero()));
+                region.assign_advice(
+                    || "1 / a",
+                    config.advice[1],
+                    0,
+                    || a_inv.ok_or(Error::Synthesis),
+                )?;
+                let v = a.value().map(|a| {
+                    if a.is_zero_vartime() {
+                        Fp::one()
+                    } else {
+                        Fp::zero()
+                    }
+                });
+                region.assign_advice(
+                    || "a == 0",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn is_equal(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "is equal",
+            |mut region| {
+                config.s_is_eq.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let diff = a.value().and_then(|a| b.value().map(|b| *a - *b));
+                let diff_inv = diff.map(|diff| diff.invert().unwrap_or(Fp::zero()));
+                region.assign_advice(
+                    || "1 / (a - b)",
+                    config.advice[2],
+                    0,
+                    || diff_inv.ok_or(Error::Synthesis),
+                )?;
+                let v = diff.map(|diff| {
+                    if diff.is_zero_vartime() {
+                        Fp::one()
+                    } else {
+                        Fp::zero()
+                    }
+                });
+                region.assign_advice(
+                    || "a == b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn cond_swap(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        cond: Self::Num,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "cond swap",
+            |mut region| {
+                config.s_swap.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                cond.copy_advice(|| "cond", &mut region, config.advice[2], 0)?;
+                let swap = cond.value().map(|cond| !cond.is_zero_vartime());
+                let (a_v, b_v) = match swap {
+                    Some(true) => (b.value(), a.value()),
+                    Some(false) => (a.value(), b.value()),
+                    None => (None, None),
+                };
+                let a_out = region.assign_advice(
+                    || "a out",
+                    config.advice[0],
+                    1,
+                    || a_v.copied().ok_or(Error::Synthesis),
+                )?;
+                let b_out = region.assign_advice(
+                    || "b out",
+                    config.advice[1],
+                    1,
+                    || b_v.copied().ok_or(Error::Synthesis),
+                )?;
+                Ok((a_out, b_out))
+            },
+        )
+    }
+
+    fn and(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "and",
+            |mut region| {
+                config.s_and.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let v = a.value().and_then(|a| b.value().map(|b| *a * *b));
+                region.assign_advice(
+                    || "a & b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn or(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "or",
+            |mut region| {
+                config.s_or.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let v = a.value().and_then(|a| b.value().map(|b| *a + *b - *a * *b));
+                region.assign_advice(
+                    || "a | b",
+                    config.advice[0],
+                    1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+
+    fn not(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "not",
+            |mut region| {
+                config.s_not.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                let v = a.value().map(|a| Fp::one() - *a);
+                region.assign_advice(|| "!a", config.advice[0], 1, || v.ok_or(Error::Synthesis))
+            },
+        )
+    }
+
+    fn decompose(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        n_bits: usize,
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+        // 255 bits can already wrap around the field, so the sum would not pin a down
+        if n_bits > MAX_BITS {
+            return Err(Error::Synthesis);
+        }
+        let bits = match a.value() {
+            Some(a) => {
+                let bits = to_bits(a);
+                if bits[n_bits..].iter().any(|bit| *bit) {
+                    return Err(Error::Synthesis);
+                }
+                bits[..n_bits].iter().map(|bit| Some(*bit)).collect()
+            }
+            None => vec![None; n_bits],
+        };
+        layouter.assign_region(
+            || "decompose",
+            |mut region| {
+                let mut acc = region.assign_advice_from_constant(
+                    || "zero",
+                    config.advice[0],
+                    0,
+                    Fp::zero(),
+                )?;
+                let mut k = Fp::one();
+                let mut cells = Vec::with_capacity(n_bits);
+                for (i, bit) in bits.iter().enumerate() {
+                    config.s_bits.enable(&mut region, i)?;
+                    let bit = bit.map(|bit| if bit { Fp::one() } else { Fp::zero() });
+                    let cell = region.assign_advice(
+                        || format!("bit {}", i),
+                        config.advice[1],
+                        i,
+                        || bit.ok_or(Error::Synthesis),
+                    )?;
+                    region.assign_fixed(|| "2^i", config.coeff, i, || Ok(k))?;
+                    let v = acc.value().and_then(|acc| bit.map(|bit| *acc + k * bit));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                    cells.push(cell);
+                    k = k.double();
+                }
+                // the recomposed value must be a itself
+                region.constrain_equal(acc.cell(), a.cell())?;
+                Ok(cells)
+            },
+        )
+    }
+
+    fn assert_fits(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        n_bits: usize,
+    ) -> Result<(), Error> {
+        // the decompose region already recomposes the bits and ties the sum to a,
+        // so there is nothing left to pack
+        self.decompose(layouter, a, n_bits)?;
+        Ok(())
+    }
+
+    fn pack_bits(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        bits: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        // same bound as decompose, more bits could wrap around the field
+        if bits.len() > MAX_BITS {
+            return Err(Error::Synthesis);
+        }
+        layouter.assign_region(
+            || "pack bits",
+            |mut region| {
+                let mut acc = region.assign_advice_from_constant(
+                    || "zero",
+                    config.advice[0],
+                    0,
+                    Fp::zero(),
+                )?;
+                for (i, bit) in bits.iter().rev().enumerate() {
+                    config.s_pack.enable(&mut region, i)?;
+                    bit.copy_advice(|| format!("bit {}", i), &mut region, config.advice[1], i)?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| bit.value().map(|bit| acc.double() + *bit));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+
+    fn shl(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let bits = self.decompose(layouter.namespace(|| "bits"), a, n_bits)?;
+        if k >= n_bits {
+            return self.load_constant(layouter.namespace(|| "zero"), Fp::zero());
+        }
+        let kept = self.pack_bits(layouter.namespace(|| "kept bits"), &bits[..n_bits - k])?;
+        if k == 0 {
+            return Ok(kept);
+        }
+        let shift = Fp::from(2).pow_vartime([k as u64]);
+        self.mul_by_constant(layouter.namespace(|| "* 2^k"), kept, shift)
+    }
+
+    fn shr(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let bits = self.decompose(layouter.namespace(|| "bits"), a, n_bits)?;
+        if k >= n_bits {
+            return self.load_constant(layouter.namespace(|| "zero"), Fp::zero());
+        }
+        self.pack_bits(layouter.namespace(|| "kept bits"), &bits[k..])
+    }
+
+    fn rotl(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let k = k.checked_rem(n_bits).unwrap_or(0);
+        let mut bits = self.decompose(layouter.namespace(|| "bits"), a, n_bits)?;
+        // little endian, so rotating left moves the top k bits to the bottom
+        bits.rotate_right(k);
+        self.pack_bits(layouter.namespace(|| "rotated bits"), &bits)
+    }
+
+    fn rotr(
+        &self,
+        layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: usize,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let k = k.checked_rem(n_bits).unwrap_or(0);
+        self.rotl(layouter, a, n_bits - k, n_bits)
+    }
+
+    fn range_check(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        bits: u8,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        let bits = bits as usize;
+        let n_chunks = bits.div_ceil(RANGE_TABLE_BITS);
+        // width of the most significant chunk
+        let top_bits = bits - RANGE_TABLE_BITS * n_chunks.saturating_sub(1);
+        // chunks of the low bits, most significant first. a value that doesn't fit still gets
+        // its low bits assigned, so the recomposition below fails instead of the synthesis
+        let chunks: Vec<Option<Fp>> = (0..n_chunks)
+            .rev()
+            .map(|i| {
+                a.value().map(|a| {
+                    let bits = to_bits(a);
+                    let width = if i == n_chunks - 1 {
+                        top_bits
+                    } else {
+                        RANGE_TABLE_BITS
+                    };
+                    let chunk = bits[i * RANGE_TABLE_BITS..i * RANGE_TABLE_BITS + width]
+                        .iter()
+                        .rev()
+                        .fold(0u64, |acc, bit| acc * 2 + *bit as u64);
+                    Fp::from(chunk)
+                })
+            })
+            .collect();
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                let mut offset = 0;
+                if n_chunks > 0 && top_bits < RANGE_TABLE_BITS {
+                    let k = Fp::from(1 << (RANGE_TABLE_BITS - top_bits));
+                    config.s_range.enable(&mut region, 0)?;
+                    config.s_range_shift.enable(&mut region, 0)?;
+                    region.assign_fixed(|| "shift", config.coeff, 0, || Ok(k))?;
+                    let shifted = chunks[0].map(|chunk| chunk * k);
+                    region.assign_advice(
+                        || "shifted top chunk",
+                        config.advice[1],
+                        0,
+                        || shifted.ok_or(Error::Synthesis),
+                    )?;
+                    offset = 1;
+                }
+                let mut acc = region.assign_advice_from_constant(
+                    || "zero",
+                    config.advice[0],
+                    offset,
+                    Fp::zero(),
+                )?;
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let row = offset + i;
+                    config.s_range.enable(&mut region, row)?;
+                    config.s_range_acc.enable(&mut region, row)?;
+                    region.assign_advice(
+                        || "chunk",
+                        config.advice[1],
+                        row,
+                        || chunk.ok_or(Error::Synthesis),
+                    )?;
+                    let v = acc.value().and_then(|acc| {
+                        chunk.map(|chunk| *acc * Fp::from(1 << RANGE_TABLE_BITS) + chunk)
+                    });
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        row + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                region.constrain_equal(acc.cell(), a.cell())
+            },
+        )
+    }
+
+    fn decompose_limbs(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        limb_bits: usize,
+        n_limbs: usize,
+    ) -> Result<Vec<Self::Num>, Error> {
+        // 256 bits is allowed so a field element can be split into 4 x 64 bit limbs
+        if limb_bits == 0 || limb_bits > 255 || limb_bits * n_limbs > 256 {
+            return Err(Error::Synthesis);
+        }
+        let limbs = match a.value() {
+            Some(a) => {
+                let bits = to_bits(a);
+                if bits[limb_bits * n_limbs..].iter().any(|bit| *bit) {
+                    return Err(Error::Synthesis);
+                }
+                bits[..limb_bits * n_limbs]
+                    .chunks(limb_bits)
+                    .map(|limb| Some(from_bits(limb)))
+                    .collect()
+            }
+            None => vec![None; n_limbs],
+        };
+        let limbs = self.load_private_many(layouter.namespace(|| "limbs"), &limbs)?;
+        let base = Fp::from(2).pow_vartime([limb_bits as u64]);
+        let mut k = Fp::one();
+        let mut terms = Vec::with_capacity(n_limbs);
+        for limb in limbs.iter() {
+            terms.push((k, limb.clone()));
+            k *= base;
+        }
+        let sum = self.linear_combination(layouter.namespace(|| "recompose"), &terms)?;
+        self.assert_equal(layouter.namespace(|| "sum == a"), sum, a)?;
+        for (i, limb) in limbs.iter().enumerate() {
+            self.range_check(
+                layouter.namespace(|| format!("limb {}", i)),
+                limb.clone(),
+                limb_bits as u8,
+            )?;
+        }
+        Ok(limbs)
+    }
+
+    fn parity(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        // with half allowed 254 bits, a - 1 halved in the field is (p - 1) / 2 + a / 2 which
+        // still fits for small even a, so the bit could be flipped
+        if n_bits == 0 || n_bits > MAX_BITS {
+            return Err(Error::Synthesis);
+        }
+        let (half, bit) = match a.value() {
+            Some(a) => {
+                let bits = to_bits(a);
+                (Some(from_bits(&bits[1..])), Some(from_bits(&bits[..1])))
+            }
+            None => (None, None),
+        };
+        let out = self.load_private_many(layouter.namespace(|| "half, bit"), &[half, bit])?;
+        let (half, bit) = (out[0].clone(), out[1].clone());
+        self.assert_boolean(layouter.namespace(|| "bit"), bit.clone())?;
+        self.range_check(
+            layouter.namespace(|| "half"),
+            half.clone(),
+            (n_bits - 1) as u8,
+        )?;
+        let sum = self.linear_combination(
+            layouter.namespace(|| "2 * half + bit"),
+            &[(Fp::from(2), half), (Fp::one(), bit.clone())],
+        )?;
+        self.assert_equal(layouter.namespace(|| "sum == a"), sum, a)?;
+        Ok(bit)
+    }
+
+    fn lt(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        // a - b + 2^n lands in [2^n, 2^(n + 1)) when a >= b and below 2^n when a < b,
+        // so bit n of it is the answer to a >= b
+        let diff = self.sub(layouter.namespace(|| "a - b"), a, b)?;
+        let offset = Fp::from(2).pow_vartime([n_bits as u64]);
+        let shifted = self.add_constant(layouter.namespace(|| "a - b + 2^n"), diff, offset)?;
+        let bits = self.decompose(layouter.namespace(|| "bits"), shifted, n_bits + 1)?;
+        self.not(layouter.namespace(|| "a < b"), bits[n_bits].clone())
+    }
+
+    fn min(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let a_lt_b = self.lt(layouter.namespace(|| "a < b"), a.clone(), b.clone(), n_bits)?;
+        self.select(layouter.namespace(|| "min"), a_lt_b, a, b)
+    }
+
+    fn max(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let a_lt_b = self.lt(layouter.namespace(|| "a < b"), a.clone(), b.clone(), n_bits)?;
+        self.select(layouter.namespace(|| "max"), a_lt_b, b, a)
+    }
+
+    fn abs_diff(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        let a_lt_b = self.lt(layouter.namespace(|| "a < b"), a.clone(), b.clone(), n_bits)?;
+        // put the larger value first, then the difference can't go negative
+        let (hi, lo) = self.cond_swap(layouter.namespace(|| "order"), a_lt_b, a, b)?;
+        self.sub(layouter.namespace(|| "hi - lo"), hi, lo)
+    }
+
+    fn add_u64(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        self.range_check(layouter.namespace(|| "a < 2^64"), a.clone(), 64)?;
+        self.range_check(layouter.namespace(|| "b < 2^64"), b.clone(), 64)?;
+        let (sum, carry) = match (a.value(), b.value()) {
+            (Some(a), Some(b)) => {
+                let (sum, carry) = to_u64(a).overflowing_add(to_u64(b));
+                (Some(Fp::from(sum)), Some(Fp::from(carry as u64)))
+            }
+            _ => (None, None),
+        };
+        let out = self.load_private_many(layouter.namespace(|| "sum, carry"), &[sum, carry])?;
+        let (sum, carry) = (out[0].clone(), out[1].clone());
+        self.range_check(layouter.namespace(|| "sum < 2^64"), sum.clone(), 64)?;
+        self.assert_boolean(layouter.namespace(|| "carry"), carry.clone())?;
+        let total = self.add(layouter.namespace(|| "a + b"), a, b)?;
+        let wrapped = self.linear_combination(
+            layouter.namespace(|| "sum + carry * 2^64"),
+            &[
+                (Fp::one(), sum.clone()),
+                (Fp::from_u128(1 << 64), carry.clone()),
+            ],
+        )?;
+        self.assert_equal(layouter.namespace(|| "no overflow lost"), total, wrapped)?;
+        Ok((sum, carry))
+    }
+
+    fn divrem_u64(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(Self::Num, Self::Num), Error> {
+        let (q, r) = match (a.value(), b.value()) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (to_u64(a), to_u64(b));
+                if b == 0 {
+                    return Err(Error::Synthesis);
+                }
+                (Some(Fp::from(a / b)), Some(Fp::from(a % b)))
+            }
+            _ => (None, None),
+        };
+        let out = self.load_private_many(layouter.namespace(|| "q, r"), &[q, r])?;
+        let (q, r) = (out[0].clone(), out[1].clone());
+        self.range_check(layouter.namespace(|| "a < 2^64"), a.clone(), 64)?;
+        self.range_check(layouter.namespace(|| "b < 2^64"), b.clone(), 64)?;
+        self.range_check(layouter.namespace(|| "q < 2^64"), q.clone(), 64)?;
+        self.range_check(layouter.namespace(|| "r < 2^64"), r.clone(), 64)?;
+        // all of it is below 2^128 + 2^64, so q * b + r can't wrap around the field
+        let qb_r = self.mul_add(
+            layouter.namespace(|| "q * b + r"),
+            q.clone(),
+            b.clone(),
+            r.clone(),
+        )?;
+        self.assert_equal(layouter.namespace(|| "q * b + r == a"), qb_r, a)?;
+        let r_lt_b = self.lt(layouter.namespace(|| "r < b"), r.clone(), b, 64)?;
+        self.assert_equals_constant(layouter.namespace(|| "r < b holds"), r_lt_b, Fp::one())?;
+        Ok((q, r))
+    }
+
+    fn isqrt(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        n: Self::Num,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        if n_bits > 64 {
+            return Err(Error::Synthesis);
+        }
+        let s = n.value().map(|n| Fp::from(to_u64(n).isqrt()));
+        let s = self.load_private(layouter.namespace(|| "s"), s)?;
+        self.range_check(layouter.namespace(|| "n"), n.clone(), n_bits as u8)?;
+        // keeps s * s and (s + 1) * (s + 1) within n_bits + 1 bits for the comparisons
+        self.range_check(
+            layouter.namespace(|| "s"),
+            s.clone(),
+            n_bits.div_ceil(2) as u8,
+        )?;
+        let s_sq = self.square(layouter.namespace(|| "s * s"), s.clone())?;
+        let s_next = self.add_constant(layouter.namespace(|| "s + 1"), s.clone(), Fp::one())?;
+        let s_next_sq = self.square(layouter.namespace(|| "(s + 1)^2"), s_next)?;
+        let too_big = self.lt(
+            layouter.namespace(|| "n < s * s"),
+            n.clone(),
+            s_sq,
+            n_bits + 2,
+        )?;
+        let below = self.lt(
+            layouter.namespace(|| "n < (s + 1)^2"),
+            n,
+            s_next_sq,
+            n_bits + 2,
+        )?;
+        self.assert_equals_constant(layouter.namespace(|| "s * s <= n"), too_big, Fp::zero())?;
+        self.assert_equals_constant(layouter.namespace(|| "n < (s + 1)^2"), below, Fp::one())?;
+        Ok(s)
+    }
+
+    fn mul_fixed_point(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+        scale_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        if scale_bits > 64 {
+            return Err(Error::Synthesis);
+        }
+        let (out, rem) = match (a.value(), b.value()) {
+            (Some(a), Some(b)) => {
+                let product = to_u64(a) as u128 * to_u64(b) as u128;
+                let rem = product & ((1u128 << scale_bits) - 1);
+                (
+                    Some(Fp::from_u128(product >> scale_bits)),
+                    Some(Fp::from_u128(rem)),
+                )
+            }
+            _ => (None, None),
+        };
+        self.range_check(layouter.namespace(|| "a < 2^64"), a.clone(), 64)?;
+        self.range_check(layouter.namespace(|| "b < 2^64"), b.clone(), 64)?;
+        let witness = self.load_private_many(layouter.namespace(|| "out, rem"), &[out, rem])?;
+        let (out, rem) = (witness[0].clone(), witness[1].clone());
+        // out has to be bounded too, otherwise it could absorb a rem that wraps the field
+        self.range_check(layouter.namespace(|| "out < 2^128"), out.clone(), 128)?;
+        self.range_check(
+            layouter.namespace(|| "rem < 2^scale"),
+            rem.clone(),
+            scale_bits as u8,
+        )?;
+        let product = self.mul(layouter.namespace(|| "a * b"), a, b)?;
+        let shift = Fp::from_u128(1 << scale_bits);
+        let recomposed = self.linear_combination(
+            layouter.namespace(|| "out * 2^scale + rem"),
+            &[(shift, out.clone()), (Fp::one(), rem)],
+        )?;
+        self.assert_equal(
+            layouter.namespace(|| "a * b == out * 2^scale + rem"),
+            product,
+            recomposed,
+        )?;
+        Ok(out)
+    }
+
+    fn mod_const(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        m: u64,
+        n_bits: usize,
+    ) -> Result<Self::Num, Error> {
+        if m == 0 || n_bits > 64 {
+            return Err(Error::Synthesis);
+        }
+        // a can be up to 2^64 * m, so the witness is worked out on the low 128 bits
+        let (q, r) = match a.value() {
+            Some(a) => {
+                let (a, m) = (a.get_lower_128(), m as u128);
+                (Some(Fp::from_u128(a / m)), Some(Fp::from_u128(a % m)))
+            }
+            None => (None, None),
+        };
+        let witness = self.load_private_many(layouter.namespace(|| "q, r"), &[q, r])?;
+        let (q, r) = (witness[0].clone(), witness[1].clone());
+        self.range_check(layouter.namespace(|| "q"), q.clone(), n_bits as u8)?;
+        // r < m alone would still let r wrap around to a negative value
+        self.range_check(layouter.namespace(|| "r < 2^64"), r.clone(), 64)?;
+        let qm = self.mul_by_constant(layouter.namespace(|| "q * m"), q, Fp::from(m))?;
+        let qm_r = self.add(layouter.namespace(|| "q * m + r"), qm, r.clone())?;
+        self.assert_equal(layouter.namespace(|| "q * m + r == a"), qm_r, a)?;
+        let m = self.load_constant(layouter.namespace(|| "m"), Fp::from(m))?;
+        let r_lt_m = self.lt(layouter.namespace(|| "r < m"), r.clone(), m, 64)?;
+        self.assert_equals_constant(layouter.namespace(|| "r < m holds"), r_lt_m, Fp::one())?;
+        Ok(r)
+    }
+
+    fn to_signed(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        magnitude: Self::Num,
+        sign_bit: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        self.range_check(layouter.namespace(|| "magnitude"), magnitude.clone(), 64)?;
+        let negated = self.neg(layouter.namespace(|| "-magnitude"), magnitude.clone())?;
+        // the select gate also makes sign_bit boolean
+        self.select(
+            layouter.namespace(|| "signed"),
+            sign_bit,
+            negated,
+            magnitude,
+        )
+    }
+
+    fn signed_lt(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        // shifting by 2^64 moves [-2^64, 2^64) onto the unsigned range [0, 2^65)
+        let offset = Fp::from_u128(1 << 64);
+        let a = self.add_constant(layouter.namespace(|| "a + 2^64"), a, offset)?;
+        let b = self.add_constant(layouter.namespace(|| "b + 2^64"), b, offset)?;
+        self.range_check(layouter.namespace(|| "a in range"), a.clone(), 65)?;
+        self.range_check(layouter.namespace(|| "b in range"), b.clone(), 65)?;
+        self.lt(layouter.namespace(|| "a < b"), a, b, 65)
+    }
+
+    fn eval_poly(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        coeffs: &[Fp],
+        x: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+        let (leading, rest) = match coeffs.split_last() {
+            Some(split) => split,
+            None => return self.load_constant(layouter.namespace(|| "empty poly"), Fp::zero()),
+        };
+        layouter.assign_region(
+            || "eval poly",
+            |mut region| {
+                // start from the leading coefficient and fold in the rest from the top
+                let mut acc = region.assign_advice_from_constant(
+                    || "leading",
+                    config.advice[0],
+                    0,
+                    *leading,
+                )?;
+                for (i, c) in rest.iter().rev().enumerate() {
+                    config.s_horner.enable(&mut region, i)?;
+                    x.copy_advice(|| "x", &mut region, config.advice[1], i)?;
+                    region.assign_fixed(|| "c", config.coeff, i, || Ok(*c))?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| x.value().map(|x| *acc * *x + *c));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+
+    fn powers(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        x: Self::Num,
+        n: usize,
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+        if n == 0 {
+            return Ok(vec![]);
+        }
+        layouter.assign_region(
+            || "powers",
+            |mut region| {
+                let mut acc = x.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                let mut powers = vec![acc.clone()];
+                // same chained mul rows as product, multiplying by x every row
+                for i in 0..n - 1 {
+                    config.s_mul.enable(&mut region, i)?;
+                    x.copy_advice(|| "x", &mut region, config.advice[1], i)?;
+                    let v = acc.value().and_then(|acc| x.value().map(|x| *acc * *x));
+                    acc = region.assign_advice(
+                        || format!("x^{}", i + 2),
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                    powers.push(acc.clone());
+                }
+                Ok(powers)
+            },
+        )
+    }
+
+    fn neg(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Self::Num, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "neg",
+            |mut region| {
+                config.s_neg.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                let v = a.value().map(|a| -*a);
+                region.assign_advice(|| "-a", config.advice[0], 1, || v.ok_or(Error::Synthesis))
+            },
+        )
+    }
+
+    fn assert_equal(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        // no gate is needed, the permutation argument ties the two cells together.
+        // a is copied in so the region occupies a row and failures can be located in it
+        layouter.assign_region(
+            || "assert equal",
+            |mut region| {
+                let a = a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                region.constrain_equal(a.cell(), b.cell())
+            },
+        )
+    }
+
+    fn assert_equals_constant(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: Self::Num,
+        k: Fp,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "assert equals constant",
+            |mut region| {
+                config.s_eq_const.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                region.assign_fixed(|| "k", config.coeff, 0, || Ok(k))?;
+                Ok(())
+            },
+        )
+    }
+
+    fn assert_nonzero(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<(), Error> {
+        let config = self.config();
+        let a_inv = match a.value() {
+            Some(a) => Some(Option::<Fp>::from(a.invert()).ok_or(Error::Synthesis)?),
+            None => None,
+        };
+        layouter.assign_region(
+            || "assert nonzero",
+            |mut region| {
+                config.s_nonzero.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                region.assign_advice(
+                    || "1 / a",
+                    config.advice[1],
+                    0,
+                    || a_inv.ok_or(Error::Synthesis),
+                )?;
+                Ok(())
+            },
+        )
+    }
+
+    fn batch_invert(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        values: &[Self::Num],
+    ) -> Result<Vec<Self::Num>, Error> {
+        let config = self.config();
+        if values.is_empty() {
+            return Ok(vec![]);
+        }
+        // montgomery's trick, invert the product of everything once and walk back down
+        // the prefix products to peel off each inverse
+        let inverses = match values
+            .iter()
+            .map(|v| v.value().copied())
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(values) => {
+                let mut prefix = Vec::with_capacity(values.len());
+                let mut acc = Fp::one();
+                for v in values.iter() {
+                    prefix.push(acc);
+                    acc *= v;
+                }
+                let mut inv = Option::<Fp>::from(acc.invert()).ok_or(Error::Synthesis)?;
+                let mut inverses = vec![Fp::zero(); values.len()];
+                for i in (0..values.len()).rev() {
+                    inverses[i] = inv * prefix[i];
+                    inv *= values[i];
+                }
+                inverses.into_iter().map(Some).collect()
+            }
+            None => vec![None; values.len()],
+        };
+        layouter.assign_region(
+            || "batch invert",
+            |mut region| {
+                let mut cells = Vec::with_capacity(values.len());
+                for (i, (v, inv)) in values.iter().zip(inverses.iter()).enumerate() {
+                    config.s_nonzero.enable(&mut region, i)?;
+                    v.copy_advice(|| "v", &mut region, config.advice[0], i)?;
+                    cells.push(region.assign_advice(
+                        || "1 / v",
+                        config.advice[1],
+                        i,
+                        || inv.ok_or(Error::Synthesis),
+                    )?);
+                }
+                Ok(cells)
+            },
+        )
+    }
+
+    fn assert_boolean(&self, layouter: impl Layouter<Fp>, a: Self::Num) -> Result<(), Error> {
+        self.into_bit(layouter, a)?;
+        Ok(())
+    }
+
+    fn into_bit(&self, mut layouter: impl Layouter<Fp>, a: Self::Num) -> Result<Bit, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "assert boolean",
+            |mut region| {
+                config.s_bool.enable(&mut region, 0)?;
+                let bit = a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                Ok(Bit(bit))
+            },
+        )
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        layouter.constrain_instance(num.cell(), config.instance, row)
+    }
+
+    fn expose_public_many(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        cells: &[Self::Num],
+        start_row: usize,
+    ) -> Result<usize, Error> {
+        // rows only repeat if they wrap around, so that is the one case to refuse
+        let end_row = start_row.checked_add(cells.len()).ok_or(Error::Synthesis)?;
+        for (i, cell) in cells.iter().enumerate() {
+            self.expose_public(
+                layouter.namespace(|| format!("row {}", start_row + i)),
+                cell.clone(),
+                start_row + i,
+            )?;
+        }
+        Ok(end_row)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MyConfig {
+    pub advice: [Column<Advice>; 4],
+    pub instance: Column<Instance>,
+    // fixed column holding per row coefficients for the constant gates
+    pub coeff: Column<Fixed>,
+    // selectors to define the rule of we want multiplication selector or addition selector
+    pub s_mul: Selector,
+    pub s_add: Selector,
+    pub s_sub: Selector,
+    pub s_neg: Selector,
+    pub s_div: Selector,
+    pub s_square: Selector,
+    pub s_mulc: Selector,
+    pub s_addc: Selector,
+    pub s_muladd: Selector,
+    pub s_lc: Selector,
+    pub s_dot: Selector,
+    pub s_select: Selector,
+    pub s_is_zero: Selector,
+    pub s_is_eq: Selector,
+    pub s_nonzero: Selector,
+    pub s_swap: Selector,
+    pub s_and: Selector,
+    pub s_or: Selector,
+    pub s_not: Selector,
+    pub s_bits: Selector,
+    // shared table of all values below 2^RANGE_TABLE_BITS
+    pub byte_table: ByteTableConfig,
+    pub s_range: Selector,
+    pub s_range_acc: Selector,
+    pub s_range_shift: Selector,
+    pub s_horner: Selector,
+    pub s_double: Selector,
+    pub s_cube: Selector,
+    pub s_mma: Selector,
+    pub s_cond_add: Selector,
+    pub s_bool: Selector,
+    pub s_pack: Selector,
+    pub s_pow_step: Selector,
+    pub s_eq_const: Selector,
+}
+
+// lookup table of every (a, b, a ^ b) for 8 bit a and b, xor has no cheap arithmetic form
+// so instead of a gate the advice triple is looked up in the table
+#[derive(Clone, Debug)]
+pub struct XorConfig {
+    pub advice: [Column<Advice>; 3],
+    // complex selector since it is used inside a lookup
+    pub s_xor: Selector,
+    pub table: [TableColumn; 3],
+}
+
+#[derive(Debug)]
+pub struct XorChip {
+    config: XorConfig,
+}
+
+impl XorChip {
+    // 2^16 rows for all byte pairs
+    pub const TABLE_ROWS: usize = 1 << 16;
+
+    pub fn new(config: XorConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fp>, advice: [Column<Advice>; 3]) -> XorConfig {
+        for adv in advice.iter() {
+            meta.enable_equality(*adv);
+        }
+        let s_xor = meta.complex_selector();
+        let table = [
+            meta.lookup_table_column(),
+            meta.lookup_table_column(),
+            meta.lookup_table_column(),
+        ];
+        meta.lookup(|meta| {
+            let s_xor = meta.query_selector(s_xor);
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[2], Rotation::cur());
+            // when the selector is off the input is (0, 0, 0), which is also a row of the table
+            vec![
+                (s_xor.clone() * a, table[0]),
+                (s_xor.clone() * b, table[1]),
+                (s_xor * out, table[2]),
+            ]
+        });
+        XorConfig {
+            advice,
+            s_xor,
+            table,
+        }
+    }
+
+    // fills the table, has to be called once per circuit before the lookups can be satisfied
+    pub fn load(&self, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let config = self.config();
+        layouter.assign_table(
+            || "xor table",
+            |mut table| {
+                for a in 0..=255u64 {
+                    for b in 0..=255u64 {
+                        let offset = (a * 256 + b) as usize;
+                        table.assign_cell(|| "a", config.table[0], offset, || Ok(Fp::from(a)))?;
+                        table.assign_cell(|| "b", config.table[1], offset, || Ok(Fp::from(b)))?;
+                        table.assign_cell(
+                            || "a ^ b",
+                            config.table[2],
+                            offset,
+                            || Ok(Fp::from(a ^ b)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // xor of two bytes, a and b not being bytes makes the lookup fail
+    pub fn xor_u8(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: AssignedCell<Fp, Fp>,
+        b: AssignedCell<Fp, Fp>,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "xor",
+            |mut region| {
+                config.s_xor.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                let v = a.value().and_then(|a| {
+                    b.value().map(|b| {
+                        let a = a.get_lower_128() as u8;
+                        let b = b.get_lower_128() as u8;
+                        Fp::from((a ^ b) as u64)
+                    })
+                });
+                region.assign_advice(
+                    || "a ^ b",
+                    config.advice[2],
+                    0,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+}
+
+impl Chip<Fp> for XorChip {
+    type Config = XorConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// fibonacci sequence down a single advice column, one gate looking at three rows at once
+#[derive(Clone, Debug)]
+pub struct FibConfig {
+    pub advice: Column<Advice>,
+    pub s_fib: Selector,
+}
+
+#[derive(Debug)]
+pub struct FibChip {
+    config: FibConfig,
+}
+
+impl FibChip {
+    pub fn new(config: FibConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fp>, advice: Column<Advice>) -> FibConfig {
+        meta.enable_equality(advice);
+        let s_fib = meta.selector();
+        meta.create_gate("fibonacci", |meta| {
+            // enabled on the middle row, f(i + 1) = f(i) + f(i - 1)
+            let prev = meta.query_advice(advice, Rotation::prev());
+            let cur = meta.query_advice(advice, Rotation::cur());
+            let next = meta.query_advice(advice, Rotation::next());
+            let s_fib = meta.query_selector(s_fib);
+            vec![s_fib * (prev + cur - next)]
+        });
+        FibConfig { advice, s_fib }
+    }
+
+    // f(n) for f(0) = a and f(1) = b, the whole sequence lives in one region of n + 1 rows
+    pub fn nth(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: AssignedCell<Fp, Fp>,
+        b: AssignedCell<Fp, Fp>,
+        n: usize,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "fibonacci",
+            |mut region| {
+                let mut prev = a.copy_advice(|| "f(0)", &mut region, config.advice, 0)?;
+                if n == 0 {
+                    return Ok(prev);
+                }
+                let mut cur = b.copy_advice(|| "f(1)", &mut region, config.advice, 1)?;
+                for i in 2..=n {
+                    config.s_fib.enable(&mut region, i - 1)?;
+                    let v = prev.value().and_then(|p| cur.value().map(|c| *p + *c));
+                    let next = region.assign_advice(
+                        || format!("f({})", i),
+                        config.advice,
+                        i,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                    prev = cur;
+                    cur = next;
+                }
+                Ok(cur)
+            },
+        )
+    }
+}
+
+impl Chip<Fp> for FibChip {
+    type Config = FibConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// mimc with the cube as round function, ceil(log_3 p) rounds
+pub const MIMC_ROUNDS: usize = 161;
+
+// round constants c_i = i^7, c_0 = 0. nothing up my sleeve, only has to be fixed and distinct
+pub(crate) fn mimc_round_constants() -> Vec<Fp> {
+    (0..MIMC_ROUNDS)
+        .map(|i| Fp::from(i as u64).pow_vartime([7]))
+        .collect()
+}
+
+// native mimc, x := (x + k + c_i)^3 every round and k added once more at the end
+pub fn mimc_hash(x: Fp, k: Fp) -> Fp {
+    let x = mimc_round_constants()
+        .iter()
+        .fold(x, |x, c| (x + k + c).cube());
+    x + k
+}
+
+// mimc_hash with one row per round, the round constants are filled into a fixed column
+// and the key is carried down next to x
+#[derive(Clone, Debug)]
+pub struct MiMCConfig {
+    // x and the key
+    pub advice: [Column<Advice>; 2],
+    pub round_constants: Column<Fixed>,
+    pub s_round: Selector,
+    pub s_final: Selector,
+}
+
+#[derive(Debug)]
+pub struct MiMCChip {
+    config: MiMCConfig,
+}
+
+impl MiMCChip {
+    pub fn new(config: MiMCConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: [Column<Advice>; 2],
+        round_constants: Column<Fixed>,
+    ) -> MiMCConfig {
+        for adv in advice.iter() {
+            meta.enable_equality(*adv);
+        }
+        let s_round = meta.selector();
+        let s_final = meta.selector();
+        meta.create_gate("mimc round", |meta| {
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_fixed(round_constants, Rotation::cur());
+            let x_next = meta.query_advice(advice[0], Rotation::next());
+            let k_next = meta.query_advice(advice[1], Rotation::next());
+            let s_round = meta.query_selector(s_round);
+            let t = x + k.clone() + c;
+            vec![
+                s_round.clone() * (t.clone() * t.clone() * t - x_next),
+                // the same key on every row, cheaper than a copy constraint per row
+                s_round * (k_next - k),
+            ]
+        });
+        meta.create_gate("mimc final", |meta| {
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let k = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_final = meta.query_selector(s_final);
+            vec![s_final * (x + k - out)]
+        });
+        MiMCConfig {
+            advice,
+            round_constants,
+            s_round,
+            s_final,
+        }
+    }
+
+    // same digest as mimc_hash(x, k), MIMC_ROUNDS + 2 rows in one region
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        x: AssignedCell<Fp, Fp>,
+        k: AssignedCell<Fp, Fp>,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "mimc",
+            |mut region| {
+                let mut x_cell = x.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                let mut k_cell = k.copy_advice(|| "k", &mut region, config.advice[1], 0)?;
+                for (i, c) in mimc_round_constants().into_iter().enumerate() {
+                    config.s_round.enable(&mut region, i)?;
+                    region.assign_fixed(|| "c", config.round_constants, i, || Ok(c))?;
+                    let v = x_cell
+                        .value()
+                        .and_then(|x| k.value().map(|k| (*x + *k + c).cube()));
+                    x_cell = region.assign_advice(
+                        || format!("x {}", i + 1),
+                        config.advice[0],
+                        i + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                    k_cell = region.assign_advice(
+                        || "k",
+                        config.advice[1],
+                        i + 1,
+                        || k.value().copied().ok_or(Error::Synthesis),
+                    )?;
+                }
+                config.s_final.enable(&mut region, MIMC_ROUNDS)?;
+                let v = x_cell.value().and_then(|x| k_cell.value().map(|k| *x + *k));
+                region.assign_advice(
+                    || "digest",
+                    config.advice[0],
+                    MIMC_ROUNDS + 1,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+}
+
+impl Chip<Fp> for MiMCChip {
+    type Config = MiMCConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// byte substitution through a lookup of (input, output) pairs. the table gets a tag column,
+// 1 on the 256 real rows and 0 on an extra all zero row, since an unselected row looks up
+// (0, 0, 0) and the s-box itself doesn't have to map 0 to 0
+#[derive(Clone, Debug)]
+pub struct SboxConfig {
+    pub advice: [Column<Advice>; 2],
+    // complex selector since it is used inside a lookup
+    pub s_sbox: Selector,
+    pub table: [TableColumn; 3],
+}
+
+#[derive(Debug)]
+pub struct SboxChip {
+    config: SboxConfig,
+    sbox: [u8; 256],
+}
+
+impl SboxChip {
+    pub fn new(config: SboxConfig, sbox: [u8; 256]) -> Self {
+        Self { config, sbox }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<Fp>, advice: [Column<Advice>; 2]) -> SboxConfig {
+        for adv in advice.iter() {
+            meta.enable_equality(*adv);
+        }
+        let s_sbox = meta.complex_selector();
+        let table = [
+            meta.lookup_table_column(),
+            meta.lookup_table_column(),
+            meta.lookup_table_column(),
+        ];
+        meta.lookup(|meta| {
+            let s_sbox = meta.query_selector(s_sbox);
+            let input = meta.query_advice(advice[0], Rotation::cur());
+            let output = meta.query_advice(advice[1], Rotation::cur());
+            vec![
+                (s_sbox.clone(), table[0]),
+                (s_sbox.clone() * input, table[1]),
+                (s_sbox * output, table[2]),
+            ]
+        });
+        SboxConfig {
+            advice,
+            s_sbox,
+            table,
+        }
+    }
+
+    // fills the table from the s-box given to new, once per circuit before any sbox call
+    pub fn load(&self, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let config = self.config();
+        layouter.assign_table(
+            || "sbox table",
+            |mut table| {
+                table.assign_cell(|| "tag", config.table[0], 0, || Ok(Fp::zero()))?;
+                table.assign_cell(|| "in", config.table[1], 0, || Ok(Fp::zero()))?;
+                table.assign_cell(|| "out", config.table[2], 0, || Ok(Fp::zero()))?;
+                for (i, out) in self.sbox.iter().enumerate() {
+                    let offset = i + 1;
+                    table.assign_cell(|| "tag", config.table[0], offset, || Ok(Fp::one()))?;
+                    table.assign_cell(
+                        || "in",
+                        config.table[1],
+                        offset,
+                        || Ok(Fp::from(i as u64)),
+                    )?;
+                    table.assign_cell(
+                        || "out",
+                        config.table[2],
+                        offset,
+                        || Ok(Fp::from(*out as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // sbox[a], a not being a byte makes the lookup fail
+    pub fn sbox(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: AssignedCell<Fp, Fp>,
+    ) -> Result<AssignedCell<Fp, Fp>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "sbox",
+            |mut region| {
+                config.s_sbox.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                let v = a
+                    .value()
+                    .map(|a| Fp::from(self.sbox[a.get_lower_128() as u8 as usize] as u64));
+                region.assign_advice(
+                    || "sbox[a]",
+                    config.advice[1],
+                    0,
+                    || v.ok_or(Error::Synthesis),
+                )
+            },
+        )
+    }
+}
+
+impl Chip<Fp> for SboxChip {
+    type Config = SboxConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// range checks against a table of 0..2^bits where the width is picked when configuring,
+// a wider table costs a bigger k but fewer chunk rows per check
+#[derive(Clone, Debug)]
+pub struct RangeConfig {
+    // accumulator and chunk columns
+    pub advice: [Column<Advice>; 2],
+    pub coeff: Column<Fixed>,
+    // complex selector since it is used inside a lookup
+    pub s_lookup: Selector,
+    pub s_acc: Selector,
+    pub s_shift: Selector,
+    pub table: TableColumn,
+    pub bits: usize,
+}
+
+#[derive(Debug)]
+pub struct RangeChip {
+    config: RangeConfig,
+}
+
+impl RangeChip {
+    pub fn new(config: RangeConfig) -> Self {
+        Self { config }
+    }
+
+    // the accumulator starts from a constant zero, so the circuit needs a constant column.
+    // bits outside 1..MAX_K has no table to build, load and range_check_wide refuse it
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        advice: [Column<Advice>; 2],
+        coeff: Column<Fixed>,
+        bits: usize,
+    ) -> RangeConfig {
+        for adv in advice.iter() {
+            meta.enable_equality(*adv);
+        }
+        let s_lookup = meta.complex_selector();
+        let s_acc = meta.selector();
+        let s_shift = meta.selector();
+        let table = meta.lookup_table_column();
+        meta.lookup(|meta| {
+            let s_lookup = meta.query_selector(s_lookup);
+            let chunk = meta.query_advice(advice[1], Rotation::cur());
+            vec![(s_lookup * chunk, table)]
+        });
+        meta.create_gate("wide range check", |meta| {
+            // same layout as the byte range check, acc_next = acc * 2^bits + chunk
+            let acc = meta.query_advice(advice[0], Rotation::cur());
+            let chunk = meta.query_advice(advice[1], Rotation::cur());
+            let acc_next = meta.query_advice(advice[0], Rotation::next());
+            let s_acc = meta.query_selector(s_acc);
+            let radix = Expression::Constant(Fp::from(2).pow_vartime([bits as u64]));
+            vec![s_acc * (acc * radix - acc_next + chunk)]
+        });
+        meta.create_gate("wide range check shift", |meta| {
+            // narrower top chunk, shifted up by k = 2^(bits - width) and looked up again
+            let shifted = meta.query_advice(advice[1], Rotation::cur());
+            let k = meta.query_fixed(coeff, Rotation::cur());
+            let chunk = meta.query_advice(advice[1], Rotation::next());
+            let s_shift = meta.query_selector(s_shift);
+            vec![s_shift * (chunk * k - shifted)]
+        });
+        RangeConfig {
+            advice,
+            coeff,
+            s_lookup,
+            s_acc,
+            s_shift,
+            table,
+            bits,
+        }
+    }
+
+    // a zero width table holds no chunk, and its 2^bits rows have to fit in 2^MAX_K
+    fn check_width(&self) -> Result<(), Error> {
+        let bits = self.config().bits;
+        if bits == 0 || bits >= MAX_K as usize {
+            return Err(Error::Synthesis);
+        }
+        Ok(())
+    }
+
+    // fills the 2^bits rows of the table, once per circuit before any check. a width outside
+    // 1..MAX_K is a synthesis error
+    pub fn load(&self, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        self.check_width()?;
+        let config = self.config();
+        layouter.assign_table(
+            || "range table",
+            |mut table| {
+                for v in 0..(1 << config.bits) {
+                    table.assign_cell(|| "value", config.table, v, || Ok(Fp::from(v as u64)))?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // constrains a < 2^total_bits in chunks of the table width, total_bits > 255 is a synthesis error
+    pub fn range_check_wide(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: AssignedCell<Fp, Fp>,
+        total_bits: usize,
+    ) -> Result<(), Error> {
+        self.check_width()?;
+        let config = self.config();
+        if total_bits > 255 {
+            return Err(Error::Synthesis);
+        }
+        let width = config.bits;
+        let n_chunks = total_bits.div_ceil(width);
+        let top_bits = total_bits - width * n_chunks.saturating_sub(1);
+        // most significant chunk first, only the low total_bits are assigned so a value
+        // that doesn't fit fails the recomposition
+        let chunks: Vec<Option<Fp>> = (0..n_chunks)
+            .rev()
+            .map(|i| {
+                a.value().map(|a| {
+                    let bits = to_bits(a);
+                    let w = if i == n_chunks - 1 { top_bits } else { width };
+                    from_bits(&bits[i * width..i * width + w])
+                })
+            })
+            .collect();
+        layouter.assign_region(
+            || "wide range check",
+            |mut region| {
+                let mut offset = 0;
+                if n_chunks > 0 && top_bits < width {
+                    let k = Fp::from(2).pow_vartime([(width - top_bits) as u64]);
+                    config.s_lookup.enable(&mut region, 0)?;
+                    config.s_shift.enable(&mut region, 0)?;
+                    region.assign_fixed(|| "shift", config.coeff, 0, || Ok(k))?;
+                    let shifted = chunks[0].map(|chunk| chunk * k);
+                    region.assign_advice(
+                        || "shifted top chunk",
+                        config.advice[1],
+                        0,
+                        || shifted.ok_or(Error::Synthesis),
+                    )?;
+                    offset = 1;
+                }
+                let mut acc = region.assign_advice_from_constant(
+                    || "zero",
+                    config.advice[0],
+                    offset,
+                    Fp::zero(),
+                )?;
+                let radix = Fp::from(2).pow_vartime([width as u64]);
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let row = offset + i;
+                    config.s_lookup.enable(&mut region, row)?;
+                    config.s_acc.enable(&mut region, row)?;
+                    region.assign_advice(
+                        || "chunk",
+                        config.advice[1],
+                        row,
+                        || chunk.ok_or(Error::Synthesis),
+                    )?;
+                    let v = acc
+                        .value()
+                        .and_then(|acc| chunk.map(|chunk| *acc * radix + chunk));
+                    acc = region.assign_advice(
+                        || "acc",
+                        config.advice[0],
+                        row + 1,
+                        || v.ok_or(Error::Synthesis),
+                    )?;
+                }
+                region.constrain_equal(acc.cell(), a.cell())
+            },
+        )
+    }
+}
+
+impl Chip<Fp> for RangeChip {
+    type Config = RangeConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// 256 bit integer as four little endian 64 bit limbs
+#[derive(Clone, Debug)]
+pub struct U256 {
+    pub limbs: [AssignedCell<Fp, Fp>; 4],
+}
+
+// non native 256 bit arithmetic, no gates of its own, everything goes through MyChip's
+// instructions on the same columns. limbs are range checked to 64 bits with the byte table
+#[derive(Debug)]
+pub struct U256Chip {
+    chip: MyChip,
+}
+
+impl U256Chip {
+    pub fn new(config: MyConfig) -> Self {
+        Self {
+            chip: MyChip::new(config),
+        }
+    }
+
+    // loads four private limbs, each range checked to 64 bits
+    pub fn load(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        value: Option<[u64; 4]>,
+    ) -> Result<U256, Error> {
+        let limbs: Vec<Option<Fp>> = (0..4).map(|i| value.map(|v| Fp::from(v[i]))).collect();
+        let limbs = self
+            .chip
+            .load_private_many(layouter.namespace(|| "limbs"), &limbs)?;
+        for (i, limb) in limbs.iter().enumerate() {
+            self.chip.range_check(
+                layouter.namespace(|| format!("limb {}", i)),
+                limb.clone(),
+                64,
+            )?;
+        }
+        let limbs = limbs.try_into().map_err(|_| Error::Synthesis)?;
+        Ok(U256 { limbs })
+    }
+
+    // a + b mod 2^256 and the overflow bit. per limb a_i + b_i + carry_in = sum_i + carry_out * 2^64,
+    // with every sum limb range checked and every carry boolean
+    pub fn add_u256(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        a: &U256,
+        b: &U256,
+    ) -> Result<(U256, AssignedCell<Fp, Fp>), Error> {
+        let chip = &self.chip;
+        let radix = Fp::from_u128(1 << 64);
+        let mut carry: Option<AssignedCell<Fp, Fp>> = None;
+        let mut limbs = Vec::with_capacity(4);
+        for i in 0..4 {
+            let mut layouter = layouter.namespace(|| format!("limb {}", i));
+            let (a, b) = (&a.limbs[i], &b.limbs[i]);
+            let mut total = chip.add(layouter.namespace(|| "a + b"), a.clone(), b.clone())?;
+            if let Some(carry) = carry.clone() {
+                total = chip.add(layouter.namespace(|| "+ carry"), total, carry)?;
+            }
+            let carry_in = match &carry {
+                Some(carry) => carry.value().map(to_u64),
+                None => Some(0),
+            };
+            let (sum, carry_out) = match (a.value(), b.value(), carry_in) {
+                (Some(a), Some(b), Some(carry_in)) => {
+                    let (s, c1) = to_u64(a).overflowing_add(to_u64(b));
+                    let (s, c2) = s.overflowing_add(carry_in);
+                    (Some(Fp::from(s)), Some(Fp::from((c1 || c2) as u64)))
+                }
+                _ => (None, None),
+            };
+            let out =
+                chip.load_private_many(layouter.namespace(|| "sum, carry"), &[sum, carry_out])?;
+            let (sum, carry_out) = (out[0].clone(), out[1].clone());
+            chip.range_check(layouter.namespace(|| "sum < 2^64"), sum.clone(), 64)?;
+            chip.assert_boolean(layouter.namespace(|| "carry"), carry_out.clone())?;
+            let wrapped = chip.linear_combination(
+                layouter.namespace(|| "sum + carry * 2^64"),
+                &[(Fp::one(), sum.clone()), (radix, carry_out.clone())],
+            )?;
+            chip.assert_equal(layouter.namespace(|| "limb sum"), total, wrapped)?;
+            limbs.push(sum);
+            carry = Some(carry_out);
+        }
+        let overflow = carry.ok_or(Error::Synthesis)?;
+        let limbs = limbs.try_into().map_err(|_| Error::Synthesis)?;
+        Ok((U256 { limbs }, overflow))
+    }
+}
+
+impl Chip<Fp> for U256Chip {
+    type Config = MyConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        self.chip.config()
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// num / den with den constrained nonzero. nothing is reduced, so 2/4 and 1/2 are different
+// cells for the same rational and equality is always checked by cross multiplication
+#[derive(Clone, Debug)]
+pub struct Rational {
+    pub num: AssignedCell<Fp, Fp>,
+    pub den: AssignedCell<Fp, Fp>,
+}
+
+// rational arithmetic on top of MyChip's instructions, no gates of its own
+#[derive(Debug)]
+pub struct RationalChip {
+    chip: MyChip,
+}
+
+impl RationalChip {
+    pub fn new(config: MyConfig) -> Self {
+        Self {
+            chip: MyChip::new(config),
+        }
+    }
+
+    // loads a private num / den, a zero den is a synthesis error
+    pub fn load(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        value: Option<(Fp, Fp)>,
+    ) -> Result<Rational, Error> {
+        let cells = self.chip.load_private_many(
+            layouter.namespace(|| "num, den"),
+            &[value.map(|v| v.0), value.map(|v| v.1)],
+        )?;
+        let (num, den) = (cells[0].clone(), cells[1].clone());
+        self.chip
+            .assert_nonzero(layouter.namespace(|| "den != 0"), den.clone())?;
+        Ok(Rational { num, den })
+    }
+
+    // a/b + c/d = (a * d + c * b) / (b * d)
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        x: &Rational,
+        y: &Rational,
+    ) -> Result<Rational, Error> {
+        let num = self.chip.mul_mul_add(
+            layouter.namespace(|| "a * d + c * b"),
+            x.num.clone(),
+            y.den.clone(),
+            y.num.clone(),
+            x.den.clone(),
+        )?;
+        let den = self
+            .chip
+            .mul(layouter.namespace(|| "b * d"), x.den.clone(), y.den.clone())?;
+        Ok(Rational { num, den })
+    }
+
+    // a/b * c/d = (a * c) / (b * d)
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        x: &Rational,
+        y: &Rational,
+    ) -> Result<Rational, Error> {
+        let num = self
+            .chip
+            .mul(layouter.namespace(|| "a * c"), x.num.clone(), y.num.clone())?;
+        let den = self
+            .chip
+            .mul(layouter.namespace(|| "b * d"), x.den.clone(), y.den.clone())?;
+        Ok(Rational { num, den })
+    }
+
+    // constrains x to equal the public n / d on the given instance rows, n * den = d * num.
+    // d is not checked nonzero here, a public 0 denominator only matches a zero numerator
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        x: &Rational,
+        num_row: usize,
+        den_row: usize,
+    ) -> Result<(), Error> {
+        let n = self.chip.load_public(layouter.namespace(|| "n"), num_row)?;
+        let d = self.chip.load_public(layouter.namespace(|| "d"), den_row)?;
+        let lhs = self
+            .chip
+            .mul(layouter.namespace(|| "n * den"), n, x.den.clone())?;
+        let rhs = self
+            .chip
+            .mul(layouter.namespace(|| "d * num"), d, x.num.clone())?;
+        self.chip
+            .assert_equal(layouter.namespace(|| "n * den == d * num"), lhs, rhs)
+    }
+}
+
+impl Chip<Fp> for RationalChip {
+    type Config = MyConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        self.chip.config()
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+// keeps a running total across regions so synthesize doesn't have to thread the
+// accumulator cell around, same columns and gates as MyChip
+#[derive(Debug)]
+pub struct AccumulatorChip {
+    chip: MyChip,
+    // latest total, None until something is absorbed
+    acc: Option<AssignedCell<Fp, Fp>>,
+}
+
+impl AccumulatorChip {
+    pub fn new(config: MyConfig) -> Self {
+        Self {
+            chip: MyChip::new(config),
+            acc: None,
+        }
+    }
+
+    // adds value to the running total
+    pub fn absorb(
+        &mut self,
+        mut layouter: impl Layouter<Fp>,
+        value: AssignedCell<Fp, Fp>,
+    ) -> Result<(), Error> {
+        let acc = match self.acc.take() {
+            Some(acc) => self
+                .chip
+                .add(layouter.namespace(|| "acc + value"), acc, value)?,
+            None => value,
+        };
+        self.acc = Some(acc);
+        Ok(())
+    }
+
+    // exposes the total on the given instance row and resets the accumulator,
+    // nothing absorbed exposes the constant 0
+    pub fn finalize(
+        &mut self,
+        mut layouter: impl Layouter<Fp>,
+        instance_row: usize,
+    ) -> Result<(), Error> {
+        let acc = match self.acc.take() {
+            Some(acc) => acc,
+            None => self
+                .chip
+                .load_constant(layouter.namespace(|| "empty total"), Fp::zero())?,
+        };
+        self.chip
+            .expose_public(layouter.namespace(|| "total"), acc, instance_row)
+    }
+}
+
+impl Chip<Fp> for AccumulatorChip {
+    type Config = MyConfig;
+
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        self.chip.config()
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
@@ -0,0 +1,2190 @@
+use crate::chip::{
+    mimc_hash, to_u64, ByteTableChip, FibChip, FibConfig, MiMCChip, MiMCConfig, MyChip, MyConfig,
+    Ops, RationalChip, XorChip, XorConfig, MIMC_ROUNDS, RANGE_TABLE_BITS,
+};
+use halo2_gadgets::poseidon::{
+    self,
+    primitives::{self as poseidon_primitives, ConstantLength, P128Pow5T3},
+    Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner},
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+// x * 3 + x + 5 = 35
+// x3 = x * x * x
+// x3_x = x3 + x
+// x3_x_5 = x3_x + 5
+// x3_x_5 == 35
+
+// only the result is public, which is the statement proof::prove and proof::verify are about
+#[derive(Clone, Default)]
+pub struct MyCircuit {
+    pub constant: Fp,
+    pub x: Option<Fp>,
+}
+
+impl MyCircuit {
+    // seven rows for the load, the cube and the two additions
+    pub fn k() -> u32 {
+        min_k::<Self>(7)
+    }
+}
+
+impl Circuit<Fp> for MyCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // the constant is part of the circuit itself, only the witness is dropped
+        Self {
+            constant: self.constant,
+            x: None,
+        }
+    }
+
+    // these are input pins for the circuit,
+    // advice is private value to,
+    // one column for to store parameter,
+    // one column to use prefix constant
+    fn configure(meta: &mut halo2_proofs::plonk::ConstraintSystem<Fp>) -> Self::Config {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        let coeff = meta.fixed_column();
+        let byte_table = ByteTableChip::configure(meta);
+        MyChip::configure(meta, advice, instance, constant, coeff, byte_table)
+    }
+
+    // so circuit uses chip, and perform basic operations,
+    // so we chain things together to get our desired result here.
+    // below is basic instruction being used in the circuit
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl halo2_proofs::circuit::Layouter<Fp>,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        let chip = MyChip::new(config);
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        let x_3 = chip.cube(layouter.namespace(|| "x3"), x.clone())?;
+        let x_3_x_5 = x3_x_constant(&chip, &mut layouter, x_3, x, self.constant)?;
+        chip.expose_public(layouter.namespace(|| "expose res"), x_3_x_5, 0)
+    }
+}
+
+// the rest of the equation once x3 is there, x3 + x + constant. every circuit built on
+// MyCircuit's equation finishes it the same way
+fn x3_x_constant(
+    chip: &MyChip,
+    layouter: &mut impl Layouter<Fp>,
+    x_3: AssignedCell<Fp, Fp>,
+    x: AssignedCell<Fp, Fp>,
+    constant: Fp,
+) -> Result<AssignedCell<Fp, Fp>, Error> {
+    let x_3_x = chip.add(layouter.namespace(|| "x3_x"), x_3, x)?;
+    chip.add_constant(layouter.namespace(|| "x3_x_5"), x_3_x, constant)
+}
+
+// same equation as MyCircuit, but x2 and x3 are published as well, public inputs are
+// [x2, x3, x3_x_5]. x2 gives away x up to its sign, so this is only for showing the outputs.
+// still fits in k = 4
+#[derive(Clone, Default)]
+pub struct MultiOutputCircuit {
+    pub constant: Fp,
+    pub x: Option<Fp>,
+}
+
+impl Circuit<Fp> for MultiOutputCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            constant: self.constant,
+            x: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        let x_2 = chip.square(layouter.namespace(|| "x2"), x.clone())?;
+        let x_3 = chip.mul(layouter.namespace(|| "x3"), x_2.clone(), x.clone())?;
+        let x_3_x_5 = x3_x_constant(&chip, &mut layouter, x_3.clone(), x, self.constant)?;
+        chip.expose_public_many(layouter.namespace(|| "expose"), &[x_2, x_3, x_3_x_5], 0)?;
+        Ok(())
+    }
+}
+
+// same equation as MyCircuit with the constant taken from the instance instead of a fixed
+// column, so one pair of keys covers x^3 + x + c = r for every c. public inputs are [r, c]
+#[derive(Clone, Default)]
+pub struct PublicConstantCircuit {
+    pub x: Option<Fp>,
+}
+
+impl Circuit<Fp> for PublicConstantCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let c = chip.load_public(layouter.namespace(|| "load c"), 1)?;
+
+        let x_3 = chip.cube(layouter.namespace(|| "x3"), x.clone())?;
+        let x_3_x = chip.add(layouter.namespace(|| "x3_x"), x_3, x)?;
+        let x_3_x_c = chip.add(layouter.namespace(|| "x3_x_c"), x_3_x, c)?;
+        chip.expose_public(layouter.namespace(|| "expose res"), x_3_x_c, 0)
+    }
+}
+
+// MyCircuit's equation for many xs in one proof, the public input on row i is
+// x_i^3 + x_i + constant. the constant is loaded once and shared by all the additions
+#[derive(Clone, Default)]
+pub struct BatchCircuit {
+    pub constant: Fp,
+    pub xs: Vec<Option<Fp>>,
+    pub n: usize,
+}
+
+impl BatchCircuit {
+    // one row to load each x, then two rows each for a cube and two adds
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(7 * self.n + 1)
+    }
+}
+
+impl Circuit<Fp> for BatchCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            constant: self.constant,
+            xs: vec![None; self.n],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.xs.len() != self.n {
+            return Err(Error::Synthesis);
+        }
+        let chip = MyChip::new(config);
+        let c = chip.load_constant(layouter.namespace(|| "constant"), self.constant)?;
+        let xs = chip.load_private_many(layouter.namespace(|| "load xs"), &self.xs)?;
+        let results = xs
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let x_3 = chip.cube(layouter.namespace(|| format!("x3 {}", i)), x.clone())?;
+                let x_3_x = chip.add(layouter.namespace(|| format!("x3_x {}", i)), x_3, x)?;
+                chip.add(
+                    layouter.namespace(|| format!("x3_x_5 {}", i)),
+                    x_3_x,
+                    c.clone(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        chip.expose_public_many(layouter.namespace(|| "expose results"), &results, 0)?;
+        Ok(())
+    }
+}
+
+// proves knowledge of a root of a * x^2 + b * x + c for public [a, b, c] and a private x.
+// a = 0 is allowed and leaves the linear b * x + c = 0, with a = b = c = 0 any x passes
+#[derive(Clone, Default)]
+pub struct QuadraticRootCircuit {
+    pub x: Option<Fp>,
+}
+
+impl QuadraticRootCircuit {
+    // four one row loads, two rows each for a square, two muls and two adds, and the zero check
+    pub fn k() -> u32 {
+        min_k::<Self>(15)
+    }
+}
+
+impl Circuit<Fp> for QuadraticRootCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        let a = chip.load_public(layouter.namespace(|| "load a"), 0)?;
+        let b = chip.load_public(layouter.namespace(|| "load b"), 1)?;
+        let c = chip.load_public(layouter.namespace(|| "load c"), 2)?;
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        let x_2 = chip.square(layouter.namespace(|| "x2"), x.clone())?;
+        let a_x_2 = chip.mul(layouter.namespace(|| "a * x2"), a, x_2)?;
+        let b_x = chip.mul(layouter.namespace(|| "b * x"), b, x)?;
+        let lhs = chip.add(layouter.namespace(|| "a * x2 + b * x"), a_x_2, b_x)?;
+        let lhs = chip.add(layouter.namespace(|| "a * x2 + b * x + c"), lhs, c)?;
+        chip.assert_equals_constant(layouter.namespace(|| "== 0"), lhs, Fp::zero())
+    }
+}
+
+// proves y = g^x mod m for public g and m and a private exponent x of bits bits. square and
+// multiply from the top bit down, reducing with mod_const after every product so the
+// accumulator stays below m and its square below 2^128
+#[derive(Clone, Default)]
+pub struct ModExpCircuit {
+    pub g: u64,
+    pub m: u64,
+    pub x: Option<Fp>,
+    pub bits: usize,
+}
+
+impl ModExpCircuit {
+    // each round is a square, a multiply and a select plus two reductions, and a reduction is
+    // about a hundred rows, most of it the 64 bit comparison r < m
+    pub fn k(&self) -> u32 {
+        min_k::<Self>((self.bits * 215 + 8).max(1 << RANGE_TABLE_BITS))
+    }
+}
+
+impl Circuit<Fp> for ModExpCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            x: None,
+            ..self.clone()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.m == 0 {
+            return Err(Error::Synthesis);
+        }
+        ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let bits = chip.decompose(layouter.namespace(|| "bits of x"), x, self.bits)?;
+        let g = Fp::from(self.g % self.m);
+        // 1 mod m, so m = 1 gives 0 for every x like it should
+        let mut acc = chip.load_constant(layouter.namespace(|| "one"), Fp::from(1 % self.m))?;
+        for (i, bit) in bits.into_iter().enumerate().rev() {
+            let acc_2 = chip.square(layouter.namespace(|| format!("acc^2 {}", i)), acc)?;
+            let acc_2 = chip.mod_const(
+                layouter.namespace(|| format!("acc^2 mod m {}", i)),
+                acc_2,
+                self.m,
+                64,
+            )?;
+            let acc_g = chip.mul_by_constant(
+                layouter.namespace(|| format!("acc * g {}", i)),
+                acc_2.clone(),
+                g,
+            )?;
+            let acc_g = chip.mod_const(
+                layouter.namespace(|| format!("acc * g mod m {}", i)),
+                acc_g,
+                self.m,
+                64,
+            )?;
+            acc = chip.select(
+                layouter.namespace(|| format!("bit {}", i)),
+                bit,
+                acc_g,
+                acc_2,
+            )?;
+        }
+        chip.expose_public(layouter.namespace(|| "expose y"), acc, 0)
+    }
+}
+
+// one private x satisfying two equations at once, x^3 + x + 5 = r1 and 2 * x^2 - 3 = r2,
+// with x2 shared between them. public inputs are [r1, r2]
+#[derive(Clone, Default)]
+pub struct EquationSystemCircuit {
+    pub x: Option<Fp>,
+}
+
+impl EquationSystemCircuit {
+    // the load and two rows for each of the six operations
+    pub fn k() -> u32 {
+        min_k::<Self>(13)
+    }
+}
+
+impl Circuit<Fp> for EquationSystemCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let x_2 = chip.square(layouter.namespace(|| "x2"), x.clone())?;
+
+        let x_3 = chip.mul(layouter.namespace(|| "x3"), x_2.clone(), x.clone())?;
+        let x_3_x = chip.add(layouter.namespace(|| "x3_x"), x_3, x)?;
+        let r1 = chip.add_constant(layouter.namespace(|| "x3_x_5"), x_3_x, Fp::from(5))?;
+
+        let two_x_2 = chip.mul_by_constant(layouter.namespace(|| "2x2"), x_2, Fp::from(2))?;
+        let r2 = chip.add_constant(layouter.namespace(|| "2x2_3"), two_x_2, -Fp::from(3))?;
+        chip.expose_public_many(layouter.namespace(|| "expose r1, r2"), &[r1, r2], 0)?;
+        Ok(())
+    }
+}
+
+// proves a * b + c = n / d for private rationals a, b, c given as (num, den) and the public
+// inputs [n, d]
+#[derive(Clone, Default)]
+pub struct RationalCircuit {
+    pub a: Option<(Fp, Fp)>,
+    pub b: Option<(Fp, Fp)>,
+    pub c: Option<(Fp, Fp)>,
+}
+
+impl RationalCircuit {
+    // 24 rows as laid out: the three loads with their nonzero checks, the mul, the add and the
+    // cross multiplied check against the publics
+    pub fn k() -> u32 {
+        min_k::<Self>(24)
+    }
+}
+
+impl Circuit<Fp> for RationalCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = RationalChip::new(config);
+        let a = chip.load(layouter.namespace(|| "load a"), self.a)?;
+        let b = chip.load(layouter.namespace(|| "load b"), self.b)?;
+        let c = chip.load(layouter.namespace(|| "load c"), self.c)?;
+        let ab = chip.mul(layouter.namespace(|| "a * b"), &a, &b)?;
+        let ab_c = chip.add(layouter.namespace(|| "a * b + c"), &ab, &c)?;
+        chip.expose_public(layouter.namespace(|| "expose n / d"), &ab_c, 0, 1)
+    }
+}
+
+// smallest k with enough rows for the circuit, on top of the rows used by regions and tables
+// halo2 reserves some rows at the end for blinding, so those are counted in as well. row
+// counts too big for a usize come out as usize::BITS, far past any k params can be made for
+pub(crate) fn min_k<C: Circuit<Fp>>(rows: usize) -> u32 {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let rows = rows.saturating_add(cs.minimum_rows());
+    let mut k = 1;
+    while k < usize::BITS && (1 << k) < rows {
+        k += 1;
+    }
+    k
+}
+
+// proves a ^ b for two private bytes equals the public value
+#[derive(Default)]
+pub struct XorCircuit {
+    pub a: Option<Fp>,
+    pub b: Option<Fp>,
+}
+
+impl XorCircuit {
+    // the table alone takes 2^16 rows, so this comes out as 17
+    pub fn k() -> u32 {
+        min_k::<Self>(XorChip::TABLE_ROWS)
+    }
+}
+
+impl Circuit<Fp> for XorCircuit {
+    type Config = (MyConfig, XorConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        // xor lookups reuse the advice columns of MyChip
+        let advice = config.advice;
+        let xor = XorChip::configure(meta, [advice[0], advice[1], advice[2]]);
+        (config, xor)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let xor = XorChip::new(config.1);
+        xor.load(layouter.namespace(|| "xor table"))?;
+        let a = chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+        let out = xor.xor_u8(layouter.namespace(|| "a ^ b"), a, b)?;
+        chip.expose_public(layouter.namespace(|| "expose a ^ b"), out, 0)
+    }
+}
+
+// proves knowledge of x with sum(coeffs[i] * x^i) equal to the public value,
+// the coefficients are part of the circuit so they end up in the keys
+#[derive(Clone, Default)]
+pub struct PolyCircuit {
+    pub coeffs: Vec<Fp>,
+    pub x: Option<Fp>,
+}
+
+impl PolyCircuit {
+    // load, powers, the linear combination and the constant term add up to 2 * (degree + 1) + 2
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(2 * self.coeffs.len() + 2)
+    }
+}
+
+impl Circuit<Fp> for PolyCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            coeffs: self.coeffs.clone(),
+            x: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let (c_0, rest) = match self.coeffs.split_first() {
+            Some((c_0, rest)) => (*c_0, rest),
+            None => (Fp::zero(), &[][..]),
+        };
+        // x^1 ..= x^n, the x^0 term is added as a constant at the end
+        let powers = chip.powers(layouter.namespace(|| "powers"), x, rest.len())?;
+        let terms: Vec<(Fp, AssignedCell<Fp, Fp>)> = rest.iter().copied().zip(powers).collect();
+        let sum = chip.linear_combination(layouter.namespace(|| "sum c_i * x^i"), &terms)?;
+        let res = chip.add_constant(layouter.namespace(|| "+ c_0"), sum, c_0)?;
+        chip.expose_public(layouter.namespace(|| "expose res"), res, 0)
+    }
+}
+
+// proves the n-th fibonacci number from two private seeds equals the public value
+#[derive(Clone, Default)]
+pub struct FibonacciCircuit {
+    pub a: Option<Fp>,
+    pub b: Option<Fp>,
+    pub n: usize,
+}
+
+impl FibonacciCircuit {
+    // n + 1 rows for the sequence and one for loading the seeds
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(self.n.saturating_add(2))
+    }
+}
+
+impl Circuit<Fp> for FibonacciCircuit {
+    type Config = (MyConfig, FibConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: None,
+            b: None,
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let fib = FibChip::configure(meta, config.advice[0]);
+        (config, fib)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let fib = FibChip::new(config.1);
+        let seeds =
+            chip.load_private_many(layouter.namespace(|| "load seeds"), &[self.a, self.b])?;
+        let out = fib.nth(
+            layouter.namespace(|| "f(n)"),
+            seeds[0].clone(),
+            seeds[1].clone(),
+            self.n,
+        )?;
+        chip.expose_public(layouter.namespace(|| "expose f(n)"), out, 0)
+    }
+}
+
+// proves knowledge of a and b with a^2 + b^2 = c^2 for the public c
+#[derive(Clone, Default)]
+pub struct PythagoreanCircuit {
+    pub a: Option<Fp>,
+    pub b: Option<Fp>,
+}
+
+impl PythagoreanCircuit {
+    // three loads, three squares, the sum and the equality check
+    pub fn k() -> u32 {
+        min_k::<Self>(12)
+    }
+}
+
+impl Circuit<Fp> for PythagoreanCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        // c is only a helper witness, the public value it gets exposed against is what counts.
+        // a^2 + b^2 not being a perfect square just leaves c * c != a^2 + b^2
+        let c = self.a.zip(self.b).map(|(a, b)| {
+            let (a, b) = (to_u64(&a) as u128, to_u64(&b) as u128);
+            Fp::from_u128((a * a + b * b).isqrt())
+        });
+        let sides =
+            chip.load_private_many(layouter.namespace(|| "load a, b, c"), &[self.a, self.b, c])?;
+        let (a, b, c) = (sides[0].clone(), sides[1].clone(), sides[2].clone());
+        let a_2 = chip.mul(layouter.namespace(|| "a^2"), a.clone(), a)?;
+        let b_2 = chip.mul(layouter.namespace(|| "b^2"), b.clone(), b)?;
+        let a_2_b_2 = chip.add(layouter.namespace(|| "a^2 + b^2"), a_2, b_2)?;
+        let c_2 = chip.mul(layouter.namespace(|| "c^2"), c.clone(), c.clone())?;
+        chip.assert_equal(layouter.namespace(|| "a^2 + b^2 == c^2"), a_2_b_2, c_2)?;
+        chip.expose_public(layouter.namespace(|| "expose c"), c, 0)
+    }
+}
+
+// proves knowledge of a factorization p * q of the public n, neither factor being 0 or 1.
+// both factors are range checked to FACTOR_BITS, so their product is an integer product and
+// can't wrap around the field to land on n
+#[derive(Clone, Default)]
+pub struct FactoringCircuit {
+    pub p: Option<Fp>,
+    pub q: Option<Fp>,
+}
+
+impl FactoringCircuit {
+    pub const FACTOR_BITS: u8 = 64;
+
+    // the byte table dominates, the checks and the product take a few dozen rows
+    pub fn k() -> u32 {
+        min_k::<Self>(1 << RANGE_TABLE_BITS)
+    }
+}
+
+impl Circuit<Fp> for FactoringCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let byte_table = ByteTableChip::new(config.byte_table.clone());
+        byte_table.load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let factors =
+            chip.load_private_many(layouter.namespace(|| "load p, q"), &[self.p, self.q])?;
+        let one = chip.load_constant(layouter.namespace(|| "one"), Fp::one())?;
+        for (i, factor) in factors.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("factor {}", i));
+            chip.range_check(
+                layouter.namespace(|| "fits"),
+                factor.clone(),
+                Self::FACTOR_BITS,
+            )?;
+            chip.assert_nonzero(layouter.namespace(|| "!= 0"), factor.clone())?;
+            let is_one =
+                chip.is_equal(layouter.namespace(|| "== 1"), factor.clone(), one.clone())?;
+            chip.assert_equals_constant(layouter.namespace(|| "!= 1"), is_one, Fp::zero())?;
+        }
+        let n = chip.mul(
+            layouter.namespace(|| "p * q"),
+            factors[0].clone(),
+            factors[1].clone(),
+        )?;
+        chip.expose_public(layouter.namespace(|| "expose n"), n, 0)
+    }
+}
+
+// same as FactoringCircuit with three factors p * q * r, none of them 0 or 1. with each
+// factor below 2^64 the product stays below 2^192 and can't wrap the field
+#[derive(Clone, Default)]
+pub struct ThreeFactorCircuit {
+    pub p: Option<Fp>,
+    pub q: Option<Fp>,
+    pub r: Option<Fp>,
+}
+
+impl ThreeFactorCircuit {
+    pub const FACTOR_BITS: u8 = 64;
+
+    // the byte table dominates, the checks and the product take a few dozen rows
+    pub fn k() -> u32 {
+        min_k::<Self>(1 << RANGE_TABLE_BITS)
+    }
+}
+
+impl Circuit<Fp> for ThreeFactorCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let byte_table = ByteTableChip::new(config.byte_table.clone());
+        byte_table.load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let factors = chip.load_private_many(
+            layouter.namespace(|| "load p, q, r"),
+            &[self.p, self.q, self.r],
+        )?;
+        let one = chip.load_constant(layouter.namespace(|| "one"), Fp::one())?;
+        for (i, factor) in factors.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("factor {}", i));
+            chip.range_check(
+                layouter.namespace(|| "fits"),
+                factor.clone(),
+                Self::FACTOR_BITS,
+            )?;
+            chip.assert_nonzero(layouter.namespace(|| "!= 0"), factor.clone())?;
+            let is_one =
+                chip.is_equal(layouter.namespace(|| "== 1"), factor.clone(), one.clone())?;
+            chip.assert_equals_constant(layouter.namespace(|| "!= 1"), is_one, Fp::zero())?;
+        }
+        let n = chip.product(layouter.namespace(|| "p * q * r"), &factors)?;
+        chip.expose_public(layouter.namespace(|| "expose n"), n, 0)
+    }
+}
+
+// proves current_year - birth_year >= threshold for a private birth year,
+// the public inputs are [current_year, threshold]
+#[derive(Clone, Default)]
+pub struct AgeCircuit {
+    pub birth_year: Option<Fp>,
+    // the prover fills in the public values too, they get exposed against the instance
+    pub current_year: Option<Fp>,
+    pub threshold: Option<Fp>,
+}
+
+impl AgeCircuit {
+    // years and ages are kept to 16 bits for the comparison
+    pub const YEAR_BITS: u8 = 16;
+
+    // the byte table dominates, the gadgets take a few dozen rows on top
+    pub fn k() -> u32 {
+        min_k::<Self>(1 << RANGE_TABLE_BITS)
+    }
+}
+
+impl Circuit<Fp> for AgeCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let byte_table = ByteTableChip::new(config.byte_table.clone());
+        byte_table.load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let years = chip.load_private_many(
+            layouter.namespace(|| "load years"),
+            &[self.birth_year, self.current_year, self.threshold],
+        )?;
+        let (birth_year, current_year, threshold) =
+            (years[0].clone(), years[1].clone(), years[2].clone());
+        chip.expose_public_many(
+            layouter.namespace(|| "expose year, threshold"),
+            &[current_year.clone(), threshold.clone()],
+            0,
+        )?;
+        let age = chip.sub(layouter.namespace(|| "age"), current_year, birth_year)?;
+        // a birth year after the current year wraps around to a huge age, which fails here
+        chip.range_check(
+            layouter.namespace(|| "age fits"),
+            age.clone(),
+            Self::YEAR_BITS,
+        )?;
+        chip.range_check(
+            layouter.namespace(|| "threshold fits"),
+            threshold.clone(),
+            Self::YEAR_BITS,
+        )?;
+        let too_young = chip.lt(
+            layouter.namespace(|| "age < threshold"),
+            age,
+            threshold,
+            Self::YEAR_BITS as usize,
+        )?;
+        chip.assert_equals_constant(
+            layouter.namespace(|| "age >= threshold"),
+            too_young,
+            Fp::zero(),
+        )
+    }
+}
+
+// proves n private values add up to the public total
+#[derive(Clone, Default)]
+pub struct SumCircuit {
+    pub values: Vec<Option<Fp>>,
+    pub n: usize,
+}
+
+impl SumCircuit {
+    // n rows to load the values and n + 1 for the running sum
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(2 * self.n + 1)
+    }
+}
+
+impl Circuit<Fp> for SumCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.n],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        // the layout is fixed by n, a witness of another length doesn't fit it
+        if self.values.len() != self.n {
+            return Err(Error::Synthesis);
+        }
+        let chip = MyChip::new(config);
+        let values = chip.load_private_many(layouter.namespace(|| "load values"), &self.values)?;
+        let sums = chip.running_sum(layouter.namespace(|| "running sum"), &values)?;
+        let total = match sums.last() {
+            Some(total) => total.clone(),
+            None => chip.load_constant(layouter.namespace(|| "empty total"), Fp::zero())?,
+        };
+        chip.expose_public(layouter.namespace(|| "expose total"), total, 0)
+    }
+}
+
+// proves the average of n private u64 values equals the public value. exact mode constrains
+// sum = avg * n with avg range checked to 64 bits as well, so neither side can wrap and a sum
+// not divisible by n can't be proven at all. otherwise the sum goes through divrem_u64 and
+// the public inputs are [avg, remainder] with the average rounded down
+#[derive(Clone, Default)]
+pub struct AverageCircuit {
+    pub values: Vec<Option<Fp>>,
+    pub n: usize,
+    pub exact: bool,
+}
+
+impl AverageCircuit {
+    // 2n + 1 rows for the values and their running sum and 9 per 64 bit range check, next to
+    // the byte table. exact mode adds avg, its range check, avg * n and the equality, divrem's
+    // range checks take 111 rows instead
+    pub fn k(&self) -> u32 {
+        let rows = if self.exact {
+            11 * self.n + 13
+        } else {
+            11 * self.n + 115
+        };
+        min_k::<Self>(rows.max(1 << RANGE_TABLE_BITS))
+    }
+}
+
+impl Circuit<Fp> for AverageCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.n],
+            n: self.n,
+            exact: self.exact,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.values.len() != self.n || self.n == 0 {
+            return Err(Error::Synthesis);
+        }
+        ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let values = chip.load_private_many(layouter.namespace(|| "load values"), &self.values)?;
+        for (i, v) in values.iter().enumerate() {
+            chip.range_check(layouter.namespace(|| format!("value {}", i)), v.clone(), 64)?;
+        }
+        let sum = chip.sum(layouter.namespace(|| "sum"), &values)?;
+        let n = Fp::from(self.n as u64);
+        if self.exact {
+            // rounded down, so an inexact average fails the check below instead of rounding
+            let avg = sum.value().map(|sum| Fp::from(to_u64(sum) / self.n as u64));
+            let avg = chip.load_private(layouter.namespace(|| "load avg"), avg)?;
+            chip.range_check(layouter.namespace(|| "avg"), avg.clone(), 64)?;
+            let avg_n = chip.mul_by_constant(layouter.namespace(|| "avg * n"), avg.clone(), n)?;
+            chip.assert_equal(layouter.namespace(|| "sum == avg * n"), sum, avg_n)?;
+            chip.expose_public(layouter.namespace(|| "expose avg"), avg, 0)
+        } else {
+            let n = chip.load_constant(layouter.namespace(|| "n"), n)?;
+            let (avg, rem) = chip.divrem_u64(layouter.namespace(|| "sum / n"), sum, n)?;
+            chip.expose_public_many(layouter.namespace(|| "expose avg, rem"), &[avg, rem], 0)?;
+            Ok(())
+        }
+    }
+}
+
+// proves the squares of n private values add up to the public total
+#[derive(Clone, Default)]
+pub struct SumOfSquaresCircuit {
+    pub values: Vec<Option<Fp>>,
+    pub n: usize,
+}
+
+impl SumOfSquaresCircuit {
+    // n rows to load, two per square and n + 1 for the running sum
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(4 * self.n + 1)
+    }
+}
+
+impl Circuit<Fp> for SumOfSquaresCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.n],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.values.len() != self.n || self.n == 0 {
+            return Err(Error::Synthesis);
+        }
+        let chip = MyChip::new(config);
+        let values = chip.load_private_many(layouter.namespace(|| "load values"), &self.values)?;
+        let squares = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| chip.square(layouter.namespace(|| format!("square {}", i)), v))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sums = chip.running_sum(layouter.namespace(|| "running sum"), &squares)?;
+        // n > 0, so there is a last prefix
+        let total = sums[sums.len() - 1].clone();
+        chip.expose_public(layouter.namespace(|| "expose total"), total, 0)
+    }
+}
+
+// proves y = a * x for a constant rows x cols matrix a and a private vector x, y is public
+#[derive(Clone, Default)]
+pub struct MatVecCircuit {
+    pub a: Vec<Vec<Fp>>,
+    pub x: Vec<Option<Fp>>,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl MatVecCircuit {
+    // cols rows to load x, then a linear combination of cols + 1 rows per output
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(self.cols + self.rows * (self.cols + 1))
+    }
+}
+
+impl Circuit<Fp> for MatVecCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        // a is part of the circuit, only x is witness
+        Self {
+            a: self.a.clone(),
+            x: vec![None; self.cols],
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.x.len() != self.cols
+            || self.a.len() != self.rows
+            || self.a.iter().any(|row| row.len() != self.cols)
+        {
+            return Err(Error::Synthesis);
+        }
+        let chip = MyChip::new(config);
+        let x = chip.load_private_many(layouter.namespace(|| "load x"), &self.x)?;
+        let y = self
+            .a
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let terms: Vec<_> = row.iter().copied().zip(x.iter().cloned()).collect();
+                chip.linear_combination(layouter.namespace(|| format!("row {}", i)), &terms)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        chip.expose_public_many(layouter.namespace(|| "expose y"), &y, 0)?;
+        Ok(())
+    }
+}
+
+// errors from building a circuit, before anything is synthesized
+#[derive(Debug, PartialEq, Eq)]
+pub enum CircuitError {
+    LengthMismatch { left: usize, right: usize },
+}
+
+impl std::fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitError::LengthMismatch { left, right } => {
+                write!(f, "vector lengths differ: {} and {}", left, right)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+// proves the dot product of two private vectors equals the public value. the fields are
+// private so new is the only way to build one, and the lengths always agree
+#[derive(Clone, Default)]
+pub struct DotProductCircuit {
+    a: Vec<Option<Fp>>,
+    b: Vec<Option<Fp>>,
+    n: usize,
+}
+
+impl DotProductCircuit {
+    pub fn new(a: Vec<Option<Fp>>, b: Vec<Option<Fp>>) -> Result<Self, CircuitError> {
+        if a.len() != b.len() {
+            return Err(CircuitError::LengthMismatch {
+                left: a.len(),
+                right: b.len(),
+            });
+        }
+        let n = a.len();
+        Ok(Self { a, b, n })
+    }
+
+    // n rows for each vector, n + 1 for the dot product
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(3 * self.n + 1)
+    }
+}
+
+impl Circuit<Fp> for DotProductCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: vec![None; self.n],
+            b: vec![None; self.n],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        let a = chip.load_private_many(layouter.namespace(|| "load a"), &self.a)?;
+        let b = chip.load_private_many(layouter.namespace(|| "load b"), &self.b)?;
+        let dot = chip.dot_product(layouter.namespace(|| "a . b"), &a, &b)?;
+        chip.expose_public(layouter.namespace(|| "expose dot"), dot, 0)
+    }
+}
+
+// the challenge for a grand product over the values, a mimc chain from zero with each value
+// as the key in turn
+pub fn challenge(values: &[Fp]) -> Fp {
+    values.iter().fold(Fp::zero(), |h, &v| mimc_hash(h, v))
+}
+
+// challenge over the assigned cells, MIMC_ROUNDS + 2 rows per cell. it is a hash of the cells
+// themselves, so a prover can't pick it before them or solve for a cell after it
+fn assign_challenge(
+    chip: &MyChip,
+    mimc: &MiMCChip,
+    mut layouter: impl Layouter<Fp>,
+    values: &[AssignedCell<Fp, Fp>],
+) -> Result<AssignedCell<Fp, Fp>, Error> {
+    let mut h = chip.load_constant(layouter.namespace(|| "zero"), Fp::zero())?;
+    for (i, v) in values.iter().enumerate() {
+        h = mimc.hash(layouter.namespace(|| format!("absorb {}", i)), h, v.clone())?;
+    }
+    Ok(h)
+}
+
+// proves the private b is a permutation of the public a with the grand product
+// prod(a_i + gamma) == prod(b_i + gamma). public inputs are [a_0, .., a_n-1], gamma is
+// challenge(a ++ b) worked out in the circuit
+#[derive(Clone, Default)]
+pub struct PermutationCircuit {
+    pub a: Vec<Option<Fp>>,
+    pub b: Vec<Option<Fp>>,
+    pub n: usize,
+}
+
+impl PermutationCircuit {
+    // 2n + 1 rows to load, a mimc region per value, two per add and n per product
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(2 * self.n * (MIMC_ROUNDS + 2) + 8 * self.n + 1)
+    }
+}
+
+impl Circuit<Fp> for PermutationCircuit {
+    type Config = (MyConfig, MiMCConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: vec![None; self.n],
+            b: vec![None; self.n],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let mimc = MiMCChip::configure(meta, [config.advice[0], config.advice[1]], config.coeff);
+        (config, mimc)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.a.len() != self.n || self.b.len() != self.n {
+            return Err(Error::Synthesis);
+        }
+        let chip = MyChip::new(config.0);
+        let mimc = MiMCChip::new(config.1);
+        let a = chip.load_private_many(layouter.namespace(|| "load a"), &self.a)?;
+        let b = chip.load_private_many(layouter.namespace(|| "load b"), &self.b)?;
+        chip.expose_public_many(layouter.namespace(|| "expose a"), &a, 0)?;
+        let ab: Vec<_> = a.iter().chain(&b).cloned().collect();
+        let gamma = assign_challenge(&chip, &mimc, layouter.namespace(|| "gamma"), &ab)?;
+
+        let mut grand_product = |name: &str, values: Vec<AssignedCell<Fp, Fp>>| {
+            let shifted = values
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    chip.add(
+                        layouter.namespace(|| format!("{}_{} + gamma", name, i)),
+                        v,
+                        gamma.clone(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            chip.product(layouter.namespace(|| format!("prod {}", name)), &shifted)
+        };
+        let prod_a = grand_product("a", a)?;
+        let prod_b = grand_product("b", b)?;
+        chip.assert_equal(layouter.namespace(|| "prod a == prod b"), prod_a, prod_b)
+    }
+}
+
+// width of the values in SortedCircuit, lt is only correct for range checked inputs
+pub const SORTED_BITS: usize = 16;
+
+// proves a private vector is non-decreasing, its sum is public so the proof is about
+// some particular vector and not just any sorted one
+#[derive(Clone, Default)]
+pub struct SortedCircuit {
+    pub values: Vec<Option<Fp>>,
+    pub n: usize,
+}
+
+impl SortedCircuit {
+    // per value a load, a range check and its share of the sum, per adjacent pair an lt whose
+    // decomposition alone is SORTED_BITS + 3 rows. never less than the byte table
+    pub fn k(&self) -> u32 {
+        let rows = 6 * self.n + self.n.saturating_sub(1) * (SORTED_BITS + 10);
+        min_k::<Self>(rows.max(1 << RANGE_TABLE_BITS))
+    }
+}
+
+impl Circuit<Fp> for SortedCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.n],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.values.len() != self.n || self.n == 0 {
+            return Err(Error::Synthesis);
+        }
+        ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let values = chip.load_private_many(layouter.namespace(|| "load values"), &self.values)?;
+        for (i, v) in values.iter().enumerate() {
+            chip.range_check(
+                layouter.namespace(|| format!("range check {}", i)),
+                v.clone(),
+                SORTED_BITS as u8,
+            )?;
+        }
+        for (i, pair) in values.windows(2).enumerate() {
+            let descending = chip.lt(
+                layouter.namespace(|| format!("v_{} < v_{}", i + 1, i)),
+                pair[1].clone(),
+                pair[0].clone(),
+                SORTED_BITS,
+            )?;
+            chip.assert_equals_constant(
+                layouter.namespace(|| format!("v_{} <= v_{}", i, i + 1)),
+                descending,
+                Fp::zero(),
+            )?;
+        }
+        let sum = chip.sum(layouter.namespace(|| "sum"), &values)?;
+        chip.expose_public(layouter.namespace(|| "expose sum"), sum, 0)
+    }
+}
+
+// width of the values in VarianceCircuit. n * sum(x^2) stays below n^2 * 2^64, far from
+// wrapping the field for any n that fits in a circuit
+pub const VARIANCE_BITS: u8 = 32;
+
+// proves n^2 * variance = n * sum(x^2) - sum(x)^2 for private values, which keeps it integral
+#[derive(Clone, Default)]
+pub struct VarianceCircuit {
+    pub values: Vec<Option<Fp>>,
+    pub n: usize,
+}
+
+impl VarianceCircuit {
+    // ten rows per value for its load, range check, square and a row in each of the two sums,
+    // six more for the two sums' starts and n * sum_sq - sum^2
+    pub fn k(&self) -> u32 {
+        min_k::<Self>((10 * self.n + 6).max(1 << RANGE_TABLE_BITS))
+    }
+}
+
+impl Circuit<Fp> for VarianceCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            values: vec![None; self.n],
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.values.len() != self.n || self.n == 0 {
+            return Err(Error::Synthesis);
+        }
+        ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let values = chip.load_private_many(layouter.namespace(|| "load values"), &self.values)?;
+        for (i, v) in values.iter().enumerate() {
+            chip.range_check(
+                layouter.namespace(|| format!("range check {}", i)),
+                v.clone(),
+                VARIANCE_BITS,
+            )?;
+        }
+        let sum = chip.sum(layouter.namespace(|| "sum"), &values)?;
+        let squares = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| chip.square(layouter.namespace(|| format!("square {}", i)), v.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sum_sq = chip.sum(layouter.namespace(|| "sum of squares"), &squares)?;
+        let n = Fp::from(self.n as u64);
+        let n_sum_sq = chip.mul_by_constant(layouter.namespace(|| "n * sum_sq"), sum_sq, n)?;
+        let sum_2 = chip.square(layouter.namespace(|| "sum^2"), sum)?;
+        let variance = chip.sub(layouter.namespace(|| "n * sum_sq - sum^2"), n_sum_sq, sum_2)?;
+        chip.expose_public(layouter.namespace(|| "expose variance"), variance, 0)
+    }
+}
+
+// bound on a pre-clamp output of LinearLayerCircuit. every product is below 2^128, so this
+// leaves room for 255 inputs plus the bias before the comparison in the clamp stops being sound
+pub const LINEAR_ACC_BITS: u8 = 136;
+
+// proves y = min(w * x + bias, clamp_max) for a public fixed point weight matrix and bias,
+// a private input vector x and a public output y. everything is unsigned with scale_bits
+// fractional bits, every w_ji * x_i is truncated on its own like mul_fixed_point does
+#[derive(Clone, Default)]
+pub struct LinearLayerCircuit {
+    pub w: Vec<Vec<Fp>>,
+    pub bias: Vec<Fp>,
+    pub x: Vec<Option<Fp>>,
+    pub inputs: usize,
+    pub outputs: usize,
+    pub scale_bits: usize,
+    pub clamp_max: Fp,
+}
+
+impl LinearLayerCircuit {
+    // a fixed point product is about 60 rows plus the scale_bits / 8 chunks of the remainder,
+    // the range check and comparison of the clamp about 170 per output
+    pub fn k(&self) -> u32 {
+        let per_product = 60 + self.scale_bits / 8;
+        let per_output = self.inputs * per_product + 180;
+        let rows = self.inputs * 2 + self.outputs * per_output;
+        min_k::<Self>(rows.max(1 << RANGE_TABLE_BITS))
+    }
+}
+
+impl Circuit<Fp> for LinearLayerCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            x: vec![None; self.inputs],
+            ..self.clone()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.x.len() != self.inputs
+            || self.bias.len() != self.outputs
+            || self.w.len() != self.outputs
+            || self.w.iter().any(|row| row.len() != self.inputs)
+        {
+            return Err(Error::Synthesis);
+        }
+        ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let x = chip.load_private_many(layouter.namespace(|| "load x"), &self.x)?;
+        let clamp_max = chip.load_constant(layouter.namespace(|| "clamp max"), self.clamp_max)?;
+        let mut y = Vec::with_capacity(self.outputs);
+        for (j, (row, bias)) in self.w.iter().zip(self.bias.iter()).enumerate() {
+            let w = chip.load_constants(layouter.namespace(|| format!("w_{}", j)), row)?;
+            let products = w
+                .into_iter()
+                .zip(x.iter().cloned())
+                .enumerate()
+                .map(|(i, (w, x))| {
+                    chip.mul_fixed_point(
+                        layouter.namespace(|| format!("w_{}{} * x_{}", j, i, i)),
+                        w,
+                        x,
+                        self.scale_bits,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let acc = chip.sum(layouter.namespace(|| format!("sum {}", j)), &products)?;
+            let acc = chip.add_constant(layouter.namespace(|| format!("+ b_{}", j)), acc, *bias)?;
+            // fails if the bias pushes the sum past the bound lt relies on
+            chip.range_check(
+                layouter.namespace(|| format!("acc {} fits", j)),
+                acc.clone(),
+                LINEAR_ACC_BITS,
+            )?;
+            let out = chip.min(
+                layouter.namespace(|| format!("clamp {}", j)),
+                acc,
+                clamp_max.clone(),
+                LINEAR_ACC_BITS as usize,
+            )?;
+            y.push(out);
+        }
+        chip.expose_public_many(layouter.namespace(|| "expose y"), &y, 0)?;
+        Ok(())
+    }
+}
+
+// proves m private boolean ballots add up to the public tally. with expose_m the public
+// inputs are [tally, m], so the verifier also learns how many ballots were counted
+#[derive(Clone, Default)]
+pub struct VoteTallyCircuit {
+    pub ballots: Vec<Option<Fp>>,
+    pub m: usize,
+    pub expose_m: bool,
+}
+
+impl VoteTallyCircuit {
+    // a load and a boolean row per ballot, m + 1 rows for the running sum
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(3 * self.m + 2)
+    }
+}
+
+impl Circuit<Fp> for VoteTallyCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            ballots: vec![None; self.m],
+            m: self.m,
+            expose_m: self.expose_m,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.ballots.len() != self.m || self.m == 0 {
+            return Err(Error::Synthesis);
+        }
+        let chip = MyChip::new(config);
+        let ballots =
+            chip.load_private_many(layouter.namespace(|| "load ballots"), &self.ballots)?;
+        for (i, ballot) in ballots.iter().enumerate() {
+            chip.assert_boolean(
+                layouter.namespace(|| format!("ballot {} is 0 or 1", i)),
+                ballot.clone(),
+            )?;
+        }
+        let sums = chip.running_sum(layouter.namespace(|| "tally"), &ballots)?;
+        let tally = sums[sums.len() - 1].clone();
+        chip.expose_public(layouter.namespace(|| "expose tally"), tally, 0)?;
+        if self.expose_m {
+            let m = chip.load_constant(layouter.namespace(|| "m"), Fp::from(self.m as u64))?;
+            chip.expose_public(layouter.namespace(|| "expose m"), m, 1)?;
+        }
+        Ok(())
+    }
+}
+
+// proves a private leaf sits in a merkle tree with the public root, nodes are hashed as
+// mimc_hash(left, right). path[i] is the sibling at level i with its direction bit,
+// 1 meaning the current node is the right child
+#[derive(Clone, Default)]
+pub struct MerklePathCircuit {
+    pub leaf: Option<Fp>,
+    pub path: Vec<(Option<Fp>, Option<Fp>)>,
+}
+
+impl MerklePathCircuit {
+    // per level a mimc region, the sibling load and the swap
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(self.path.len() * (MIMC_ROUNDS + 6) + 2)
+    }
+}
+
+impl Circuit<Fp> for MerklePathCircuit {
+    type Config = (MyConfig, MiMCConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            leaf: None,
+            path: vec![(None, None); self.path.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let mimc = MiMCChip::configure(meta, [config.advice[0], config.advice[1]], config.coeff);
+        (config, mimc)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let mimc = MiMCChip::new(config.1);
+        let mut node = chip.load_private(layouter.namespace(|| "load leaf"), self.leaf)?;
+        for (i, (sibling, direction)) in self.path.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("level {}", i));
+            let level = chip.load_private_many(
+                layouter.namespace(|| "load sibling"),
+                &[*sibling, *direction],
+            )?;
+            // cond_swap also constrains the direction to be a bit
+            let (left, right) = chip.cond_swap(
+                layouter.namespace(|| "order"),
+                level[1].clone(),
+                node,
+                level[0].clone(),
+            )?;
+            node = mimc.hash(layouter.namespace(|| "hash"), left, right)?;
+        }
+        chip.expose_public(layouter.namespace(|| "expose root"), node, 0)
+    }
+}
+
+// proves a secret polynomial sum(c_i * z^i) of the given degree evaluates to y at z,
+// the coefficients are the witness and the public inputs are [z, y]
+#[derive(Clone, Default)]
+pub struct PrivatePolyCircuit {
+    pub coeffs: Vec<Option<Fp>>,
+    pub degree: usize,
+}
+
+impl PrivatePolyCircuit {
+    // degree + 1 rows each for the coefficients, the powers and the dot product, two more for
+    // loading z and the dot product's total
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(3 * self.degree + 5)
+    }
+}
+
+impl Circuit<Fp> for PrivatePolyCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            coeffs: vec![None; self.degree + 1],
+            degree: self.degree,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.coeffs.len() != self.degree + 1 {
+            return Err(Error::Synthesis);
+        }
+        let chip = MyChip::new(config);
+        let z = chip.load_public(layouter.namespace(|| "load z"), 0)?;
+        let coeffs = chip.load_private_many(layouter.namespace(|| "load coeffs"), &self.coeffs)?;
+        let mut powers = vec![chip.load_constant(layouter.namespace(|| "z^0"), Fp::one())?];
+        powers.extend(chip.powers(layouter.namespace(|| "powers of z"), z, self.degree)?);
+        let y = chip.dot_product(layouter.namespace(|| "sum c_i * z^i"), &coeffs, &powers)?;
+        chip.expose_public(layouter.namespace(|| "expose y"), y, 1)
+    }
+}
+
+// proves the private start reaches 1 within steps collatz steps. 1 is held fixed instead of
+// going on to 4, so a trajectory shorter than steps just pads with ones. steps is public
+// through the circuit shape and is also exposed on instance row 0
+#[derive(Clone, Default)]
+pub struct CollatzCircuit {
+    pub start: Option<Fp>,
+    pub steps: usize,
+}
+
+impl CollatzCircuit {
+    // every step takes 124 rows, most of them the u64 division by 2 and its comparison
+    pub fn k(&self) -> u32 {
+        min_k::<Self>((self.steps * 124 + 5).max(1 << RANGE_TABLE_BITS))
+    }
+}
+
+impl Circuit<Fp> for CollatzCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            start: None,
+            steps: self.steps,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let one = chip.load_constant(layouter.namespace(|| "one"), Fp::one())?;
+        let two = chip.load_constant(layouter.namespace(|| "two"), Fp::from(2))?;
+        let mut n = chip.load_private(layouter.namespace(|| "load start"), self.start)?;
+        for i in 0..self.steps {
+            let mut layouter = layouter.namespace(|| format!("step {}", i));
+            // the remainder is the parity of n, r < 2 makes it a bit
+            let (half, odd) =
+                chip.divrem_u64(layouter.namespace(|| "n / 2"), n.clone(), two.clone())?;
+            let triple =
+                chip.mul_by_constant(layouter.namespace(|| "3n"), n.clone(), Fp::from(3))?;
+            let triple = chip.add_constant(layouter.namespace(|| "3n + 1"), triple, Fp::one())?;
+            let next = chip.select(layouter.namespace(|| "branch"), odd, triple, half)?;
+            let done = chip.is_equal(layouter.namespace(|| "n == 1"), n, one.clone())?;
+            n = chip.select(layouter.namespace(|| "stay at 1"), done, one.clone(), next)?;
+        }
+        chip.assert_equals_constant(layouter.namespace(|| "reached 1"), n, Fp::one())?;
+        let steps =
+            chip.load_constant(layouter.namespace(|| "steps"), Fp::from(self.steps as u64))?;
+        chip.expose_public(layouter.namespace(|| "expose steps"), steps, 0)
+    }
+}
+
+// seed hashed n times with mimc under the key 0
+pub fn hash_chain(seed: Fp, n: usize) -> Fp {
+    (0..n).fold(seed, |h, _| mimc_hash(h, Fp::zero()))
+}
+
+// proves the public value is the private seed hashed n times, n = 0 exposes the seed itself
+#[derive(Clone, Default)]
+pub struct HashChainCircuit {
+    pub seed: Option<Fp>,
+    pub n: usize,
+}
+
+impl HashChainCircuit {
+    // a mimc region per link, the seed and the zero key
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(self.n * (MIMC_ROUNDS + 2) + 2)
+    }
+}
+
+impl Circuit<Fp> for HashChainCircuit {
+    type Config = (MyConfig, MiMCConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            seed: None,
+            n: self.n,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let mimc = MiMCChip::configure(meta, [config.advice[0], config.advice[1]], config.coeff);
+        (config, mimc)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let mimc = MiMCChip::new(config.1);
+        let key = chip.load_constant(layouter.namespace(|| "key"), Fp::zero())?;
+        let mut h = chip.load_private(layouter.namespace(|| "load seed"), self.seed)?;
+        for i in 0..self.n {
+            h = mimc.hash(layouter.namespace(|| format!("link {}", i)), h, key.clone())?;
+        }
+        chip.expose_public(layouter.namespace(|| "expose digest"), h, 0)
+    }
+}
+
+// poseidon with the P128Pow5T3 parameters over [x, 0], the digest PoseidonCircuit proves a
+// preimage of. a single element would be padded up to the rate of 2, and the Pow5 chip loads
+// padding from a fixed cell, whose value the halo2_proofs 0.1.0 prover never computes. with
+// the zero as a message word there is no padding and the chip can prove
+pub fn poseidon_hash(x: Fp) -> Fp {
+    poseidon_primitives::Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init()
+        .hash([x, Fp::zero()])
+}
+
+// proves knowledge of a private x with poseidon_hash(x) equal to the public digest, with the
+// Pow5 chip from halo2_gadgets sharing MyChip's advice columns
+#[derive(Clone, Default)]
+pub struct PoseidonCircuit {
+    pub x: Option<Fp>,
+}
+
+impl PoseidonCircuit {
+    // x and the zero, then 41 rows of Pow5 regions: the initial state, the absorbed words and
+    // a row per full round and per pair of partial rounds
+    pub fn k() -> u32 {
+        min_k::<Self>(43)
+    }
+}
+
+impl Circuit<Fp> for PoseidonCircuit {
+    type Config = (MyConfig, Pow5Config<Fp, 3, 2>);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    // the state is three of MyChip's advice columns and the partial sbox the fourth, only
+    // the round constants need columns of their own. the initial state comes from MyChip's
+    // constant column
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let [a, b, c, d] = config.advice;
+        let rc_a = [
+            meta.fixed_column(),
+            meta.fixed_column(),
+            meta.fixed_column(),
+        ];
+        let rc_b = [
+            meta.fixed_column(),
+            meta.fixed_column(),
+            meta.fixed_column(),
+        ];
+        let poseidon = Pow5Chip::configure::<P128Pow5T3>(meta, [a, b, c], d, rc_a, rc_b);
+        (config, poseidon)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        let zero = chip.load_constant(layouter.namespace(|| "zero"), Fp::zero())?;
+        let hasher = poseidon::Hash::<_, _, P128Pow5T3, ConstantLength<2>, 3, 2>::init(
+            Pow5Chip::construct(config.1),
+            layouter.namespace(|| "init"),
+        )?;
+        let digest = hasher.hash(layouter.namespace(|| "poseidon"), [x, zero])?;
+        chip.expose_public(layouter.namespace(|| "expose digest"), digest, 0)
+    }
+}
+
+// proves the private grid is a solved sudoku agreeing with the public clues. clues are the
+// grid positions given by the puzzle, the public inputs are the clue values in order.
+// every row, column and box is checked against {1..9} with the grand product from
+// PermutationCircuit, gamma is challenge(grid) worked out in the circuit
+#[derive(Clone, Default)]
+pub struct SudokuCircuit {
+    pub grid: Vec<Option<Fp>>,
+    pub clues: Vec<usize>,
+}
+
+impl SudokuCircuit {
+    // per cell a load, a mimc region and two 4 bit range checks with their offsets, per
+    // group nine adds and a product, plus the loads and exposes of the clues
+    pub fn k(&self) -> u32 {
+        min_k::<Self>(81 * (MIMC_ROUNDS + 15) + 27 * 28 + 2 * self.clues.len() + 64)
+    }
+}
+
+impl Circuit<Fp> for SudokuCircuit {
+    type Config = (MyConfig, MiMCConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            grid: vec![None; 81],
+            clues: self.clues.clone(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let mimc = MiMCChip::configure(meta, [config.advice[0], config.advice[1]], config.coeff);
+        (config, mimc)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if self.grid.len() != 81 || self.clues.iter().any(|&pos| pos >= 81) {
+            return Err(Error::Synthesis);
+        }
+        ByteTableChip::new(config.0.byte_table.clone())
+            .load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config.0);
+        let mimc = MiMCChip::new(config.1);
+        let grid = chip.load_private_many(layouter.namespace(|| "load grid"), &self.grid)?;
+        for (i, &pos) in self.clues.iter().enumerate() {
+            chip.expose_public(
+                layouter.namespace(|| format!("clue {}", pos)),
+                grid[pos].clone(),
+                i,
+            )?;
+        }
+        let gamma = assign_challenge(&chip, &mimc, layouter.namespace(|| "gamma"), &grid)?;
+
+        // v - 1 < 16 and 9 - v < 16 together leave exactly 1..=9
+        let nine = chip.load_constant(layouter.namespace(|| "nine"), Fp::from(9))?;
+        for (i, cell) in grid.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("cell {}", i));
+            let lo = chip.add_constant(layouter.namespace(|| "v - 1"), cell.clone(), -Fp::one())?;
+            chip.range_check(layouter.namespace(|| "v >= 1"), lo, 4)?;
+            let hi = chip.sub(layouter.namespace(|| "9 - v"), nine.clone(), cell.clone())?;
+            chip.range_check(layouter.namespace(|| "v <= 9"), hi, 4)?;
+        }
+
+        let digits = (1..=9)
+            .map(|d| {
+                chip.add_constant(
+                    layouter.namespace(|| format!("{} + gamma", d)),
+                    gamma.clone(),
+                    Fp::from(d),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let target = chip.product(layouter.namespace(|| "prod digits"), &digits)?;
+
+        let rows = (0..9).map(|r| (0..9).map(|c| r * 9 + c).collect::<Vec<_>>());
+        let cols = (0..9).map(|c| (0..9).map(|r| r * 9 + c).collect::<Vec<_>>());
+        let boxes = (0..9).map(|b| {
+            (0..9)
+                .map(|i| (b / 3 * 3 + i / 3) * 9 + b % 3 * 3 + i % 3)
+                .collect::<Vec<_>>()
+        });
+        for (g, group) in rows.chain(cols).chain(boxes).enumerate() {
+            let mut layouter = layouter.namespace(|| format!("group {}", g));
+            let shifted = group
+                .iter()
+                .map(|&pos| {
+                    chip.add(
+                        layouter.namespace(|| format!("cell {} + gamma", pos)),
+                        grid[pos].clone(),
+                        gamma.clone(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let prod = chip.product(layouter.namespace(|| "prod"), &shifted)?;
+            chip.assert_equal(layouter.namespace(|| "is 1..9"), prod, target.clone())?;
+        }
+        Ok(())
+    }
+}
+
+// hiding commitment to the two balances of a transfer
+pub fn state_commitment(sender: Fp, receiver: Fp, salt: Fp) -> Fp {
+    commit(mimc_hash(sender, receiver), salt)
+}
+
+// proves a private transfer of amount from sender to receiver keeps the balances valid.
+// the public inputs are the commitments [before, after] to the two balances, the amount
+// is at most the sender's balance and all balances are u64
+#[derive(Clone, Default)]
+pub struct TransferCircuit {
+    pub sender_before: Option<Fp>,
+    pub receiver_before: Option<Fp>,
+    pub amount: Option<Fp>,
+    pub salt_before: Option<Fp>,
+    pub salt_after: Option<Fp>,
+}
+
+impl TransferCircuit {
+    // four mimc regions, the 64 bit comparison and range checks take about 150 rows
+    pub fn k() -> u32 {
+        min_k::<Self>(4 * (MIMC_ROUNDS + 2) + 160)
+    }
+}
+
+impl Circuit<Fp> for TransferCircuit {
+    type Config = (MyConfig, MiMCConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let mimc = MiMCChip::configure(meta, [config.advice[0], config.advice[1]], config.coeff);
+        (config, mimc)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        ByteTableChip::new(config.0.byte_table.clone())
+            .load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config.0);
+        let mimc = MiMCChip::new(config.1);
+        let w = chip.load_private_many(
+            layouter.namespace(|| "load witness"),
+            &[
+                self.sender_before,
+                self.receiver_before,
+                self.amount,
+                self.salt_before,
+                self.salt_after,
+            ],
+        )?;
+        let (sender, receiver, amount) = (w[0].clone(), w[1].clone(), w[2].clone());
+        for (name, v) in [
+            ("sender", &sender),
+            ("receiver", &receiver),
+            ("amount", &amount),
+        ] {
+            chip.range_check(layouter.namespace(|| name), v.clone(), 64)?;
+        }
+        let overdraft = chip.lt(
+            layouter.namespace(|| "sender < amount"),
+            sender.clone(),
+            amount.clone(),
+            64,
+        )?;
+        chip.assert_equals_constant(layouter.namespace(|| "no overdraft"), overdraft, Fp::zero())?;
+        let sender_after = chip.sub(
+            layouter.namespace(|| "sender - amount"),
+            sender.clone(),
+            amount.clone(),
+        )?;
+        let receiver_after = chip.add(
+            layouter.namespace(|| "receiver + amount"),
+            receiver.clone(),
+            amount,
+        )?;
+        // the receiver can't wrap past u64 either
+        chip.range_check(
+            layouter.namespace(|| "receiver after"),
+            receiver_after.clone(),
+            64,
+        )?;
+
+        let before = mimc.hash(layouter.namespace(|| "balances before"), sender, receiver)?;
+        let before = mimc.hash(layouter.namespace(|| "commit before"), before, w[3].clone())?;
+        let after = mimc.hash(
+            layouter.namespace(|| "balances after"),
+            sender_after,
+            receiver_after,
+        )?;
+        let after = mimc.hash(layouter.namespace(|| "commit after"), after, w[4].clone())?;
+        chip.expose_public_many(layouter.namespace(|| "expose states"), &[before, after], 0)?;
+        Ok(())
+    }
+}
+
+// width of x and the bounds in IntervalCircuit
+pub const INTERVAL_BITS: usize = 32;
+
+// proves a <= x <= b for a private x and public [a, b], an interval with a > b has no x
+#[derive(Clone, Default)]
+pub struct IntervalCircuit {
+    pub x: Option<Fp>,
+}
+
+impl IntervalCircuit {
+    // two comparisons of about INTERVAL_BITS + 10 rows and three range checks, well under the
+    // byte table
+    pub fn k() -> u32 {
+        min_k::<Self>(1 << RANGE_TABLE_BITS)
+    }
+}
+
+impl Circuit<Fp> for IntervalCircuit {
+    type Config = MyConfig;
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+        let chip = MyChip::new(config);
+        let a = chip.load_public(layouter.namespace(|| "load a"), 0)?;
+        let b = chip.load_public(layouter.namespace(|| "load b"), 1)?;
+        let x = chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+        // lt is only meaningful on values that fit, the bounds included
+        for (name, v) in [("a", &a), ("b", &b), ("x", &x)] {
+            chip.range_check(layouter.namespace(|| name), v.clone(), INTERVAL_BITS as u8)?;
+        }
+        let below = chip.lt(layouter.namespace(|| "x < a"), x.clone(), a, INTERVAL_BITS)?;
+        chip.assert_equals_constant(layouter.namespace(|| "a <= x"), below, Fp::zero())?;
+        let above = chip.lt(layouter.namespace(|| "b < x"), b, x, INTERVAL_BITS)?;
+        chip.assert_equals_constant(layouter.namespace(|| "x <= b"), above, Fp::zero())
+    }
+}
+
+// hiding commitment to x, the randomness r goes in as the mimc key
+pub fn commit(x: Fp, r: Fp) -> Fp {
+    mimc_hash(x, r)
+}
+
+// opens the public commitment to a secret x and proves x^3 + x + constant = result about it
+// in the same proof, public inputs are [commitment, result]
+#[derive(Clone, Default)]
+pub struct CommitmentCircuit {
+    pub constant: Fp,
+    pub x: Option<Fp>,
+    pub r: Option<Fp>,
+}
+
+impl CommitmentCircuit {
+    // one mimc region plus the loads and the handful of rows for the cubic
+    pub fn k() -> u32 {
+        min_k::<Self>(MIMC_ROUNDS + 12)
+    }
+}
+
+impl Circuit<Fp> for CommitmentCircuit {
+    type Config = (MyConfig, MiMCConfig);
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            constant: self.constant,
+            x: None,
+            r: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let mimc = MiMCChip::configure(meta, [config.advice[0], config.advice[1]], config.coeff);
+        (config, mimc)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let mimc = MiMCChip::new(config.1);
+        let opening =
+            chip.load_private_many(layouter.namespace(|| "load x, r"), &[self.x, self.r])?;
+        let (x, r) = (opening[0].clone(), opening[1].clone());
+        let commitment = mimc.hash(layouter.namespace(|| "commit"), x.clone(), r)?;
+        chip.expose_public(layouter.namespace(|| "expose commitment"), commitment, 0)?;
+
+        let x_3 = chip.cube(layouter.namespace(|| "x3"), x.clone())?;
+        let x_3_x_5 = x3_x_constant(&chip, &mut layouter, x_3, x, self.constant)?;
+        chip.expose_public(layouter.namespace(|| "expose res"), x_3_x_5, 1)
+    }
+}
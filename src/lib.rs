@@ -0,0 +1,6 @@
+// chips are the building blocks, circuits wire them up for one statement each and proof
+// runs a circuit through keygen, proving and verification
+pub mod chip;
+pub mod circuit;
+pub mod proof;
+pub mod registry;
@@ -0,0 +1,394 @@
+// the keygen, prove and verify steps of the pipeline, generic over the circuit so every
+// circuit in the crate is proven the same way. proofs are blake2b transcripts over pasta
+use crate::circuit::{MultiOutputCircuit, MyCircuit};
+use halo2_proofs::{
+    arithmetic::Field,
+    pasta::group::ff::PrimeField,
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey,
+        SingleVerifier, VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand::rngs::OsRng;
+use std::io::{self, Read};
+
+// params for 2^k rows and the proving key of the circuit, the verifying key is pk.get_vk()
+pub fn keygen<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+) -> Result<(Params<EqAffine>, ProvingKey<EqAffine>), Error> {
+    let params: Params<EqAffine> = Params::new(k);
+    let vk = keygen_vk(&params, circuit)?;
+    let pk = keygen_pk(&params, vk, circuit)?;
+    Ok((params, pk))
+}
+
+// proves the circuit with its single instance column filled with publics
+pub fn create_proof_bytes<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: &C,
+    publics: &[Fp],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        params,
+        pk,
+        std::slice::from_ref(circuit),
+        &[&[publics]],
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    // the bytes are not a proof at all, cut short, followed by trailing bytes or holding
+    // something that does not decode as a point or scalar
+    Malformed(io::Error),
+    // the public inputs do not fit the instance column
+    PublicInputs(Error),
+    // a well formed proof that does not satisfy the constraints for these public inputs
+    Unsatisfied,
+    Other(Error),
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::Malformed(e) => write!(f, "malformed proof: {}", e),
+            VerifyError::PublicInputs(e) => write!(f, "invalid public inputs: {:?}", e),
+            VerifyError::Unsatisfied => write!(f, "proof does not satisfy the constraints"),
+            VerifyError::Other(e) => write!(f, "verification failed: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+// the proof bytes as the transcript reads them. halo2 turns read errors in the opening part
+// of the proof into Error::Opening like a failed check, so running out is tracked here
+struct ProofReader<'a> {
+    bytes: &'a [u8],
+    ran_out: bool,
+}
+
+impl Read for ProofReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.bytes.read(buf)?;
+        if n < buf.len() {
+            self.ran_out = true;
+        }
+        Ok(n)
+    }
+}
+
+// needs only the verifying key, the prover side is not involved
+pub fn verify_proof_bytes(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    publics: &[Fp],
+) -> Result<(), VerifyError> {
+    let strategy = SingleVerifier::new(params);
+    let mut reader = ProofReader {
+        bytes: proof,
+        ran_out: false,
+    };
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&mut reader);
+    let verified = verify_proof(params, vk, strategy, &[&[publics]], &mut transcript);
+    if reader.ran_out {
+        return Err(VerifyError::Malformed(io::ErrorKind::UnexpectedEof.into()));
+    }
+    match verified {
+        Ok(()) if !reader.bytes.is_empty() => Err(VerifyError::Malformed(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "trailing bytes after the proof",
+        ))),
+        Ok(()) => Ok(()),
+        Err(Error::Transcript(e)) => Err(VerifyError::Malformed(e)),
+        Err(e @ (Error::InvalidInstances | Error::InstanceTooLarge)) => {
+            Err(VerifyError::PublicInputs(e))
+        }
+        Err(Error::ConstraintSystemFailure | Error::Opening) => Err(VerifyError::Unsatisfied),
+        Err(e) => Err(VerifyError::Other(e)),
+    }
+}
+
+// the smallest k MyCircuit fits in
+pub const MIN_K: u32 = 4;
+pub use crate::chip::MAX_K;
+
+#[derive(Debug)]
+pub enum ProveError {
+    // result is not x ^ 3 + x + constant, so there is nothing true to prove
+    WrongResult { expected: Fp, got: Fp },
+    KTooSmall { k: u32, min: u32 },
+    KTooLarge { k: u32, max: u32 },
+    Keygen(Error),
+    Proof(Error),
+}
+
+impl std::fmt::Display for ProveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProveError::WrongResult { expected, got } => write!(
+                f,
+                "result {:?} does not match x ^ 3 + x + constant = {:?}",
+                got, expected
+            ),
+            ProveError::KTooSmall { k, min } => {
+                write!(
+                    f,
+                    "k = {} is too small, the circuit needs at least {}",
+                    k, min
+                )
+            }
+            ProveError::KTooLarge { k, max } => write!(f, "k = {} is above the maximum {}", k, max),
+            ProveError::Keygen(e) => write!(f, "key generation failed: {:?}", e),
+            ProveError::Proof(e) => write!(f, "proving failed: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProveError {}
+
+fn check_k(k: u32) -> Result<(), ProveError> {
+    if k < MIN_K {
+        return Err(ProveError::KTooSmall { k, min: MIN_K });
+    }
+    if k > MAX_K {
+        return Err(ProveError::KTooLarge { k, max: MAX_K });
+    }
+    Ok(())
+}
+
+fn check_result(x: Fp, constant: Fp, result: Fp) -> Result<(), ProveError> {
+    let expected = x.cube() + x + constant;
+    if result != expected {
+        return Err(ProveError::WrongResult {
+            expected,
+            got: result,
+        });
+    }
+    Ok(())
+}
+
+// params and the proving key of MyCircuit for one constant, made once and reused for any
+// number of proofs. the verifying key and k are what a verifier needs besides the params
+pub struct Prover {
+    k: u32,
+    constant: Fp,
+    params: Params<EqAffine>,
+    pk: ProvingKey<EqAffine>,
+}
+
+impl Prover {
+    pub fn setup(k: u32, constant: Fp) -> Result<Self, ProveError> {
+        check_k(k)?;
+        let circuit = MyCircuit { constant, x: None };
+        let (params, pk) = keygen(k, &circuit).map_err(ProveError::Keygen)?;
+        Ok(Self {
+            k,
+            constant,
+            params,
+            pk,
+        })
+    }
+
+    // only synthesis and create_proof, the keys are already there
+    pub fn prove(&self, x: Fp, result: Fp) -> Result<Vec<u8>, ProveError> {
+        check_result(x, self.constant, result)?;
+        let circuit = MyCircuit {
+            constant: self.constant,
+            x: Some(x),
+        };
+        create_proof_bytes(&self.params, &self.pk, &circuit, &[result]).map_err(ProveError::Proof)
+    }
+
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    pub fn constant(&self) -> Fp {
+        self.constant
+    }
+
+    pub fn params(&self) -> &Params<EqAffine> {
+        &self.params
+    }
+
+    pub fn vk(&self) -> &VerifyingKey<EqAffine> {
+        self.pk.get_vk()
+    }
+}
+
+#[derive(Debug)]
+pub enum ArtifactError {
+    // params bytes that are not what Params::write produces for some k up to MAX_K
+    Params(io::Error),
+    // the constant bytes are not the 32 byte encoding of a field element
+    Constant,
+    Keygen(Error),
+}
+
+impl std::fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactError::Params(e) => write!(f, "invalid params: {}", e),
+            ArtifactError::Constant => write!(f, "constant is not a 32 byte field element"),
+            ArtifactError::Keygen(e) => write!(f, "key generation failed: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactError {}
+
+// the verifying side of a Prover, params and verifying key without the proving key.
+// halo2 0.1.0 cannot serialize a verifying key, so what gets stored is the params and
+// MyCircuit's constant, and from_params_and_constant rebuilds the vk from them with keygen_vk.
+// that comes out the same as the prover's since keygen is deterministic in the params and
+// the circuit, but it means a keygen_vk per load and a Verifier only ever checks MyCircuit
+pub struct Verifier {
+    k: u32,
+    constant: Fp,
+    params: Params<EqAffine>,
+    vk: VerifyingKey<EqAffine>,
+}
+
+impl Verifier {
+    pub fn from_prover(prover: &Prover) -> Self {
+        Self {
+            k: prover.k,
+            constant: prover.constant,
+            params: prover.params.clone(),
+            vk: prover.vk().clone(),
+        }
+    }
+
+    // params as written by to_params_and_constant, or Params::write, and the 32 byte
+    // constant of the MyCircuit the proofs are for
+    pub fn from_params_and_constant(params: &[u8], constant: &[u8]) -> Result<Self, ArtifactError> {
+        let constant = <[u8; 32]>::try_from(constant)
+            .ok()
+            .and_then(|repr| Option::from(Fp::from_repr(repr)))
+            .ok_or(ArtifactError::Constant)?;
+        let (k, params) = read_params(params).map_err(ArtifactError::Params)?;
+        let circuit = MyCircuit { constant, x: None };
+        let vk = keygen_vk(&params, &circuit).map_err(ArtifactError::Keygen)?;
+        Ok(Self {
+            k,
+            constant,
+            params,
+            vk,
+        })
+    }
+
+    // params bytes and constant bytes for from_params_and_constant
+    pub fn to_params_and_constant(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut params = vec![];
+        self.params
+            .write(&mut params)
+            .expect("writing to a vec cannot fail");
+        (params, self.constant.to_repr().to_vec())
+    }
+
+    pub fn verify(&self, proof: &[u8], public_inputs: &[Fp]) -> Result<(), VerifyError> {
+        verify_proof_bytes(&self.params, &self.vk, proof, public_inputs)
+    }
+
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    pub fn params(&self) -> &Params<EqAffine> {
+        &self.params
+    }
+
+    pub fn vk(&self) -> &VerifyingKey<EqAffine> {
+        &self.vk
+    }
+}
+
+// Params::read trusts the k up front, so that and the length are checked before reading.
+// the bytes are k, 2^k points twice and two more points, 32 bytes each
+fn read_params(bytes: &[u8]) -> io::Result<(u32, Params<EqAffine>)> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let k = bytes
+        .get(..4)
+        .map(|k| u32::from_le_bytes([k[0], k[1], k[2], k[3]]))
+        .ok_or_else(|| invalid("too short for k"))?;
+    if k > MAX_K {
+        return Err(invalid("k is above MAX_K"));
+    }
+    if bytes.len() != 4 + ((2 << k) + 2) * 32 {
+        return Err(invalid("wrong length for k"));
+    }
+    Ok((k, Params::read(&mut &bytes[..])?))
+}
+
+// proves knowledge of x with x ^ 3 + x + constant = result using MyCircuit, so result is
+// the only public input. params and keys are made fresh for 2^k rows, the inputs are checked
+// first so a bad call fails before any keygen. use a Prover for more than one proof
+pub fn prove(x: Fp, constant: Fp, result: Fp, k: u32) -> Result<Vec<u8>, ProveError> {
+    check_result(x, constant, result)?;
+    Prover::setup(k, constant)?.prove(x, result)
+}
+
+// checks a proof from prove against keys for MyCircuit with the same constant and k
+pub fn verify(
+    proof: &[u8],
+    result: Fp,
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+) -> Result<(), VerifyError> {
+    verify_proof_bytes(params, vk, proof, &[result])
+}
+
+#[derive(Debug)]
+pub enum DemoError {
+    Prove(ProveError),
+    Verify(VerifyError),
+}
+
+impl std::fmt::Display for DemoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DemoError::Prove(e) => write!(f, "{}", e),
+            DemoError::Verify(e) => write!(f, "proof did not verify: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DemoError {}
+
+// the cubic demo the binary and examples/cubic.rs run, proves knowledge of x with
+// x ^ 3 + x + 5 = 35 and returns the proof once it has verified
+pub fn cubic_demo() -> Result<Vec<u8>, DemoError> {
+    // 3 is the solution, any other x fails the circuit. Fp is an integer in the field, p is
+    // the size of the field which is very large
+    let x = Fp::from(3);
+    // constant in our equation, never changes
+    let constant = Fp::from(5);
+    // x is advice and never published itself, but x * x and x * x * x are public next to the
+    // result, and x2 gives x away up to its sign. MyCircuit keeps only the result public
+    let public_inputs = [Fp::from(9), Fp::from(27), Fp::from(35)];
+    let circuit = MultiOutputCircuit {
+        constant,
+        x: Some(x),
+    };
+
+    // 2^4 rows fit the circuit. keygen makes the params and the proving key, the verifying
+    // key is inside it
+    let (params, pk) = keygen(4, &circuit.without_witnesses())
+        .map_err(|e| DemoError::Prove(ProveError::Keygen(e)))?;
+    let proof = create_proof_bytes(&params, &pk, &circuit, &public_inputs)
+        .map_err(|e| DemoError::Prove(ProveError::Proof(e)))?;
+    // the verifier has neither the circuit witness nor x, only the params and the verifying key
+    verify_proof_bytes(&params, pk.get_vk(), &proof, &public_inputs).map_err(DemoError::Verify)?;
+    Ok(proof)
+}
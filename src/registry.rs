@@ -0,0 +1,178 @@
+// circuits selectable by name at runtime, each entry builds its circuit from a json witness
+// and knows its k and the public inputs it expects
+use crate::{
+    circuit::{FibonacciCircuit, IntervalCircuit, MyCircuit},
+    proof::{create_proof_bytes, keygen, verify_proof_bytes, VerifyError, MAX_K},
+};
+use halo2_proofs::{arithmetic::FieldExt, pasta::Fp, plonk::Circuit};
+use serde_json::Value;
+
+#[derive(Debug)]
+pub enum RegistryError {
+    UnknownCircuit {
+        name: String,
+        available: Vec<&'static str>,
+    },
+    Json(String),
+    // a witness field that is missing or not a non negative integer
+    BadField(&'static str),
+    WrongPublicCount {
+        expected: usize,
+        got: usize,
+    },
+    // the witness asks for more rows than params can be made for
+    KTooLarge {
+        k: u32,
+        max: u32,
+    },
+    Proof(halo2_proofs::plonk::Error),
+    Verify(VerifyError),
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::UnknownCircuit { name, available } => write!(
+                f,
+                "no circuit named {:?}, available are {}",
+                name,
+                available.join(", ")
+            ),
+            RegistryError::Json(e) => write!(f, "invalid json: {}", e),
+            RegistryError::BadField(field) => {
+                write!(f, "witness field {:?} is missing or not an integer", field)
+            }
+            RegistryError::WrongPublicCount { expected, got } => {
+                write!(f, "expected {} public inputs, got {}", expected, got)
+            }
+            RegistryError::KTooLarge { k, max } => {
+                write!(f, "the circuit needs k = {}, above the maximum {}", k, max)
+            }
+            RegistryError::Proof(e) => write!(f, "proving failed: {:?}", e),
+            RegistryError::Verify(e) => write!(f, "proof did not verify: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+// proves with the given public inputs and verifies the proof
+type ProveAndVerify = Box<dyn Fn(&[Fp]) -> Result<(), RegistryError>>;
+
+// a circuit built from a witness, with the type erased so every entry looks the same
+pub struct Built {
+    pub k: u32,
+    prove_and_verify: ProveAndVerify,
+}
+
+impl Built {
+    fn new<C: Circuit<Fp> + 'static>(circuit: C, k: u32) -> Result<Self, RegistryError> {
+        if k > MAX_K {
+            return Err(RegistryError::KTooLarge { k, max: MAX_K });
+        }
+        Ok(Self {
+            k,
+            prove_and_verify: Box::new(move |publics| round_trip(&circuit, k, publics)),
+        })
+    }
+}
+
+pub struct Entry {
+    pub name: &'static str,
+    // what goes on the instance rows, in order
+    pub public_inputs: &'static [&'static str],
+    pub build: fn(&Value) -> Result<Built, RegistryError>,
+}
+
+pub fn entries() -> Vec<Entry> {
+    vec![
+        Entry {
+            name: "cubic",
+            public_inputs: &["x^3 + x + constant"],
+            build: |w| {
+                let circuit = MyCircuit {
+                    constant: field(w, "constant")?,
+                    x: Some(field(w, "x")?),
+                };
+                Built::new(circuit, MyCircuit::k())
+            },
+        },
+        Entry {
+            name: "fibonacci",
+            public_inputs: &["f(n)"],
+            build: |w| {
+                let circuit = FibonacciCircuit {
+                    a: Some(field(w, "a")?),
+                    b: Some(field(w, "b")?),
+                    n: w["n"].as_u64().ok_or(RegistryError::BadField("n"))? as usize,
+                };
+                let k = circuit.k();
+                Built::new(circuit, k)
+            },
+        },
+        Entry {
+            name: "range",
+            public_inputs: &["a", "b"],
+            build: |w| {
+                let circuit = IntervalCircuit {
+                    x: Some(field(w, "x")?),
+                };
+                Built::new(circuit, IntervalCircuit::k())
+            },
+        },
+    ]
+}
+
+pub fn find(name: &str) -> Result<Entry, RegistryError> {
+    let entries = entries();
+    let available = entries.iter().map(|e| e.name).collect();
+    entries
+        .into_iter()
+        .find(|e| e.name == name)
+        .ok_or(RegistryError::UnknownCircuit {
+            name: name.to_string(),
+            available,
+        })
+}
+
+// builds the named circuit from a json object witness and proves and verifies it against a
+// json array of public inputs. values are integers, or decimal strings for ones past u64
+pub fn run(name: &str, witness_json: &str, publics_json: &str) -> Result<(), RegistryError> {
+    let entry = find(name)?;
+    let witness: Value =
+        serde_json::from_str(witness_json).map_err(|e| RegistryError::Json(e.to_string()))?;
+    let publics: Value =
+        serde_json::from_str(publics_json).map_err(|e| RegistryError::Json(e.to_string()))?;
+    let publics = publics
+        .as_array()
+        .ok_or_else(|| RegistryError::Json("public inputs must be an array".to_string()))?
+        .iter()
+        .map(|v| to_field(v).ok_or(RegistryError::BadField("public input")))
+        .collect::<Result<Vec<_>, _>>()?;
+    if publics.len() != entry.public_inputs.len() {
+        return Err(RegistryError::WrongPublicCount {
+            expected: entry.public_inputs.len(),
+            got: publics.len(),
+        });
+    }
+    let built = (entry.build)(&witness)?;
+    (built.prove_and_verify)(&publics)
+}
+
+fn to_field(v: &Value) -> Option<Fp> {
+    match v {
+        Value::Number(n) => n.as_u64().map(Fp::from),
+        Value::String(s) => s.parse::<u128>().ok().map(Fp::from_u128),
+        _ => None,
+    }
+}
+
+fn field(w: &Value, name: &'static str) -> Result<Fp, RegistryError> {
+    to_field(&w[name]).ok_or(RegistryError::BadField(name))
+}
+
+fn round_trip<C: Circuit<Fp>>(circuit: &C, k: u32, publics: &[Fp]) -> Result<(), RegistryError> {
+    let (params, pk) = keygen(k, circuit).map_err(RegistryError::Proof)?;
+    let proof = create_proof_bytes(&params, &pk, circuit, publics).map_err(RegistryError::Proof)?;
+    verify_proof_bytes(&params, pk.get_vk(), &proof, publics).map_err(RegistryError::Verify)
+}
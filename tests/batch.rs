@@ -0,0 +1,374 @@
+// instructions working over a whole slice of cells in one region
+#[macro_use]
+mod common;
+
+use common::{copies, f, mock, prove_and_verify, regions, rows};
+use halo2::chip::AccumulatorChip;
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip},
+    pasta::Fp,
+};
+
+// the sum of every private input, loaded in one region
+gadget_circuit!(LoadManyCircuit, |chip, layouter, w, n| {
+    let values = chip.load_private_many(layouter.namespace(|| "inputs"), w)?;
+    assert_eq!(values.len(), w.len());
+    let total = chip.sum(layouter.namespace(|| "sum"), &values)?;
+    chip.expose_public(layouter.namespace(|| "total"), total, 0)
+});
+
+// the same with a load_private region per input
+gadget_circuit!(LoadLoopCircuit, |chip, layouter, w, n| {
+    let values = w
+        .iter()
+        .map(|v| chip.load_private(layouter.namespace(|| "input"), *v))
+        .collect::<Result<Vec<_>, _>>()?;
+    let total = chip.sum(layouter.namespace(|| "sum"), &values)?;
+    chip.expose_public(layouter.namespace(|| "total"), total, 0)
+});
+
+// nothing but the inputs
+gadget_circuit!(LoadOnlyCircuit, |chip, layouter, w, n| {
+    let values = chip.load_private_many(layouter.namespace(|| "inputs"), w)?;
+    assert_eq!(values.len(), w.len());
+    Ok(())
+});
+
+#[test]
+fn load_private_many_of_sixteen() {
+    let inputs: Vec<u64> = (1..=16).collect();
+    let batched = LoadManyCircuit::new(&inputs, 0);
+    let looped = LoadLoopCircuit::new(&inputs, 0);
+    assert_eq!(mock(6, &batched, vec![f(136)]), Ok(()));
+    assert!(mock(6, &batched, vec![f(135)]).is_err());
+    assert!(prove_and_verify(6, &batched, &[f(136)]).is_ok());
+    assert_eq!(mock(6, &looped, vec![f(136)]), Ok(()));
+    // every load is a row of advice[0] either way, the loop pays a region per input
+    assert!(rows(&batched, &[f(136)]) <= rows(&looped, &[f(136)]));
+    assert_eq!(
+        regions(&batched, &[f(136)]) + 15,
+        regions(&looped, &[f(136)])
+    );
+}
+
+#[test]
+fn load_private_many_keeps_the_order() {
+    let circuit = LoadManyCircuit::new(&[3, 5], 0);
+    assert_eq!(mock(4, &circuit, vec![f(8)]), Ok(()));
+    // 16 values down one column, one row each
+    assert_eq!(rows(&LoadOnlyCircuit::new(&[0; 16], 0), &[]), 16);
+}
+
+#[test]
+fn load_private_many_of_nothing_makes_no_region() {
+    let empty = LoadOnlyCircuit::new(&[], 0);
+    assert_eq!(mock(4, &empty, vec![]), Ok(()));
+    assert_eq!(rows(&empty, &[]), 0);
+    assert_eq!(regions(&empty, &[]), 0);
+    assert_eq!(copies(&empty, &[]), 0);
+}
+
+// loads [1, 2, 1, 3, 2] at once and 1 and 4 again afterwards, checks which cells are shared and
+// publishes 1 + 2 + 1 + 3 + 2 + 1 + 4 from the returned cells
+gadget_circuit!(ConstantsCircuit, |chip, layouter, w, n| {
+    let cells = chip.load_constants(
+        layouter.namespace(|| "constants"),
+        &[f(1), f(2), f(1), f(3), f(2)],
+    )?;
+    let one = chip.load_constant(layouter.namespace(|| "one again"), f(1))?;
+    let four = chip.load_constant(layouter.namespace(|| "four"), f(4))?;
+    let at = |c: &AssignedCell<Fp, Fp>| format!("{:?}", c.cell());
+    // duplicates are the very same cell, region and offset included
+    assert_eq!(at(&cells[0]), at(&cells[2]));
+    assert_eq!(at(&cells[1]), at(&cells[4]));
+    assert_ne!(at(&cells[0]), at(&cells[1]));
+    assert_ne!(at(&cells[1]), at(&cells[3]));
+    // and so is a later load_constant of a value already loaded
+    assert_eq!(at(&cells[0]), at(&one));
+    let mut all = cells;
+    all.push(one);
+    all.push(four);
+    let total = chip.sum(layouter.namespace(|| "sum"), &all)?;
+    chip.expose_public(layouter.namespace(|| "total"), total, 0)
+});
+
+// 1 loaded with load_constant as often as n says
+gadget_circuit!(RepeatedConstantCircuit, |chip, layouter, w, n| {
+    for _ in 0..n {
+        chip.load_constant(layouter.namespace(|| "one"), f(1))?;
+    }
+    Ok(())
+});
+
+#[test]
+fn load_constants_deduplicates() {
+    let circuit = ConstantsCircuit::new(&[], 0);
+    assert_eq!(mock(5, &circuit, vec![f(14)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(13)]).is_err());
+    // a region of three rows for 1, 2, 3, one more for 4 and the sum
+    assert_eq!(regions(&ConstantsCircuit::new(&[], 0), &[f(14)]), 3);
+}
+
+#[test]
+fn load_constant_is_cached() {
+    let once = RepeatedConstantCircuit::new(&[], 1);
+    let ten = RepeatedConstantCircuit::new(&[], 10);
+    assert_eq!(mock(4, &ten, vec![]), Ok(()));
+    assert_eq!(rows(&once, &[]), rows(&ten, &[]));
+    assert_eq!(regions(&once, &[]), regions(&ten, &[]));
+}
+
+// the product of n copies of x, one mul_many region or n - 1 mul regions when w has a second
+// entry
+gadget_circuit!(MulManyCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let copies = vec![x; n];
+    let y = if w.len() == 1 {
+        chip.mul_many(layouter.namespace(|| "x^n"), &copies)?
+    } else {
+        let mut y = copies[0].clone();
+        for (i, c) in copies[1..].iter().enumerate() {
+            y = chip.mul(layouter.namespace(|| format!("mul {}", i)), y, c.clone())?;
+        }
+        y
+    };
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn mul_many_of_eight() {
+    let circuit = MulManyCircuit::new(&[3], 8);
+    assert_eq!(mock(5, &circuit, vec![f(6561)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(6560)]).is_err());
+    assert!(prove_and_verify(5, &circuit, &[f(6561)]).is_ok());
+}
+
+#[test]
+fn mul_many_edge_cases() {
+    assert_eq!(mock(4, &MulManyCircuit::new(&[3], 0), vec![f(1)]), Ok(()));
+    assert!(mock(4, &MulManyCircuit::new(&[3], 0), vec![f(3)]).is_err());
+    assert_eq!(mock(4, &MulManyCircuit::new(&[3], 1), vec![f(3)]), Ok(()));
+}
+
+#[test]
+fn mul_many_uses_fewer_rows_than_a_mul_chain() {
+    let single = MulManyCircuit::new(&[3], 8);
+    let chain = MulManyCircuit::new(&[3, 0], 8);
+    assert_eq!(mock(5, &chain, vec![f(6561)]), Ok(()));
+    // eight rows for the chain in one region against two rows for each of seven muls
+    let (single_rows, chain_rows) = (rows(&single, &[f(6561)]), rows(&chain, &[f(6561)]));
+    assert_eq!(chain_rows - single_rows, 6);
+    assert_eq!(
+        regions(&single, &[f(6561)]) + 6,
+        regions(&chain, &[f(6561)])
+    );
+}
+
+// x^3 + x + 5 built from powers(x, 3), n = 3 is the cubic
+gadget_circuit!(PowersCubicCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let p = chip.powers(layouter.namespace(|| "powers"), x, 3)?;
+    let y = chip.add(layouter.namespace(|| "x3 + x"), p[2].clone(), p[0].clone())?;
+    let y = chip.add_constant(layouter.namespace(|| "+ 5"), y, f(5))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// every power of x up to n, published in order
+gadget_circuit!(PowersCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let p = chip.powers(layouter.namespace(|| "powers"), x, n)?;
+    assert_eq!(p.len(), n);
+    chip.expose_public_many(layouter.namespace(|| "powers"), &p, 0)?;
+    Ok(())
+});
+
+#[test]
+fn powers_make_the_cubic() {
+    let circuit = PowersCubicCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &circuit, vec![f(35)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(36)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(35)]).is_ok());
+}
+
+#[test]
+fn powers() {
+    let expected: Vec<_> = (1..=6).map(|i| common::pow(f(3), i)).collect();
+    assert_eq!(
+        mock(4, &PowersCircuit::new(&[3], 6), expected.clone()),
+        Ok(())
+    );
+    let mut wrong = expected;
+    wrong.swap(1, 2);
+    assert!(mock(4, &PowersCircuit::new(&[3], 6), wrong).is_err());
+}
+
+#[test]
+fn powers_edge_cases() {
+    assert_eq!(mock(4, &PowersCircuit::new(&[3], 0), vec![]), Ok(()));
+    assert_eq!(mock(4, &PowersCircuit::new(&[3], 1), vec![f(3)]), Ok(()));
+    assert!(mock(4, &PowersCircuit::new(&[3], 1), vec![f(9)]).is_err());
+}
+
+// every prefix sum of the inputs, the n-th one (counting from 1) is published
+gadget_circuit!(RunningSumCircuit, |chip, layouter, w, n| {
+    let inputs = chip.load_private_many(layouter.namespace(|| "inputs"), w)?;
+    let prefixes = chip.running_sum(layouter.namespace(|| "prefixes"), &inputs)?;
+    assert_eq!(prefixes.len(), w.len());
+    chip.expose_public(layouter.namespace(|| "prefix"), prefixes[n - 1].clone(), 0)
+});
+
+// running_sum alone over n copies of one input
+gadget_circuit!(RunningSumRowsCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    chip.running_sum(layouter.namespace(|| "prefixes"), &vec![x; n])?;
+    Ok(())
+});
+
+#[test]
+fn running_sum_of_ten() {
+    let inputs: Vec<u64> = (1..=10).collect();
+    // 1 + 2 + 3 + 4 + 5
+    let circuit = RunningSumCircuit::new(&inputs, 5);
+    assert_eq!(mock(5, &circuit, vec![f(15)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(55)]).is_err());
+    assert!(prove_and_verify(5, &circuit, &[f(15)]).is_ok());
+    for i in 1..=10 {
+        let circuit = RunningSumCircuit::new(&inputs, i);
+        assert_eq!(
+            mock(5, &circuit, vec![f(i as u64 * (i as u64 + 1) / 2)]),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn running_sum_takes_n_plus_one_rows() {
+    // a row for the load of x, then the zero and one row per input
+    for n in [0, 1, 4, 10] {
+        let circuit = RunningSumRowsCircuit::new(&[1], n);
+        assert_eq!(rows(&circuit, &[]), 1 + n + 1, "n = {}", n);
+    }
+}
+
+// every prefix product of the inputs published in order, n = 1 publishes only the last one
+gadget_circuit!(RunningProductCircuit, |chip, layouter, w, n| {
+    let inputs = chip.load_private_many(layouter.namespace(|| "inputs"), w)?;
+    let prefixes = chip.running_product(layouter.namespace(|| "prefixes"), &inputs)?;
+    assert_eq!(prefixes.len(), w.len());
+    if n == 1 {
+        let last = prefixes.last().unwrap().clone();
+        chip.expose_public(layouter.namespace(|| "product"), last, 0)
+    } else {
+        chip.expose_public_many(layouter.namespace(|| "prefixes"), &prefixes, 0)?;
+        Ok(())
+    }
+});
+
+fn prefix_products(values: &[u64]) -> Vec<Fp> {
+    values
+        .iter()
+        .scan(1u64, |acc, v| {
+            *acc *= v;
+            Some(f(*acc))
+        })
+        .collect()
+}
+
+#[test]
+fn running_product_prefixes() {
+    // 1, 2, 6, 24, 120, the factorials
+    let inputs = [1, 2, 3, 4, 5];
+    let circuit = RunningProductCircuit::new(&inputs, 0);
+    let prefixes = prefix_products(&inputs);
+    assert_eq!(mock(5, &circuit, prefixes.clone()), Ok(()));
+    let mut wrong = prefixes;
+    wrong[2] = f(5);
+    assert!(mock(5, &circuit, wrong).is_err());
+}
+
+#[test]
+fn running_product_with_a_zero() {
+    let inputs = [3, 4, 0, 5, 6];
+    let prefixes = prefix_products(&inputs);
+    assert_eq!(prefixes[2..], [f(0), f(0), f(0)]);
+    assert_eq!(
+        mock(5, &RunningProductCircuit::new(&inputs, 0), prefixes),
+        Ok(())
+    );
+}
+
+#[test]
+fn running_product_proves_the_final_product() {
+    let circuit = RunningProductCircuit::new(&[1, 2, 3, 4, 5, 6], 1);
+    assert!(prove_and_verify(5, &circuit, &[f(720)]).is_ok());
+    assert!(prove_and_verify(5, &circuit, &[f(120)]).is_err());
+}
+
+// every w absorbed in a step of its own, the total finalized onto row 0 and, for n = 1, a
+// second total of w[0] alone onto row 1
+gadget_circuit!(AccumulateCircuit, |chip, layouter, w, n| {
+    let mut acc = AccumulatorChip::new(chip.config().clone());
+    for (i, v) in w.iter().enumerate() {
+        let v = chip.load_private(layouter.namespace(|| format!("value {}", i)), *v)?;
+        acc.absorb(layouter.namespace(|| format!("absorb {}", i)), v)?;
+    }
+    acc.finalize(layouter.namespace(|| "total"), 0)?;
+    if n == 1 {
+        let v = chip.load_private(layouter.namespace(|| "again"), w[0])?;
+        acc.absorb(layouter.namespace(|| "absorb again"), v)?;
+        acc.finalize(layouter.namespace(|| "second total"), 1)?;
+    }
+    Ok(())
+});
+
+#[test]
+fn accumulator_totals_ten_values() {
+    let values: Vec<u64> = (1..=10).map(|i| i * i).collect();
+    let circuit = AccumulateCircuit::new(&values, 0);
+    assert_eq!(mock(6, &circuit, vec![f(385)]), Ok(()));
+    assert!(mock(6, &circuit, vec![f(384)]).is_err());
+    assert!(prove_and_verify(6, &circuit, &[f(385)]).is_ok());
+}
+
+#[test]
+fn accumulator_finalized_empty_is_zero() {
+    let circuit = AccumulateCircuit::new(&[], 0);
+    assert_eq!(mock(4, &circuit, vec![f(0)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(1)]).is_err());
+}
+
+#[test]
+fn accumulator_starts_over_after_finalize() {
+    let circuit = AccumulateCircuit::new(&[3, 4], 1);
+    assert_eq!(mock(5, &circuit, vec![f(7), f(3)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(7), f(10)]).is_err());
+}
+
+// w exposed with expose_public_many from row n, the next free row checked against the length
+gadget_circuit!(ExposeManyCircuit, |chip, layouter, w, n| {
+    let cells = chip.load_private_many(layouter.namespace(|| "cells"), w)?;
+    let next = chip.expose_public_many(layouter.namespace(|| "expose"), &cells, n)?;
+    assert_eq!(next, n + w.len());
+    Ok(())
+});
+
+#[test]
+fn expose_public_many_of_two_cells() {
+    let circuit = ExposeManyCircuit::new(&[9, 35], 0);
+    assert_eq!(mock(4, &circuit, vec![f(9), f(35)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(35), f(9)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(9), f(35)]).is_ok());
+    assert!(prove_and_verify(4, &circuit, &[f(35), f(9)]).is_err());
+    // starting further down leaves the rows above free
+    let circuit = ExposeManyCircuit::new(&[9, 35], 1);
+    assert_eq!(mock(4, &circuit, vec![f(0), f(9), f(35)]), Ok(()));
+}
+
+#[test]
+fn expose_public_many_refuses_rows_that_wrap() {
+    let circuit = ExposeManyCircuit::new(&[9, 35], usize::MAX);
+    assert!(matches!(
+        common::synthesis_error(4, &circuit, vec![]),
+        Some(halo2_proofs::plonk::Error::Synthesis)
+    ));
+}
@@ -0,0 +1,516 @@
+// bit decomposition and the gadgets built on it
+#[macro_use]
+mod common;
+
+use common::{f, mock, pow, synthesis_error};
+use halo2::chip::MAX_BITS;
+use halo2_proofs::{
+    circuit::Chip,
+    dev::VerifyFailure,
+    pasta::{group::ff::PrimeField, Fp},
+    plonk::Error,
+};
+
+// the n little endian bits of w[0], published on rows 0..n
+gadget_circuit!(DecomposeCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let bits = chip.decompose(layouter.namespace(|| "bits"), a, n)?;
+    chip.expose_public_many(layouter.namespace(|| "bits"), &bits, 0)?;
+    Ok(())
+});
+
+// the decompose region filled in by hand, w = [a, bit 0, bit 1, ..]
+gadget_circuit!(ForgedDecomposeCircuit, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    layouter.assign_region(
+        || "decompose",
+        |mut region| {
+            let mut acc =
+                region.assign_advice_from_constant(|| "zero", config.advice[0], 0, Fp::zero())?;
+            let mut k = Fp::one();
+            for (i, bit) in w[1..].iter().enumerate() {
+                config.s_bits.enable(&mut region, i)?;
+                region.assign_advice(
+                    || "bit",
+                    config.advice[1],
+                    i,
+                    || bit.ok_or(Error::Synthesis),
+                )?;
+                region.assign_fixed(|| "2^i", config.coeff, i, || Ok(k))?;
+                let v = acc.value().and_then(|acc| bit.map(|bit| *acc + k * bit));
+                acc = region.assign_advice(
+                    || "acc",
+                    config.advice[0],
+                    i + 1,
+                    || v.ok_or(Error::Synthesis),
+                )?;
+                k = k.double();
+            }
+            region.constrain_equal(acc.cell(), a.cell())
+        },
+    )
+});
+
+fn bits_of(v: u64, n: usize) -> Vec<Fp> {
+    (0..n).map(|i| f((v >> i) & 1)).collect()
+}
+
+// little endian bits of the field modulus p, which is 0 in the field
+fn modulus_bits() -> Vec<Fp> {
+    // p - 1 is even, so p is p - 1 with the lowest bit set
+    let mut repr = (-Fp::one()).to_repr();
+    repr[0] |= 1;
+    let mut bits: Vec<Fp> = repr
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| f(((byte >> i) & 1) as u64)))
+        .collect();
+    while bits.last() == Some(&Fp::zero()) {
+        bits.pop();
+    }
+    bits
+}
+
+#[test]
+fn decompose() {
+    assert_eq!(
+        mock(4, &DecomposeCircuit::new(&[0], 4), bits_of(0, 4)),
+        Ok(())
+    );
+    assert_eq!(
+        mock(4, &DecomposeCircuit::new(&[1], 4), bits_of(1, 4)),
+        Ok(())
+    );
+    assert_eq!(
+        mock(4, &DecomposeCircuit::new(&[0b1011], 4), bits_of(0b1011, 4)),
+        Ok(())
+    );
+    for n in [1, 8, 16, 63, 64] {
+        let max = u64::MAX >> (64 - n);
+        let circuit = DecomposeCircuit::new(&[max], n);
+        assert_eq!(mock(7, &circuit, bits_of(max, n)), Ok(()), "n = {}", n);
+    }
+    // the bits are published little endian
+    assert!(mock(4, &DecomposeCircuit::new(&[0b0001], 4), bits_of(0b1000, 4)).is_err());
+}
+
+#[test]
+fn decompose_rejects_values_that_do_not_fit() {
+    for (v, n) in [(2, 1), (16, 4), (256, 8)] {
+        assert!(
+            matches!(
+                synthesis_error(5, &DecomposeCircuit::new(&[v], n), vec![]),
+                Some(Error::Synthesis)
+            ),
+            "{} in {} bits",
+            v,
+            n
+        );
+    }
+    // and a prover skipping the check gets caught by the mock prover, 16 has no 4 bit form
+    let forged = ForgedDecomposeCircuit::with_fields(&[f(16), f(0), f(0), f(0), f(0)], 0);
+    let failures = mock(5, &forged, vec![]).unwrap_err();
+    assert!(failures
+        .iter()
+        .all(|e| matches!(e, VerifyFailure::Permutation { .. })));
+}
+
+#[test]
+fn decompose_max_bits_is_the_boundary() {
+    assert_eq!(MAX_BITS, 254);
+    // 2^254 - 1, all ones in the widest decomposition allowed
+    let all_ones = pow(f(2), MAX_BITS as u64) - Fp::one();
+    let circuit = DecomposeCircuit::with_fields(&[all_ones], MAX_BITS);
+    assert_eq!(mock(9, &circuit, vec![f(1); MAX_BITS]), Ok(()));
+    for n in [MAX_BITS + 1, 256, 1000] {
+        assert!(matches!(
+            synthesis_error(11, &DecomposeCircuit::new(&[1], n), vec![]),
+            Some(Error::Synthesis)
+        ));
+    }
+    // why it stops there: p itself needs 255 bits, and those bits satisfy every gate for a = 0
+    let p = modulus_bits();
+    assert_eq!(p.len(), MAX_BITS + 1);
+    let witness: Vec<Fp> = std::iter::once(Fp::zero()).chain(p).collect();
+    let forged = ForgedDecomposeCircuit::with_fields(&witness, 0);
+    assert_eq!(mock(9, &forged, vec![]), Ok(()));
+}
+
+// w[0] < w[1] as n bit values
+gadget_circuit!(LtCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "a, b"), w)?;
+    let lt = chip.lt(
+        layouter.namespace(|| "a < b"),
+        v[0].clone(),
+        v[1].clone(),
+        n,
+    )?;
+    chip.expose_public(layouter.namespace(|| "lt"), lt, 0)
+});
+
+#[test]
+fn lt() {
+    let max = 255;
+    for (a, b, lt) in [
+        (3, 5, 1),
+        (5, 5, 0),
+        (5, 3, 0),
+        (0, 0, 0),
+        (0, 1, 1),
+        (0, max, 1),
+        (max, 0, 0),
+        (max - 1, max, 1),
+        (max, max, 0),
+    ] {
+        let circuit = LtCircuit::new(&[a, b], 8);
+        assert_eq!(mock(5, &circuit, vec![f(lt)]), Ok(()), "{} < {}", a, b);
+        assert!(mock(5, &circuit, vec![f(1 - lt)]).is_err(), "{} < {}", a, b);
+    }
+    // 64 bit values, the widest the rest of the crate compares
+    let circuit = LtCircuit::new(&[u64::MAX - 1, u64::MAX], 64);
+    assert_eq!(mock(7, &circuit, vec![f(1)]), Ok(()));
+}
+
+#[test]
+fn lt_refuses_inputs_outside_n_bits() {
+    // 256 - 0 + 2^8 needs ten bits, one more than the decomposition has
+    assert!(matches!(
+        synthesis_error(5, &LtCircuit::new(&[256, 0], 8), vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+// n = 0 publishes min(w[0], w[1]) and n = 1 the max, as 8 bit values
+gadget_circuit!(MinMaxCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "a, b"), w)?;
+    let out = if n == 0 {
+        chip.min(layouter.namespace(|| "min"), v[0].clone(), v[1].clone(), 8)?
+    } else {
+        chip.max(layouter.namespace(|| "max"), v[0].clone(), v[1].clone(), 8)?
+    };
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+// the largest of three secret 8 bit values is below the public cap
+gadget_circuit!(BelowCapCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "values"), w)?;
+    let cap = chip.load_public(layouter.namespace(|| "cap"), 0)?;
+    let m = chip.max(
+        layouter.namespace(|| "max ab"),
+        v[0].clone(),
+        v[1].clone(),
+        8,
+    )?;
+    let m = chip.max(layouter.namespace(|| "max abc"), m, v[2].clone(), 8)?;
+    let below = chip.lt(layouter.namespace(|| "max < cap"), m, cap, 8)?;
+    chip.assert_equals_constant(layouter.namespace(|| "holds"), below, f(1))
+});
+
+#[test]
+fn min_max() {
+    for (a, b) in [(3, 9), (9, 3), (0, 255)] {
+        let (lo, hi) = (a.min(b), a.max(b));
+        assert_eq!(
+            mock(6, &MinMaxCircuit::new(&[a, b], 0), vec![f(lo)]),
+            Ok(())
+        );
+        assert!(mock(6, &MinMaxCircuit::new(&[a, b], 0), vec![f(hi)]).is_err());
+        assert_eq!(
+            mock(6, &MinMaxCircuit::new(&[a, b], 1), vec![f(hi)]),
+            Ok(())
+        );
+        assert!(mock(6, &MinMaxCircuit::new(&[a, b], 1), vec![f(lo)]).is_err());
+    }
+}
+
+#[test]
+fn min_max_of_equal_inputs() {
+    assert_eq!(mock(6, &MinMaxCircuit::new(&[7, 7], 0), vec![f(7)]), Ok(()));
+    assert_eq!(mock(6, &MinMaxCircuit::new(&[7, 7], 1), vec![f(7)]), Ok(()));
+    assert!(mock(6, &MinMaxCircuit::new(&[7, 7], 1), vec![f(8)]).is_err());
+}
+
+#[test]
+fn max_below_a_public_cap() {
+    let circuit = BelowCapCircuit::new(&[12, 40, 7], 0);
+    assert_eq!(mock(7, &circuit, vec![f(41)]), Ok(()));
+    assert!(common::prove_and_verify(7, &circuit, &[f(100)]).is_ok());
+    // the cap has to be strictly above the max
+    assert!(mock(7, &circuit, vec![f(40)]).is_err());
+    assert!(common::prove_and_verify(7, &circuit, &[f(39)]).is_err());
+}
+
+// |w[0] - w[1]| for n bit values
+gadget_circuit!(AbsDiffCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "a, b"), w)?;
+    let d = chip.abs_diff(
+        layouter.namespace(|| "|a - b|"),
+        v[0].clone(),
+        v[1].clone(),
+        n,
+    )?;
+    chip.expose_public(layouter.namespace(|| "d"), d, 0)
+});
+
+// the secret measurement is within 5 of the public reference, |m - ref| < 6
+gadget_circuit!(ToleranceCircuit, |chip, layouter, w, n| {
+    let m = chip.load_private(layouter.namespace(|| "m"), w[0])?;
+    let reference = chip.load_public(layouter.namespace(|| "ref"), 0)?;
+    let tolerance = chip.load_constant(layouter.namespace(|| "6"), f(6))?;
+    let d = chip.abs_diff(layouter.namespace(|| "|m - ref|"), m, reference, 16)?;
+    let ok = chip.lt(layouter.namespace(|| "d < 6"), d, tolerance, 16)?;
+    chip.assert_equals_constant(layouter.namespace(|| "ok"), ok, f(1))
+});
+
+#[test]
+fn abs_diff() {
+    for (a, b) in [(9u64, 3), (3, 9), (7, 7), (0, 255), (255, 0)] {
+        let d = a.abs_diff(b);
+        let circuit = AbsDiffCircuit::new(&[a, b], 8);
+        assert_eq!(mock(6, &circuit, vec![f(d)]), Ok(()), "|{} - {}|", a, b);
+        assert!(mock(6, &circuit, vec![f(d + 1)]).is_err());
+        if a < b {
+            // a - b in the field is p - d, not a small difference
+            assert!(mock(6, &circuit, vec![f(a) - f(b)]).is_err());
+        }
+    }
+}
+
+#[test]
+fn abs_diff_refuses_inputs_outside_n_bits() {
+    assert!(matches!(
+        synthesis_error(6, &AbsDiffCircuit::new(&[256, 0], 8), vec![f(256)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn abs_diff_tolerance() {
+    assert_eq!(
+        mock(7, &ToleranceCircuit::new(&[100], 0), vec![f(105)]),
+        Ok(())
+    );
+    assert_eq!(
+        mock(7, &ToleranceCircuit::new(&[100], 0), vec![f(95)]),
+        Ok(())
+    );
+    assert!(mock(7, &ToleranceCircuit::new(&[100], 0), vec![f(106)]).is_err());
+    assert!(mock(7, &ToleranceCircuit::new(&[100], 0), vec![f(94)]).is_err());
+}
+
+// decompose w[0] into n bits, pack them back and pin the result to the original cell
+gadget_circuit!(RoundTripCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let bits = chip.decompose(layouter.namespace(|| "bits"), a.clone(), n)?;
+    let packed = chip.pack_bits(layouter.namespace(|| "pack"), &bits)?;
+    chip.assert_equal(layouter.namespace(|| "packed == a"), packed, a)
+});
+
+// packs the private w as bits and publishes the value
+gadget_circuit!(PackCircuit, |chip, layouter, w, n| {
+    let bits = chip.load_private_many(layouter.namespace(|| "bits"), w)?;
+    let packed = chip.pack_bits(layouter.namespace(|| "pack"), &bits)?;
+    chip.expose_public(layouter.namespace(|| "packed"), packed, 0)
+});
+
+#[test]
+fn pack_bits_round_trip() {
+    for (v, n) in [(0, 1), (1, 1), (0b1011, 4), (0b1011, 8), (u64::MAX, 64)] {
+        let circuit = RoundTripCircuit::new(&[v], n);
+        assert_eq!(mock(8, &circuit, vec![]), Ok(()), "{} in {} bits", v, n);
+    }
+    assert!(common::prove_and_verify(8, &RoundTripCircuit::new(&[0xbeef], 16), &[]).is_ok());
+}
+
+#[test]
+fn pack_bits() {
+    // little endian, [1, 1, 0, 1] is 11
+    assert_eq!(
+        mock(4, &PackCircuit::new(&[1, 1, 0, 1], 0), vec![f(11)]),
+        Ok(())
+    );
+    assert!(mock(4, &PackCircuit::new(&[1, 1, 0, 1], 0), vec![f(13)]).is_err());
+    assert_eq!(mock(4, &PackCircuit::new(&[], 0), vec![f(0)]), Ok(()));
+}
+
+#[test]
+fn pack_bits_constrains_the_bits() {
+    // [3, 0] would pack to 3 like [1, 1] does, the bits are checked again on the way in
+    let circuit = PackCircuit::new(&[3, 0], 0);
+    assert!(common::fails_gate(mock(4, &circuit, vec![f(3)]), "pack"));
+}
+
+#[test]
+fn pack_bits_stops_at_max_bits() {
+    let ones = vec![1; MAX_BITS];
+    let max = pow(f(2), MAX_BITS as u64) - Fp::one();
+    assert_eq!(mock(10, &PackCircuit::new(&ones, 0), vec![max]), Ok(()));
+    let too_many = PackCircuit::new(&vec![0; MAX_BITS + 1], 0);
+    assert!(matches!(
+        synthesis_error(10, &too_many, vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+// w[0] shifted by n both ways as a 32 bit value, [shl, shr] published
+gadget_circuit!(ShiftCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let left = chip.shl(layouter.namespace(|| "a << k"), a.clone(), n, 32)?;
+    let right = chip.shr(layouter.namespace(|| "a >> k"), a, n, 32)?;
+    chip.expose_public_many(layouter.namespace(|| "shifted"), &[left, right], 0)?;
+    Ok(())
+});
+
+// two decompositions of 32 bits and their repacking
+const SHIFT_K: u32 = 8;
+
+fn shifted(a: u32, k: usize) -> Vec<Fp> {
+    let k = k as u32;
+    vec![
+        f(a.checked_shl(k).unwrap_or(0) as u64),
+        f(a.checked_shr(k).unwrap_or(0) as u64),
+    ]
+}
+
+proptest::proptest! {
+    #![proptest_config(proptest::prelude::ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn shifts_match_native_u32(a: u32, k in 0usize..40) {
+        let circuit = ShiftCircuit::new(&[a as u64], k);
+        proptest::prop_assert_eq!(mock(SHIFT_K, &circuit, shifted(a, k)), Ok(()));
+    }
+}
+
+#[test]
+fn shift_by_zero_and_past_the_width() {
+    let a = 0xdead_beef;
+    for k in [0, 1, 31, 32, 33, 100] {
+        let circuit = ShiftCircuit::new(&[a as u64], k);
+        assert_eq!(mock(SHIFT_K, &circuit, shifted(a, k)), Ok(()), "k = {}", k);
+    }
+    // shifting by 0 is the identity both ways
+    assert_eq!(shifted(a, 0), vec![f(a as u64), f(a as u64)]);
+    let circuit = ShiftCircuit::new(&[a as u64], 32);
+    assert!(mock(SHIFT_K, &circuit, vec![f(0), f(1)]).is_err());
+}
+
+#[test]
+fn shifts_refuse_values_wider_than_n_bits() {
+    let circuit = ShiftCircuit::new(&[1 << 32], 1);
+    assert!(matches!(
+        synthesis_error(SHIFT_K, &circuit, vec![f(0), f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+// w[0] rotated by n both ways as a 32 bit value, [rotl, rotr] published
+gadget_circuit!(RotateCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let left = chip.rotl(layouter.namespace(|| "rotl"), a.clone(), n, 32)?;
+    let right = chip.rotr(layouter.namespace(|| "rotr"), a, n, 32)?;
+    chip.expose_public_many(layouter.namespace(|| "rotated"), &[left, right], 0)?;
+    Ok(())
+});
+
+fn rotated(a: u32, k: usize) -> Vec<Fp> {
+    let k = k as u32;
+    vec![f(a.rotate_left(k) as u64), f(a.rotate_right(k) as u64)]
+}
+
+#[test]
+fn rotations_match_native_u32() {
+    for (a, k) in [
+        (0x8000_0001, 1),
+        (0xdead_beef, 4),
+        (0x0123_4567, 13),
+        (0xffff_0000, 16),
+        (1, 31),
+    ] {
+        let circuit = RotateCircuit::new(&[a as u64], k);
+        assert_eq!(
+            mock(SHIFT_K, &circuit, rotated(a, k)),
+            Ok(()),
+            "{:#x} by {}",
+            a,
+            k
+        );
+        // the two directions only agree on a half turn
+        if k != 16 {
+            let mut swapped = rotated(a, k);
+            swapped.swap(0, 1);
+            assert!(mock(SHIFT_K, &circuit, swapped).is_err());
+        }
+    }
+}
+
+#[test]
+fn rotation_by_zero_or_the_width_is_the_identity() {
+    let a = 0xdead_beef_u32;
+    for k in [0, 32, 64] {
+        let circuit = RotateCircuit::new(&[a as u64], k);
+        let identity = vec![f(a as u64), f(a as u64)];
+        assert_eq!(mock(SHIFT_K, &circuit, identity), Ok(()), "k = {}", k);
+    }
+}
+
+#[test]
+fn rotation_proves_and_verifies() {
+    let (a, k) = (0xdead_beef_u32, 8);
+    let circuit = RotateCircuit::new(&[a as u64], k);
+    assert!(common::prove_and_verify(SHIFT_K, &circuit, &rotated(a, k)).is_ok());
+    let wrong = rotated(a, k + 1);
+    assert!(common::prove_and_verify(SHIFT_K, &circuit, &wrong).is_err());
+}
+
+// w[0] asserted to fit in n bits
+gadget_circuit!(FitsCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    chip.assert_fits(layouter.namespace(|| "fits"), a, n)
+});
+
+// the same assertion through decompose, pack_bits and assert_equal
+gadget_circuit!(FitsByRepackingCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let bits = chip.decompose(layouter.namespace(|| "bits"), a.clone(), n)?;
+    let packed = chip.pack_bits(layouter.namespace(|| "pack"), &bits)?;
+    chip.assert_equal(layouter.namespace(|| "packed == a"), packed, a)
+});
+
+#[test]
+fn assert_fits_at_the_boundary() {
+    for n in [1, 8, 16, 63] {
+        let max = (1u64 << n) - 1;
+        assert_eq!(
+            mock(7, &FitsCircuit::new(&[max], n), vec![]),
+            Ok(()),
+            "{} bits",
+            n
+        );
+        assert_eq!(
+            mock(7, &FitsCircuit::new(&[0], n), vec![]),
+            Ok(()),
+            "{} bits",
+            n
+        );
+        assert!(
+            matches!(
+                synthesis_error(7, &FitsCircuit::new(&[max + 1], n), vec![]),
+                Some(Error::Synthesis)
+            ),
+            "{} bits",
+            n
+        );
+    }
+    // zero fits even in no bits at all
+    assert_eq!(mock(4, &FitsCircuit::new(&[0], 0), vec![]), Ok(()));
+}
+
+#[test]
+fn assert_fits_costs_less_than_repacking() {
+    let fits = FitsCircuit::new(&[200], 8);
+    let repacked = FitsByRepackingCircuit::new(&[200], 8);
+    assert_eq!(mock(5, &repacked, vec![]), Ok(()));
+    assert!(common::rows(&fits, &[]) < common::rows(&repacked, &[]));
+    assert!(common::regions(&fits, &[]) < common::regions(&repacked, &[]));
+}
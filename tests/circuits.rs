@@ -0,0 +1,1630 @@
+// the example circuits, each proved end to end and checked against its own k()
+mod common;
+
+use common::{assert_k, f, fails_gate, measured_k, mock, pow, prove_and_verify, synthesis_error};
+use halo2::{
+    chip::{mimc_hash, ByteTableChip},
+    circuit::{
+        challenge, AgeCircuit, AverageCircuit, CircuitError, CollatzCircuit, DotProductCircuit,
+        FactoringCircuit, FibonacciCircuit, IntervalCircuit, LinearLayerCircuit, MatVecCircuit,
+        MerklePathCircuit, ModExpCircuit, PermutationCircuit, PolyCircuit, PrivatePolyCircuit,
+        PythagoreanCircuit, QuadraticRootCircuit, RationalCircuit, SortedCircuit, SudokuCircuit,
+        SumCircuit, SumOfSquaresCircuit, ThreeFactorCircuit, VarianceCircuit, VoteTallyCircuit,
+        INTERVAL_BITS, SORTED_BITS, VARIANCE_BITS,
+    },
+    proof::keygen,
+};
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::Chip,
+    pasta::Fp,
+    plonk::{Circuit, Error},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn poly(coeffs: &[u64], x: Fp) -> (PolyCircuit, Fp) {
+    let coeffs: Vec<Fp> = coeffs.iter().map(|&c| f(c)).collect();
+    let y = coeffs
+        .iter()
+        .enumerate()
+        .fold(Fp::zero(), |acc, (i, c)| acc + *c * pow(x, i as u64));
+    let circuit = PolyCircuit { coeffs, x: Some(x) };
+    (circuit, y)
+}
+
+#[test]
+fn poly_of_degree_1_3_and_10() {
+    let mut rng = StdRng::seed_from_u64(61);
+    for coeffs in [
+        vec![7, 2],
+        vec![5, 1, 0, 1],
+        vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    ] {
+        let (circuit, y) = poly(&coeffs, Fp::random(&mut rng));
+        let k = circuit.k();
+        assert!(prove_and_verify(k, &circuit, &[y]).is_ok(), "{:?}", coeffs);
+        assert!(
+            prove_and_verify(k, &circuit, &[y + f(1)]).is_err(),
+            "{:?}",
+            coeffs
+        );
+        // a different x gives a different value, so the same public input can't verify
+        let (wrong, _) = poly(&coeffs, Fp::random(&mut rng));
+        assert!(prove_and_verify(k, &wrong, &[y]).is_err(), "{:?}", coeffs);
+    }
+}
+
+#[test]
+fn poly_agrees_with_the_cubic() {
+    // x^3 + x + 5 at x = 3 is MyCircuit's 35
+    let (circuit, y) = poly(&[5, 1, 0, 1], f(3));
+    assert_eq!(y, f(35));
+    assert_eq!(mock(circuit.k(), &circuit, vec![f(35)]), Ok(()));
+    // no coefficients at all is the zero polynomial
+    let (circuit, y) = poly(&[], f(3));
+    assert_eq!(mock(circuit.k(), &circuit, vec![y]), Ok(()));
+}
+
+#[test]
+fn poly_k() {
+    for coeffs in [
+        vec![7, 2],
+        vec![5, 1, 0, 1],
+        (1..=11).collect(),
+        (1..=40).collect(),
+    ] {
+        let (circuit, y) = poly(&coeffs, f(2));
+        assert_k(circuit.k(), &circuit, vec![y]);
+    }
+}
+
+fn fibonacci(n: usize) -> (FibonacciCircuit, Vec<Fp>) {
+    let mut seq = vec![f(1), f(1)];
+    while seq.len() <= n + 1 {
+        let next = seq[seq.len() - 1] + seq[seq.len() - 2];
+        seq.push(next);
+    }
+    let circuit = FibonacciCircuit {
+        a: Some(f(1)),
+        b: Some(f(1)),
+        n,
+    };
+    (circuit, seq)
+}
+
+#[test]
+fn fibonacci_at_1_10_and_50() {
+    for n in [1, 10, 50] {
+        let (circuit, seq) = fibonacci(n);
+        let k = circuit.k();
+        assert!(
+            prove_and_verify(k, &circuit, &[seq[n]]).is_ok(),
+            "n = {}",
+            n
+        );
+        // off by one in either direction, f(0) and f(1) are both 1 so n = 1 has no lower one
+        assert!(mock(k, &circuit, vec![seq[n + 1]]).is_err(), "n = {}", n);
+        if n > 1 {
+            assert!(mock(k, &circuit, vec![seq[n - 1]]).is_err(), "n = {}", n);
+        }
+    }
+    // f(50) starting from 1, 1 is 20365011074
+    assert_eq!(fibonacci(50).1[50], f(20365011074));
+    assert!(prove_and_verify(fibonacci(50).0.k(), &fibonacci(50).0, &[f(20365011073)]).is_err());
+}
+
+#[test]
+fn fibonacci_from_other_seeds() {
+    // 2, 5, 7, 12, 19
+    let circuit = FibonacciCircuit {
+        a: Some(f(2)),
+        b: Some(f(5)),
+        n: 4,
+    };
+    assert_eq!(mock(circuit.k(), &circuit, vec![f(19)]), Ok(()));
+    let circuit = FibonacciCircuit { n: 0, ..circuit };
+    assert_eq!(mock(circuit.k(), &circuit, vec![f(2)]), Ok(()));
+}
+
+#[test]
+fn fibonacci_k() {
+    for n in [0, 1, 10, 50, 200] {
+        let (circuit, seq) = fibonacci(n);
+        assert_k(circuit.k(), &circuit, vec![seq[n]]);
+    }
+}
+
+fn pythagorean(a: u64, b: u64) -> PythagoreanCircuit {
+    PythagoreanCircuit {
+        a: Some(f(a)),
+        b: Some(f(b)),
+    }
+}
+
+#[test]
+fn pythagorean_triples() {
+    let k = PythagoreanCircuit::k();
+    assert!(prove_and_verify(k, &pythagorean(3, 4), &[f(5)]).is_ok());
+    assert!(prove_and_verify(k, &pythagorean(5, 12), &[f(13)]).is_ok());
+    assert!(prove_and_verify(k, &pythagorean(5, 12), &[f(12)]).is_err());
+}
+
+#[test]
+fn pythagorean_rejects_a_non_triple() {
+    // 2^2 + 3^2 = 13 has no integer root, whatever c is claimed
+    let k = PythagoreanCircuit::k();
+    assert!(mock(k, &pythagorean(2, 3), vec![f(4)]).is_err());
+    assert!(mock(k, &pythagorean(2, 3), vec![f(3)]).is_err());
+    assert!(prove_and_verify(k, &pythagorean(2, 3), &[f(4)]).is_err());
+}
+
+#[test]
+fn pythagorean_keygen_without_witnesses() {
+    let circuit = pythagorean(3, 4).without_witnesses();
+    assert!(circuit.a.is_none() && circuit.b.is_none());
+    let (params, pk) = keygen(PythagoreanCircuit::k(), &circuit).unwrap();
+    // the same keys prove every triple
+    for (a, b, c) in [(3, 4, 5), (8, 15, 17)] {
+        let proof = halo2::proof::create_proof_bytes(&params, &pk, &pythagorean(a, b), &[f(c)]);
+        let proof = proof.unwrap();
+        assert!(halo2::proof::verify_proof_bytes(&params, pk.get_vk(), &proof, &[f(c)]).is_ok());
+    }
+}
+
+#[test]
+fn pythagorean_k() {
+    assert_k(PythagoreanCircuit::k(), &pythagorean(3, 4), vec![f(5)]);
+}
+
+fn factoring(p: Fp, q: Fp) -> FactoringCircuit {
+    FactoringCircuit {
+        p: Some(p),
+        q: Some(q),
+    }
+}
+
+#[test]
+fn factoring_15() {
+    let k = FactoringCircuit::k();
+    assert!(prove_and_verify(k, &factoring(f(3), f(5)), &[f(15)]).is_ok());
+    assert!(prove_and_verify(k, &factoring(f(5), f(3)), &[f(15)]).is_ok());
+    // a wrong n fails verification
+    assert!(prove_and_verify(k, &factoring(f(3), f(5)), &[f(21)]).is_err());
+}
+
+#[test]
+fn factoring_rejects_trivial_factors() {
+    let k = FactoringCircuit::k();
+    assert!(mock(k, &factoring(f(1), f(15)), vec![f(15)]).is_err());
+    assert!(mock(k, &factoring(f(15), f(1)), vec![f(15)]).is_err());
+    // zero has no inverse to witness, so it is refused before any constraint
+    assert!(matches!(
+        synthesis_error(k, &factoring(f(0), f(15)), vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn factoring_rejects_products_that_wrap() {
+    // 2 * (15 / 2) is 15 in the field, with 15 / 2 about as big as p
+    let k = FactoringCircuit::k();
+    let half = f(15) * f(2).invert().unwrap();
+    assert!(mock(k, &factoring(f(2), half), vec![f(15)]).is_err());
+    // the largest factors that fit still multiply out exactly
+    let max = u64::MAX;
+    let n = f(max) * f(max);
+    assert_eq!(mock(k, &factoring(f(max), f(max)), vec![n]), Ok(()));
+}
+
+#[test]
+fn factoring_k() {
+    assert_k(FactoringCircuit::k(), &factoring(f(3), f(5)), vec![f(15)]);
+}
+
+// a tree over the leaves hashed natively, levels[0] being the leaves and the last level the root
+fn merkle_tree(leaves: &[Fp]) -> Vec<Vec<Fp>> {
+    let mut levels = vec![leaves.to_vec()];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| mimc_hash(pair[0], pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+// the path of leaf index in the tree, the direction is 1 where the node is a right child
+fn merkle_path(levels: &[Vec<Fp>], index: usize) -> MerklePathCircuit {
+    let path = levels[..levels.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(depth, level)| {
+            let i = index >> depth;
+            (Some(level[i ^ 1]), Some(f((i & 1) as u64)))
+        })
+        .collect();
+    MerklePathCircuit {
+        leaf: Some(levels[0][index]),
+        path,
+    }
+}
+
+fn depth_4_tree() -> Vec<Vec<Fp>> {
+    let mut rng = StdRng::seed_from_u64(66);
+    let leaves: Vec<Fp> = (0..16).map(|_| Fp::random(&mut rng)).collect();
+    merkle_tree(&leaves)
+}
+
+#[test]
+fn merkle_membership_at_depth_4() {
+    let levels = depth_4_tree();
+    let root = levels[4][0];
+    for index in [0, 5, 15] {
+        let circuit = merkle_path(&levels, index);
+        assert_eq!(
+            mock(circuit.k(), &circuit, vec![root]),
+            Ok(()),
+            "leaf {}",
+            index
+        );
+    }
+    let circuit = merkle_path(&levels, 5);
+    assert!(prove_and_verify(circuit.k(), &circuit, &[root]).is_ok());
+    assert!(prove_and_verify(circuit.k(), &circuit, &[root + f(1)]).is_err());
+}
+
+#[test]
+fn merkle_rejects_a_bad_path() {
+    let levels = depth_4_tree();
+    let root = levels[4][0];
+    let honest = merkle_path(&levels, 5);
+    let k = honest.k();
+    let mut wrong_sibling = honest.clone();
+    wrong_sibling.path[2].0 = Some(levels[1][0]);
+    assert!(mock(k, &wrong_sibling, vec![root]).is_err());
+    let mut flipped = honest.clone();
+    flipped.path[1].1 = Some(f(1) - flipped.path[1].1.unwrap());
+    assert!(mock(k, &flipped, vec![root]).is_err());
+    // a leaf that is not in the tree
+    let mut outsider = honest;
+    outsider.leaf = Some(f(66));
+    assert!(mock(k, &outsider, vec![root]).is_err());
+}
+
+#[test]
+fn merkle_empty_path_is_the_leaf() {
+    let circuit = MerklePathCircuit {
+        leaf: Some(f(66)),
+        path: vec![],
+    };
+    assert_eq!(mock(circuit.k(), &circuit, vec![f(66)]), Ok(()));
+    assert!(mock(circuit.k(), &circuit, vec![f(67)]).is_err());
+}
+
+#[test]
+fn merkle_k() {
+    let levels = depth_4_tree();
+    assert_k(
+        merkle_path(&levels, 5).k(),
+        &merkle_path(&levels, 5),
+        vec![levels[4][0]],
+    );
+    let circuit = MerklePathCircuit {
+        leaf: Some(f(66)),
+        path: vec![],
+    };
+    assert_k(circuit.k(), &circuit, vec![f(66)]);
+}
+
+fn age(birth_year: u64, current_year: u64, threshold: u64) -> (AgeCircuit, Vec<Fp>) {
+    let circuit = AgeCircuit {
+        birth_year: Some(f(birth_year)),
+        current_year: Some(f(current_year)),
+        threshold: Some(f(threshold)),
+    };
+    (circuit, vec![f(current_year), f(threshold)])
+}
+
+#[test]
+fn age_at_the_threshold() {
+    let k = AgeCircuit::k();
+    let (circuit, publics) = age(2008, 2026, 18);
+    assert!(prove_and_verify(k, &circuit, &publics).is_ok());
+    let (circuit, publics) = age(1950, 2026, 18);
+    assert_eq!(mock(k, &circuit, publics), Ok(()));
+    // the same proof doesn't pass for another threshold
+    let (circuit, _) = age(2008, 2026, 18);
+    assert!(prove_and_verify(k, &circuit, &[f(2026), f(17)]).is_err());
+}
+
+#[test]
+fn age_one_year_short() {
+    let (circuit, publics) = age(2009, 2026, 18);
+    assert!(mock(AgeCircuit::k(), &circuit, publics).is_err());
+}
+
+#[test]
+fn age_rejects_a_birth_year_in_the_future() {
+    // 2026 - 3000 wraps around to p - 974, which would be far above any threshold
+    let (circuit, publics) = age(3000, 2026, 18);
+    assert!(mock(AgeCircuit::k(), &circuit, publics).is_err());
+    // born this year is age 0, which still meets a threshold of 0
+    let (circuit, publics) = age(2026, 2026, 0);
+    assert_eq!(mock(AgeCircuit::k(), &circuit, publics), Ok(()));
+}
+
+#[test]
+fn age_k() {
+    let (circuit, publics) = age(2008, 2026, 18);
+    assert_k(AgeCircuit::k(), &circuit, publics);
+}
+
+fn sum(values: &[u64]) -> (SumCircuit, Fp) {
+    let circuit = SumCircuit {
+        values: values.iter().map(|&v| Some(f(v))).collect(),
+        n: values.len(),
+    };
+    (circuit, f(values.iter().sum()))
+}
+
+#[test]
+fn sum_of_1_16_and_100_values() {
+    let mut ks = vec![];
+    for n in [1, 16, 100] {
+        let values: Vec<u64> = (1..=n).collect();
+        let (circuit, total) = sum(&values);
+        let k = circuit.k();
+        assert!(prove_and_verify(k, &circuit, &[total]).is_ok(), "n = {}", n);
+        assert!(
+            prove_and_verify(k, &circuit, &[total + f(1)]).is_err(),
+            "n = {}",
+            n
+        );
+        ks.push(k);
+    }
+    // 100 values no longer fit the k of 16
+    assert!(ks[2] > ks[1]);
+}
+
+#[test]
+fn sum_refuses_a_witness_of_the_wrong_length() {
+    let (mut circuit, total) = sum(&[1, 2, 3]);
+    circuit.n = 4;
+    assert!(matches!(
+        synthesis_error(circuit.k(), &circuit, vec![total]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn sum_k() {
+    for n in [0, 1, 16, 100] {
+        let values: Vec<u64> = (1..=n).collect();
+        let (circuit, total) = sum(&values);
+        assert_k(circuit.k(), &circuit, vec![total]);
+    }
+}
+
+fn average(values: &[u64], exact: bool) -> AverageCircuit {
+    AverageCircuit {
+        values: values.iter().map(|&v| Some(f(v))).collect(),
+        n: values.len(),
+        exact,
+    }
+}
+
+#[test]
+fn exact_average() {
+    // 2 + 4 + 9 = 15 = 5 * 3
+    let circuit = average(&[2, 4, 9], true);
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(5)]).is_ok());
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(6)]).is_err());
+}
+
+#[test]
+fn exact_average_of_a_sum_that_does_not_divide_is_unsatisfiable() {
+    // 2 + 4 + 10 = 16, neither 5 nor 6 times 3
+    let circuit = average(&[2, 4, 10], true);
+    for avg in [5, 6] {
+        assert!(mock(circuit.k(), &circuit, vec![f(avg)]).is_err());
+    }
+    // and not the field element 16 / 3 either, the average is only ever rounded down
+    let third = f(16) * f(3).invert().unwrap();
+    assert!(mock(circuit.k(), &circuit, vec![third]).is_err());
+}
+
+// AverageCircuit's exact layout with the last private value loaded as avg, so a prover can
+// put the field quotient there
+gadget_circuit!(ForgedAverage, |chip, layouter, w, n| {
+    ByteTableChip::new(chip.config().byte_table.clone())
+        .load(layouter.namespace(|| "byte table"))?;
+    let values = chip.load_private_many(layouter.namespace(|| "load values"), &w[..n])?;
+    for v in &values {
+        chip.range_check(layouter.namespace(|| "value"), v.clone(), 64)?;
+    }
+    let sum = chip.sum(layouter.namespace(|| "sum"), &values)?;
+    let avg = chip.load_private(layouter.namespace(|| "load avg"), w[n])?;
+    chip.range_check(layouter.namespace(|| "avg"), avg.clone(), 64)?;
+    let avg_n = chip.mul_by_constant(
+        layouter.namespace(|| "avg * n"),
+        avg.clone(),
+        Fp::from(n as u64),
+    )?;
+    chip.assert_equal(layouter.namespace(|| "sum == avg * n"), sum, avg_n)?;
+    chip.expose_public(layouter.namespace(|| "expose avg"), avg, 0)
+});
+
+#[test]
+fn exact_average_of_a_forged_field_quotient() {
+    // 1 + 2 = 3 and 3 / 2 in the field times 2 is 3 again, so only the range check on avg
+    // stops it
+    let quotient = f(3) * f(2).invert().unwrap();
+    assert_eq!(quotient * f(2), f(3));
+    let forged = ForgedAverage::with_fields(&[f(1), f(2), quotient], 2);
+    let k = average(&[1, 2], true).k();
+    assert!(mock(k, &forged, vec![quotient]).is_err());
+    // the layout itself is fine, an honest avg passes it
+    let honest = ForgedAverage::new(&[2, 4, 3], 2);
+    assert_eq!(mock(k, &honest, vec![f(3)]), Ok(()));
+}
+
+#[test]
+fn average_values_have_to_be_u64() {
+    // -1 + 3 = 2 in the field
+    for exact in [true, false] {
+        let circuit = AverageCircuit {
+            values: vec![Some(-Fp::one()), Some(f(3))],
+            n: 2,
+            exact,
+        };
+        let publics = if exact { vec![f(1)] } else { vec![f(1), f(0)] };
+        assert!(mock(circuit.k(), &circuit, publics).is_err());
+    }
+}
+
+#[test]
+fn average_with_remainder() {
+    // 16 = 5 * 3 + 1
+    let circuit = average(&[2, 4, 10], false);
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(5), f(1)]).is_ok());
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(5), f(0)]).is_err());
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(4), f(4)]).is_err());
+    // with nothing left over the remainder is 0
+    let circuit = average(&[2, 4, 9], false);
+    assert_eq!(mock(circuit.k(), &circuit, vec![f(5), f(0)]), Ok(()));
+}
+
+#[test]
+fn average_of_nothing_is_refused() {
+    for exact in [true, false] {
+        let circuit = average(&[], exact);
+        assert!(matches!(
+            synthesis_error(circuit.k(), &circuit, vec![f(0)]),
+            Some(Error::Synthesis)
+        ));
+    }
+}
+
+#[test]
+fn average_k() {
+    // the remainder mode outgrows its byte table past n = 70
+    for n in [1, 20, 70, 100, 200] {
+        let values: Vec<u64> = (1..=n).collect();
+        let sum = n * (n + 1) / 2;
+        let (avg, rem) = (f(sum / n), f(sum % n));
+        let exact = average(&values, true);
+        // 1 + .. + n only divides by n for odd n, the rest can only be laid out
+        if n % 2 == 1 {
+            assert_k(exact.k(), &exact, vec![avg]);
+        } else {
+            assert_eq!(exact.k(), measured_k(&exact, &[avg]));
+        }
+        let remainder = average(&values, false);
+        assert_k(remainder.k(), &remainder, vec![avg, rem]);
+    }
+}
+
+fn sum_of_squares(values: &[u64]) -> (SumOfSquaresCircuit, Fp) {
+    let circuit = SumOfSquaresCircuit {
+        values: values.iter().map(|&v| Some(f(v))).collect(),
+        n: values.len(),
+    };
+    (circuit, f(values.iter().map(|v| v * v).sum()))
+}
+
+// SumOfSquaresCircuit with the square of w[n] laid out by hand as one more than it is
+gadget_circuit!(ForgedSquare, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    let values = chip.load_private_many(layouter.namespace(|| "load values"), w)?;
+    let squares = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| {
+            if i != n {
+                return chip.square(layouter.namespace(|| "square"), v);
+            }
+            layouter.assign_region(
+                || "forged square",
+                |mut region| {
+                    config.s_square.enable(&mut region, 0)?;
+                    v.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                    let forged = v.value().map(|v| v.square() + Fp::one());
+                    region.assign_advice(
+                        || "a * a",
+                        config.advice[0],
+                        1,
+                        || forged.ok_or(Error::Synthesis),
+                    )
+                },
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let sums = chip.running_sum(layouter.namespace(|| "running sum"), &squares)?;
+    let total = sums[sums.len() - 1].clone();
+    chip.expose_public(layouter.namespace(|| "expose total"), total, 0)
+});
+
+#[test]
+fn sum_of_squares_of_1_8_and_64_values() {
+    for n in [1, 8, 64] {
+        let values: Vec<u64> = (1..=n).collect();
+        let (circuit, total) = sum_of_squares(&values);
+        let k = circuit.k();
+        assert!(prove_and_verify(k, &circuit, &[total]).is_ok(), "n = {}", n);
+        assert!(
+            prove_and_verify(k, &circuit, &[total - f(1)]).is_err(),
+            "n = {}",
+            n
+        );
+    }
+}
+
+#[test]
+fn sum_of_squares_with_a_corrupted_square() {
+    let values: Vec<u64> = (1..=8).collect();
+    let (circuit, total) = sum_of_squares(&values);
+    // the forged layout matches the real one cell for cell
+    assert_eq!(
+        mock(circuit.k(), &ForgedSquare::new(&values, 8), vec![total]),
+        Ok(())
+    );
+    // with 4 * 4 taken as 17 the total the prover claims is one more
+    let forged = ForgedSquare::new(&values, 3);
+    assert!(fails_gate(
+        mock(circuit.k(), &forged, vec![total + f(1)]),
+        "square"
+    ));
+}
+
+#[test]
+fn sum_of_squares_k() {
+    for n in [1, 8, 64, 100] {
+        let values: Vec<u64> = (1..=n).collect();
+        let (circuit, total) = sum_of_squares(&values);
+        assert_k(circuit.k(), &circuit, vec![total]);
+    }
+}
+
+// a random rows x cols matrix and x, with y = a * x worked out natively
+fn mat_vec(rows: usize, cols: usize, rng: &mut StdRng) -> (MatVecCircuit, Vec<Fp>) {
+    let a: Vec<Vec<Fp>> = (0..rows)
+        .map(|_| (0..cols).map(|_| Fp::random(&mut *rng)).collect())
+        .collect();
+    let x: Vec<Fp> = (0..cols).map(|_| Fp::random(&mut *rng)).collect();
+    let y = a
+        .iter()
+        .map(|row| row.iter().zip(&x).fold(Fp::zero(), |y, (a, x)| y + a * x))
+        .collect();
+    let circuit = MatVecCircuit {
+        a,
+        x: x.into_iter().map(Some).collect(),
+        rows,
+        cols,
+    };
+    (circuit, y)
+}
+
+#[test]
+fn mat_vec_2x2_and_4x8() {
+    let mut rng = StdRng::seed_from_u64(72);
+    for (rows, cols) in [(2, 2), (4, 8)] {
+        let (circuit, y) = mat_vec(rows, cols, &mut rng);
+        assert!(prove_and_verify(circuit.k(), &circuit, &y).is_ok());
+    }
+}
+
+#[test]
+fn mat_vec_with_a_wrong_output_entry() {
+    let mut rng = StdRng::seed_from_u64(72);
+    let (circuit, y) = mat_vec(4, 8, &mut rng);
+    for i in 0..4 {
+        let mut wrong = y.clone();
+        wrong[i] += Fp::one();
+        assert!(
+            prove_and_verify(circuit.k(), &circuit, &wrong).is_err(),
+            "y_{}",
+            i
+        );
+    }
+}
+
+#[test]
+fn mat_vec_refuses_a_matrix_of_the_wrong_shape() {
+    let mut rng = StdRng::seed_from_u64(72);
+    let (mut circuit, y) = mat_vec(2, 2, &mut rng);
+    circuit.a[1].pop();
+    assert!(matches!(
+        synthesis_error(circuit.k(), &circuit, y),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn mat_vec_k() {
+    let mut rng = StdRng::seed_from_u64(72);
+    for (rows, cols) in [(1, 1), (2, 2), (4, 8), (8, 16)] {
+        let (circuit, y) = mat_vec(rows, cols, &mut rng);
+        assert_k(circuit.k(), &circuit, y);
+    }
+}
+
+fn dot_product(a: &[u64], b: &[u64]) -> (DotProductCircuit, Fp) {
+    let wrap = |v: &[u64]| v.iter().map(|&v| Some(f(v))).collect();
+    let circuit = DotProductCircuit::new(wrap(a), wrap(b)).unwrap();
+    (circuit, f(a.iter().zip(b).map(|(a, b)| a * b).sum()))
+}
+
+#[test]
+fn dot_product_of_lengths_1_and_16() {
+    // 7 * 6
+    let (circuit, dot) = dot_product(&[7], &[6]);
+    assert_eq!(dot, f(42));
+    assert!(prove_and_verify(circuit.k(), &circuit, &[dot]).is_ok());
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(13)]).is_err());
+    let a: Vec<u64> = (1..=16).collect();
+    let b: Vec<u64> = (1..=16).rev().collect();
+    let (circuit, dot) = dot_product(&a, &b);
+    assert!(prove_and_verify(circuit.k(), &circuit, &[dot]).is_ok());
+    assert!(prove_and_verify(circuit.k(), &circuit, &[dot + f(1)]).is_err());
+}
+
+#[test]
+fn dot_product_of_mismatched_lengths_is_refused_up_front() {
+    let err = match DotProductCircuit::new(vec![Some(f(1)); 3], vec![Some(f(1)); 2]) {
+        Ok(_) => panic!("lengths 3 and 2 accepted"),
+        Err(err) => err,
+    };
+    assert_eq!(err, CircuitError::LengthMismatch { left: 3, right: 2 });
+    assert_eq!(err.to_string(), "vector lengths differ: 3 and 2");
+}
+
+#[test]
+fn dot_product_k() {
+    for n in [1, 16, 50] {
+        let a: Vec<u64> = (1..=n).collect();
+        let (circuit, dot) = dot_product(&a, &a);
+        assert_k(circuit.k(), &circuit, vec![dot]);
+    }
+}
+
+// b against the public a, returns the circuit and its public inputs
+fn permutation(a: &[u64], b: &[u64]) -> (PermutationCircuit, Vec<Fp>) {
+    let wrap = |v: &[u64]| v.iter().map(|&v| Some(f(v))).collect();
+    let circuit = PermutationCircuit {
+        a: wrap(a),
+        b: wrap(b),
+        n: a.len(),
+    };
+    (circuit, a.iter().map(|&v| f(v)).collect())
+}
+
+#[test]
+fn permutation_of_itself_and_a_shuffle() {
+    let a = [3, 1, 4, 5, 9, 2];
+    for b in [[3, 1, 4, 5, 9, 2], [9, 2, 3, 5, 1, 4]] {
+        let (circuit, publics) = permutation(&a, &b);
+        assert!(prove_and_verify(circuit.k(), &circuit, &publics).is_ok());
+    }
+}
+
+#[test]
+fn permutation_with_one_element_changed() {
+    let (circuit, publics) = permutation(&[3, 1, 4, 5, 9, 2], &[9, 2, 3, 5, 1, 7]);
+    assert!(mock(circuit.k(), &circuit, publics.clone()).is_err());
+    assert!(prove_and_verify(circuit.k(), &circuit, &publics).is_err());
+}
+
+#[test]
+fn permutation_with_duplicates() {
+    let (circuit, publics) = permutation(&[1, 1, 2], &[1, 2, 1]);
+    assert_eq!(mock(circuit.k(), &circuit, publics), Ok(()));
+    // the same elements as a set, but not as often
+    for b in [[1, 2, 2], [2, 2, 1], [1, 1, 1]] {
+        let (circuit, publics) = permutation(&[1, 1, 2], &b);
+        assert!(mock(circuit.k(), &circuit, publics).is_err(), "{:?}", b);
+    }
+}
+
+#[test]
+fn permutation_with_b_solved_for_a_known_gamma() {
+    // with a gamma fixed up front a prover can pick all of b but the last element and solve
+    // for that one, here against the gamma of the honest shuffle
+    let a = [3, 1, 4, 5, 9, 2].map(f);
+    let mut b = [7, 7, 7, 7, 7, 0].map(f);
+    let gamma = challenge(&[&a[..], &[9, 2, 3, 5, 1, 4].map(f)].concat());
+    let prod = |v: &[Fp]| v.iter().fold(Fp::one(), |p, &v| p * (v + gamma));
+    b[5] = prod(&a) * prod(&b[..5]).invert().unwrap() - gamma;
+    assert_eq!(prod(&a), prod(&b));
+    let circuit = PermutationCircuit {
+        a: a.iter().copied().map(Some).collect(),
+        b: b.iter().copied().map(Some).collect(),
+        n: 6,
+    };
+    // but the circuit's gamma is the hash of this b, not of the shuffle
+    assert_ne!(challenge(&[&a[..], &b[..]].concat()), gamma);
+    assert!(mock(circuit.k(), &circuit, a.to_vec()).is_err());
+    assert!(prove_and_verify(circuit.k(), &circuit, &a).is_err());
+}
+
+#[test]
+fn permutation_k() {
+    for n in [1, 6, 20] {
+        let a: Vec<u64> = (0..n).collect();
+        let b: Vec<u64> = a.iter().rev().copied().collect();
+        let (circuit, publics) = permutation(&a, &b);
+        assert_k(circuit.k(), &circuit, publics);
+    }
+}
+
+fn sorted(values: &[u64]) -> (SortedCircuit, Fp) {
+    let circuit = SortedCircuit {
+        values: values.iter().map(|&v| Some(f(v))).collect(),
+        n: values.len(),
+    };
+    (circuit, f(values.iter().sum()))
+}
+
+#[test]
+fn sorted_vectors() {
+    for values in [&[1, 2, 2, 7, 100, 65535][..], &[9; 6], &[42]] {
+        let (circuit, sum) = sorted(values);
+        assert!(
+            prove_and_verify(circuit.k(), &circuit, &[sum]).is_ok(),
+            "{:?}",
+            values
+        );
+        assert!(prove_and_verify(circuit.k(), &circuit, &[sum + f(1)]).is_err());
+    }
+}
+
+#[test]
+fn sorted_with_one_inversion() {
+    let (circuit, sum) = sorted(&[1, 2, 7, 2, 100]);
+    assert!(mock(circuit.k(), &circuit, vec![sum]).is_err());
+    // one apart is still out of order
+    let (circuit, sum) = sorted(&[1, 3, 2]);
+    assert!(mock(circuit.k(), &circuit, vec![sum]).is_err());
+}
+
+#[test]
+fn sorted_values_have_to_fit_the_width() {
+    let (circuit, sum) = sorted(&[1, 1 << SORTED_BITS]);
+    assert!(mock(circuit.k(), &circuit, vec![sum]).is_err());
+}
+
+#[test]
+fn sorted_k() {
+    for n in [1, 2, 10, 40] {
+        let values: Vec<u64> = (0..n).collect();
+        let (circuit, sum) = sorted(&values);
+        assert_k(circuit.k(), &circuit, vec![sum]);
+    }
+}
+
+// n^2 * variance worked out natively, n * sum(x^2) - sum(x)^2
+fn native_variance(values: &[u64]) -> u128 {
+    let n = values.len() as u128;
+    let sum: u128 = values.iter().map(|&v| v as u128).sum();
+    let sum_sq: u128 = values.iter().map(|&v| v as u128 * v as u128).sum();
+    n * sum_sq - sum * sum
+}
+
+fn variance(values: &[u64]) -> VarianceCircuit {
+    VarianceCircuit {
+        values: values.iter().map(|&v| Some(f(v))).collect(),
+        n: values.len(),
+    }
+}
+
+fn random_dataset(rng: &mut StdRng) -> Vec<u64> {
+    (0..8)
+        .map(|_| rng.gen_range(0..1u64 << VARIANCE_BITS))
+        .collect()
+}
+
+#[test]
+fn variance_of_random_datasets() {
+    let mut rng = StdRng::seed_from_u64(76);
+    for _ in 0..3 {
+        let values = random_dataset(&mut rng);
+        let circuit = variance(&values);
+        let expected = Fp::from_u128(native_variance(&values));
+        assert!(prove_and_verify(circuit.k(), &circuit, &[expected]).is_ok());
+    }
+    // no spread at all
+    let circuit = variance(&[7; 8]);
+    assert_eq!(mock(circuit.k(), &circuit, vec![f(0)]), Ok(()));
+}
+
+#[test]
+fn variance_with_a_tweaked_element() {
+    let mut rng = StdRng::seed_from_u64(76);
+    let mut values = random_dataset(&mut rng);
+    let expected = Fp::from_u128(native_variance(&values));
+    values[3] ^= 1;
+    let circuit = variance(&values);
+    assert!(prove_and_verify(circuit.k(), &circuit, &[expected]).is_err());
+}
+
+#[test]
+fn variance_inputs_have_to_fit_the_width() {
+    let values = [1, 2, 3, 1 << VARIANCE_BITS];
+    let circuit = variance(&values);
+    let expected = Fp::from_u128(native_variance(&values));
+    assert!(mock(circuit.k(), &circuit, vec![expected]).is_err());
+}
+
+#[test]
+fn variance_k() {
+    for n in [1, 8, 30, 100, 200] {
+        let values: Vec<u64> = (0..n).collect();
+        let circuit = variance(&values);
+        let expected = Fp::from_u128(native_variance(&values));
+        assert_k(circuit.k(), &circuit, vec![expected]);
+    }
+}
+
+// a public layer with the given weights, bias and clamp, applied to x
+fn linear_layer(
+    w: &[Vec<u64>],
+    bias: &[u64],
+    x: &[u64],
+    scale_bits: usize,
+    clamp_max: u64,
+) -> LinearLayerCircuit {
+    LinearLayerCircuit {
+        w: w.iter()
+            .map(|row| row.iter().map(|&v| f(v)).collect())
+            .collect(),
+        bias: bias.iter().map(|&b| f(b)).collect(),
+        x: x.iter().map(|&v| Some(f(v))).collect(),
+        inputs: x.len(),
+        outputs: bias.len(),
+        scale_bits,
+        clamp_max: f(clamp_max),
+    }
+}
+
+// the fixed point reference, each product truncated before the sum
+fn native_linear_layer(
+    w: &[Vec<u64>],
+    bias: &[u64],
+    x: &[u64],
+    scale_bits: usize,
+    clamp_max: u64,
+) -> Vec<Fp> {
+    w.iter()
+        .zip(bias)
+        .map(|(row, b)| {
+            let acc: u128 = row
+                .iter()
+                .zip(x)
+                .map(|(&w, &x)| (w as u128 * x as u128) >> scale_bits)
+                .sum::<u128>()
+                + *b as u128;
+            Fp::from_u128(acc.min(clamp_max as u128))
+        })
+        .collect()
+}
+
+// 4 inputs to 2 outputs with 8 fractional bits, 1.0 is 256
+fn layer_4_to_2() -> (Vec<Vec<u64>>, Vec<u64>, Vec<u64>) {
+    let w = vec![vec![128, 256, 64, 300], vec![512, 1, 1000, 77]];
+    let bias = vec![256, 10];
+    let x = vec![1000, 333, 4096, 7];
+    (w, bias, x)
+}
+
+#[test]
+fn linear_layer_4_to_2() {
+    let (w, bias, x) = layer_4_to_2();
+    // high enough that neither output is clamped
+    let circuit = linear_layer(&w, &bias, &x, 8, 1 << 20);
+    let y = native_linear_layer(&w, &bias, &x, 8, 1 << 20);
+    assert!(prove_and_verify(circuit.k(), &circuit, &y).is_ok());
+    // the second output is well past 10000 and clamped to it
+    let circuit = linear_layer(&w, &bias, &x, 8, 10000);
+    let y = native_linear_layer(&w, &bias, &x, 8, 10000);
+    assert_eq!(y[1], f(10000));
+    assert_eq!(mock(circuit.k(), &circuit, y), Ok(()));
+}
+
+#[test]
+fn linear_layer_off_by_one() {
+    let (w, bias, x) = layer_4_to_2();
+    let circuit = linear_layer(&w, &bias, &x, 8, 1 << 20);
+    let y = native_linear_layer(&w, &bias, &x, 8, 1 << 20);
+    for j in 0..2 {
+        let mut wrong = y.clone();
+        wrong[j] += Fp::one();
+        assert!(
+            prove_and_verify(circuit.k(), &circuit, &wrong).is_err(),
+            "y_{}",
+            j
+        );
+    }
+}
+
+#[test]
+fn linear_layer_k() {
+    let (w, bias, x) = layer_4_to_2();
+    for scale_bits in [0, 8, 16, 32] {
+        let circuit = linear_layer(&w, &bias, &x, scale_bits, 1 << 20);
+        let y = native_linear_layer(&w, &bias, &x, scale_bits, 1 << 20);
+        assert_k(circuit.k(), &circuit, y);
+    }
+    let w: Vec<Vec<u64>> = (0..4)
+        .map(|j| (0..8).map(|i| i * 100 + j).collect())
+        .collect();
+    let x: Vec<u64> = (0..8).map(|i| i * 1000).collect();
+    let circuit = linear_layer(&w, &[1; 4], &x, 8, 1 << 20);
+    let y = native_linear_layer(&w, &[1; 4], &x, 8, 1 << 20);
+    assert_k(circuit.k(), &circuit, y);
+}
+
+fn vote_tally(ballots: &[u64], expose_m: bool) -> (VoteTallyCircuit, Vec<Fp>) {
+    let circuit = VoteTallyCircuit {
+        ballots: ballots.iter().map(|&b| Some(f(b))).collect(),
+        m: ballots.len(),
+        expose_m,
+    };
+    let mut publics = vec![f(ballots.iter().sum())];
+    if expose_m {
+        publics.push(f(ballots.len() as u64));
+    }
+    (circuit, publics)
+}
+
+#[test]
+fn vote_tally_of_all_zero_all_one_and_mixed_ballots() {
+    for ballots in [[0; 7], [1; 7], [1, 0, 0, 1, 1, 0, 1]] {
+        for expose_m in [false, true] {
+            let (circuit, publics) = vote_tally(&ballots, expose_m);
+            assert!(
+                prove_and_verify(circuit.k(), &circuit, &publics).is_ok(),
+                "{:?}",
+                ballots
+            );
+        }
+    }
+}
+
+#[test]
+fn vote_tally_with_a_non_boolean_ballot() {
+    // 2 counts twice, the tally of 4 matches the ballots but not any boolean vote
+    let (circuit, publics) = vote_tally(&[1, 0, 2, 1], false);
+    assert!(mock(circuit.k(), &circuit, publics).is_err());
+}
+
+#[test]
+fn vote_tally_off_by_one() {
+    let (circuit, publics) = vote_tally(&[1, 0, 0, 1, 1, 0, 1], true);
+    for wrong in [[f(3), f(7)], [f(5), f(7)], [f(4), f(8)]] {
+        assert!(prove_and_verify(circuit.k(), &circuit, &wrong).is_err());
+    }
+    assert!(prove_and_verify(circuit.k(), &circuit, &publics).is_ok());
+}
+
+#[test]
+fn vote_tally_k() {
+    for m in [1, 7, 100] {
+        for expose_m in [false, true] {
+            let (circuit, publics) = vote_tally(&vec![1; m], expose_m);
+            assert_k(circuit.k(), &circuit, publics);
+        }
+    }
+}
+
+fn quadratic_root(x: Fp) -> QuadraticRootCircuit {
+    QuadraticRootCircuit { x: Some(x) }
+}
+
+#[test]
+fn both_roots_of_x2_minus_5x_plus_6() {
+    let publics = [f(1), -f(5), f(6)];
+    for x in [2, 3] {
+        let circuit = quadratic_root(f(x));
+        assert!(prove_and_verify(QuadraticRootCircuit::k(), &circuit, &publics).is_ok());
+    }
+}
+
+#[test]
+fn quadratic_non_root() {
+    let publics = vec![f(1), -f(5), f(6)];
+    for x in [0, 1, 4, 6] {
+        let circuit = quadratic_root(f(x));
+        assert!(mock(QuadraticRootCircuit::k(), &circuit, publics.clone()).is_err());
+    }
+    // 2 solves it, but not with c = 7
+    let circuit = quadratic_root(f(2));
+    assert!(prove_and_verify(QuadraticRootCircuit::k(), &circuit, &[f(1), -f(5), f(7)]).is_err());
+}
+
+#[test]
+fn quadratic_with_a_zero() {
+    // 2x - 8 = 0
+    let publics = vec![f(0), f(2), -f(8)];
+    assert_eq!(
+        mock(
+            QuadraticRootCircuit::k(),
+            &quadratic_root(f(4)),
+            publics.clone()
+        ),
+        Ok(())
+    );
+    assert!(mock(QuadraticRootCircuit::k(), &quadratic_root(f(3)), publics).is_err());
+    // every x solves 0 = 0
+    assert_eq!(
+        mock(
+            QuadraticRootCircuit::k(),
+            &quadratic_root(f(12345)),
+            vec![f(0); 3]
+        ),
+        Ok(())
+    );
+    // and 1 = 0 none
+    assert!(mock(
+        QuadraticRootCircuit::k(),
+        &quadratic_root(f(1)),
+        vec![f(0), f(0), f(1)]
+    )
+    .is_err());
+}
+
+#[test]
+fn quadratic_root_k() {
+    assert_k(
+        QuadraticRootCircuit::k(),
+        &quadratic_root(f(2)),
+        vec![f(1), -f(5), f(6)],
+    );
+}
+
+// the largest prime below 2^64
+const MODEXP_M: u64 = 0xffff_ffff_ffff_ffc5;
+const MODEXP_G: u64 = 3;
+const MODEXP_BITS: usize = 8;
+
+// g^x mod m with square and multiply over bits bits, like the circuit. corrupt adds one to
+// the square in that round before it is reduced
+fn native_modpow(g: u64, x: u64, m: u64, bits: usize, corrupt: Option<usize>) -> u64 {
+    let (mut acc, g, m) = (1 % m as u128, g as u128 % m as u128, m as u128);
+    for i in (0..bits).rev() {
+        acc = (acc * acc + (corrupt == Some(i)) as u128) % m;
+        if x >> i & 1 == 1 {
+            acc = acc * g % m;
+        }
+    }
+    acc as u64
+}
+
+fn mod_exp(g: u64, m: u64, x: u64, bits: usize) -> (ModExpCircuit, Fp) {
+    let circuit = ModExpCircuit {
+        g,
+        m,
+        x: Some(f(x)),
+        bits,
+    };
+    (circuit, f(native_modpow(g, x, m, bits, None)))
+}
+
+// ModExpCircuit for MODEXP_G, MODEXP_M and MODEXP_BITS with acc^2 of round n laid out by hand
+// as one more than it is, every reduction after it worked out from the corrupted value
+gadget_circuit!(ForgedModExp, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    ByteTableChip::new(config.byte_table.clone()).load(layouter.namespace(|| "byte table"))?;
+    let x = chip.load_private(layouter.namespace(|| "load x"), w[0])?;
+    let bits = chip.decompose(layouter.namespace(|| "bits of x"), x, MODEXP_BITS)?;
+    let mut acc = chip.load_constant(layouter.namespace(|| "one"), Fp::one())?;
+    for (i, bit) in bits.into_iter().enumerate().rev() {
+        let acc_2 = if i == n {
+            layouter.assign_region(
+                || "forged square",
+                |mut region| {
+                    config.s_square.enable(&mut region, 0)?;
+                    acc.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                    let v = acc.value().map(|a| a.square() + Fp::one());
+                    region.assign_advice(
+                        || "a * a",
+                        config.advice[0],
+                        1,
+                        || v.ok_or(Error::Synthesis),
+                    )
+                },
+            )?
+        } else {
+            chip.square(layouter.namespace(|| "acc^2"), acc)?
+        };
+        let acc_2 = chip.mod_const(layouter.namespace(|| "acc^2 mod m"), acc_2, MODEXP_M, 64)?;
+        let acc_g =
+            chip.mul_by_constant(layouter.namespace(|| "acc * g"), acc_2.clone(), f(MODEXP_G))?;
+        let acc_g = chip.mod_const(layouter.namespace(|| "acc * g mod m"), acc_g, MODEXP_M, 64)?;
+        acc = chip.select(layouter.namespace(|| "bit"), bit, acc_g, acc_2)?;
+    }
+    chip.expose_public(layouter.namespace(|| "expose y"), acc, 0)
+});
+
+#[test]
+fn mod_exp_of_random_8_and_16_bit_exponents() {
+    let mut rng = StdRng::seed_from_u64(83);
+    for bits in [8, 16] {
+        for m in [MODEXP_M, 1_000_003] {
+            let g = rng.gen_range(2..m);
+            let x = rng.gen_range(0..1u64 << bits);
+            let (circuit, y) = mod_exp(g, m, x, bits);
+            assert!(
+                prove_and_verify(circuit.k(), &circuit, &[y]).is_ok(),
+                "{}^{} mod {}",
+                g,
+                x,
+                m
+            );
+            assert!(mock(circuit.k(), &circuit, vec![y + f(1)]).is_err());
+        }
+    }
+}
+
+#[test]
+fn mod_exp_edge_cases() {
+    // x = 0 is 1, and m = 1 leaves 0 for everything
+    for (g, m, x, y) in [(5, 7, 0, 1), (5, 1, 3, 0), (0, 7, 3, 0), (7, 7, 200, 0)] {
+        let (circuit, expected) = mod_exp(g, m, x, 8);
+        assert_eq!(expected, f(y));
+        assert_eq!(mock(circuit.k(), &circuit, vec![f(y)]), Ok(()));
+    }
+    // an exponent wider than bits is refused
+    let (circuit, y) = mod_exp(3, 7, 256, 8);
+    assert!(synthesis_error(circuit.k(), &circuit, vec![y]).is_some());
+}
+
+#[test]
+fn mod_exp_with_a_corrupted_intermediate() {
+    let x = 0b1011_0110;
+    let (circuit, y) = mod_exp(MODEXP_G, MODEXP_M, x, MODEXP_BITS);
+    let k = circuit.k();
+    assert_eq!(
+        mock(k, &ForgedModExp::new(&[x], MODEXP_BITS), vec![y]),
+        Ok(())
+    );
+    for round in [0, 3, 7] {
+        let forged = ForgedModExp::new(&[x], round);
+        let forged_y = f(native_modpow(
+            MODEXP_G,
+            x,
+            MODEXP_M,
+            MODEXP_BITS,
+            Some(round),
+        ));
+        assert_ne!(forged_y, y);
+        assert!(
+            fails_gate(mock(k, &forged, vec![forged_y]), "square"),
+            "round {}",
+            round
+        );
+    }
+}
+
+#[test]
+fn mod_exp_k() {
+    for bits in [1, 8, 16, 32] {
+        let (circuit, y) = mod_exp(MODEXP_G, MODEXP_M, 1, bits);
+        assert_k(circuit.k(), &circuit, vec![y]);
+    }
+}
+
+fn interval(x: u64) -> IntervalCircuit {
+    IntervalCircuit { x: Some(f(x)) }
+}
+
+#[test]
+fn interval_at_both_ends() {
+    let k = IntervalCircuit::k();
+    for x in [1970, 1990, 2005] {
+        assert_eq!(
+            mock(k, &interval(x), vec![f(1970), f(2005)]),
+            Ok(()),
+            "{}",
+            x
+        );
+        assert!(
+            prove_and_verify(k, &interval(x), &[f(1970), f(2005)]).is_ok(),
+            "{}",
+            x
+        );
+    }
+    // a single point
+    assert!(prove_and_verify(k, &interval(7), &[f(7), f(7)]).is_ok());
+}
+
+#[test]
+fn interval_just_outside() {
+    let k = IntervalCircuit::k();
+    for x in [1969, 2006, 0, (1 << INTERVAL_BITS) - 1] {
+        assert!(
+            mock(k, &interval(x), vec![f(1970), f(2005)]).is_err(),
+            "{}",
+            x
+        );
+        assert!(
+            prove_and_verify(k, &interval(x), &[f(1970), f(2005)]).is_err(),
+            "{}",
+            x
+        );
+    }
+}
+
+#[test]
+fn empty_interval() {
+    let k = IntervalCircuit::k();
+    // a > b, not even a itself or b itself is inside
+    for x in [1990, 2005, 1970, 2000] {
+        assert!(
+            mock(k, &interval(x), vec![f(2005), f(1970)]).is_err(),
+            "{}",
+            x
+        );
+    }
+}
+
+#[test]
+fn interval_values_have_to_fit_the_width() {
+    let k = IntervalCircuit::k();
+    let wide = 1 << INTERVAL_BITS;
+    // too wide to even split into bits for the comparison
+    assert!(matches!(
+        synthesis_error(k, &interval(wide), vec![f(0), f(wide + 1)]),
+        Some(Error::Synthesis)
+    ));
+    // a bound that doesn't fit lays out fine, but fails its range check
+    assert!(mock(k, &interval(5), vec![f(0), f(wide)]).is_err());
+}
+
+#[test]
+fn interval_k() {
+    assert_k(
+        IntervalCircuit::k(),
+        &interval(1990),
+        vec![f(1970), f(2005)],
+    );
+}
+
+// random coefficients for the degree and y = sum(c_i * z^i) by horner's rule
+fn private_poly(degree: usize, z: Fp, rng: &mut StdRng) -> (PrivatePolyCircuit, Vec<Fp>) {
+    let coeffs: Vec<Fp> = (0..=degree).map(|_| Fp::random(&mut *rng)).collect();
+    let y = coeffs.iter().rev().fold(Fp::zero(), |y, c| y * z + c);
+    let circuit = PrivatePolyCircuit {
+        coeffs: coeffs.into_iter().map(Some).collect(),
+        degree,
+    };
+    (circuit, vec![z, y])
+}
+
+#[test]
+fn private_poly_of_degree_1_4_and_16() {
+    let mut rng = StdRng::seed_from_u64(86);
+    for degree in [1, 4, 16] {
+        let z = Fp::random(&mut rng);
+        let (circuit, publics) = private_poly(degree, z, &mut rng);
+        assert!(
+            prove_and_verify(circuit.k(), &circuit, &publics).is_ok(),
+            "{}",
+            degree
+        );
+        // y is about this z only
+        let other = [z + f(1), publics[1]];
+        assert!(
+            prove_and_verify(circuit.k(), &circuit, &other).is_err(),
+            "{}",
+            degree
+        );
+    }
+}
+
+#[test]
+fn private_poly_with_a_perturbed_coefficient() {
+    let mut rng = StdRng::seed_from_u64(86);
+    let (circuit, publics) = private_poly(4, f(3), &mut rng);
+    for i in 0..=4 {
+        let mut perturbed = circuit.clone();
+        perturbed.coeffs[i] = perturbed.coeffs[i].map(|c| c + f(1));
+        assert!(
+            mock(circuit.k(), &perturbed, publics.clone()).is_err(),
+            "c_{}",
+            i
+        );
+    }
+}
+
+#[test]
+fn private_poly_k() {
+    let mut rng = StdRng::seed_from_u64(86);
+    for degree in [0, 1, 4, 16, 50] {
+        let (circuit, publics) = private_poly(degree, f(2), &mut rng);
+        assert_k(circuit.k(), &circuit, publics);
+    }
+}
+
+fn collatz(start: u64, steps: usize) -> (CollatzCircuit, Vec<Fp>) {
+    let circuit = CollatzCircuit {
+        start: Some(f(start)),
+        steps,
+    };
+    (circuit, vec![f(steps as u64)])
+}
+
+#[test]
+fn collatz_6_reaches_1_in_8_steps() {
+    // 6, 3, 10, 5, 16, 8, 4, 2, 1
+    let (circuit, publics) = collatz(6, 8);
+    assert!(prove_and_verify(circuit.k(), &circuit, &publics).is_ok());
+    // the step count is part of the statement
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(7)]).is_err());
+}
+
+#[test]
+fn collatz_with_too_few_steps() {
+    for steps in [0, 1, 7] {
+        let (circuit, publics) = collatz(6, steps);
+        assert!(
+            mock(circuit.k(), &circuit, publics).is_err(),
+            "{} steps",
+            steps
+        );
+    }
+}
+
+#[test]
+fn collatz_pads_with_ones() {
+    // past 1 it stays at 1 instead of going round 4, 2, 1
+    for (start, steps) in [(6, 9), (6, 12), (1, 0), (1, 3), (2, 1), (2, 2)] {
+        let (circuit, publics) = collatz(start, steps);
+        assert_eq!(
+            mock(circuit.k(), &circuit, publics),
+            Ok(()),
+            "{} in {}",
+            start,
+            steps
+        );
+    }
+    // 0 never gets anywhere
+    let (circuit, publics) = collatz(0, 4);
+    assert!(mock(circuit.k(), &circuit, publics).is_err());
+}
+
+#[test]
+fn collatz_k() {
+    for steps in [1, 2, 8, 20] {
+        let (circuit, publics) = collatz(2, steps);
+        assert_k(circuit.k(), &circuit, publics);
+    }
+}
+
+fn three_factor(p: Fp, q: Fp, r: Fp) -> ThreeFactorCircuit {
+    ThreeFactorCircuit {
+        p: Some(p),
+        q: Some(q),
+        r: Some(r),
+    }
+}
+
+#[test]
+fn three_factors_of_30() {
+    let k = ThreeFactorCircuit::k();
+    assert!(prove_and_verify(k, &three_factor(f(2), f(3), f(5)), &[f(30)]).is_ok());
+    assert!(prove_and_verify(k, &three_factor(f(5), f(2), f(3)), &[f(30)]).is_ok());
+    // keys from the circuit without witnesses
+    assert!(keygen(k, &ThreeFactorCircuit::default()).is_ok());
+}
+
+#[test]
+fn three_factors_with_a_1() {
+    let k = ThreeFactorCircuit::k();
+    for (p, q, r) in [(1, 6, 5), (2, 1, 15), (2, 15, 1), (1, 1, 30)] {
+        let circuit = three_factor(f(p), f(q), f(r));
+        assert!(
+            mock(k, &circuit, vec![f(30)]).is_err(),
+            "{} * {} * {}",
+            p,
+            q,
+            r
+        );
+    }
+}
+
+#[test]
+fn three_factors_of_the_wrong_n() {
+    let k = ThreeFactorCircuit::k();
+    let circuit = three_factor(f(2), f(3), f(5));
+    for n in [29, 31, 60] {
+        assert!(prove_and_verify(k, &circuit, &[f(n)]).is_err(), "{}", n);
+    }
+}
+
+#[test]
+fn three_factors_that_wrap() {
+    // 2 * (30 / 4) * 2 is 30 in the field with the middle factor about as big as p
+    let k = ThreeFactorCircuit::k();
+    let big = f(30) * f(4).invert().unwrap();
+    assert!(mock(k, &three_factor(f(2), big, f(2)), vec![f(30)]).is_err());
+    let big = f(30) * f(6).invert().unwrap() * f(7).invert().unwrap();
+    assert!(mock(k, &three_factor(f(7), f(6), big), vec![f(30)]).is_err());
+    // the largest factors that fit still multiply out exactly
+    let max = f(u64::MAX);
+    let n = max * max * max;
+    assert_eq!(mock(k, &three_factor(max, max, max), vec![n]), Ok(()));
+}
+
+#[test]
+fn three_factor_k() {
+    assert_k(
+        ThreeFactorCircuit::k(),
+        &three_factor(f(2), f(3), f(5)),
+        vec![f(30)],
+    );
+}
+
+// a solved grid, row by row
+const SUDOKU: [u64; 81] = [
+    5, 3, 4, 6, 7, 8, 9, 1, 2, //
+    6, 7, 2, 1, 9, 5, 3, 4, 8, //
+    1, 9, 8, 3, 4, 2, 5, 6, 7, //
+    8, 5, 9, 7, 6, 1, 4, 2, 3, //
+    4, 2, 6, 8, 5, 3, 7, 9, 1, //
+    7, 1, 3, 9, 2, 4, 8, 5, 6, //
+    9, 6, 1, 5, 3, 7, 2, 8, 4, //
+    2, 8, 7, 4, 1, 9, 6, 3, 5, //
+    3, 4, 5, 2, 8, 6, 1, 7, 9, //
+];
+
+// the grid against clues at every third position and the clue values the puzzle gives
+fn sudoku(grid: &[u64; 81], clues: &[u64]) -> (SudokuCircuit, Vec<Fp>) {
+    let positions: Vec<usize> = (0..81).step_by(3).collect();
+    let circuit = SudokuCircuit {
+        grid: grid.iter().map(|&v| Some(f(v))).collect(),
+        clues: positions.clone(),
+    };
+    let publics = positions.iter().map(|&pos| f(clues[pos])).collect();
+    (circuit, publics)
+}
+
+#[test]
+fn sudoku_solution() {
+    let (circuit, publics) = sudoku(&SUDOKU, &SUDOKU);
+    assert!(prove_and_verify(circuit.k(), &circuit, &publics).is_ok());
+}
+
+#[test]
+fn sudoku_with_a_duplicate_in_a_row() {
+    // the 4 in the first row becomes a second 5, off the clue positions
+    let mut grid = SUDOKU;
+    grid[2] = 5;
+    assert!(!(0..81).step_by(3).any(|pos| pos == 2));
+    let (circuit, publics) = sudoku(&grid, &SUDOKU);
+    assert!(mock(circuit.k(), &circuit, publics).is_err());
+    // swapping two cells of a row keeps the row, but breaks their columns
+    let mut grid = SUDOKU;
+    grid.swap(1, 2);
+    let (circuit, publics) = sudoku(&grid, &SUDOKU);
+    assert!(mock(circuit.k(), &circuit, publics).is_err());
+}
+
+#[test]
+fn sudoku_against_other_clues() {
+    // a different valid solution, every digit relabelled d -> d % 9 + 1
+    let relabelled = SUDOKU.map(|d| d % 9 + 1);
+    let (circuit, publics) = sudoku(&relabelled, &relabelled);
+    assert_eq!(mock(circuit.k(), &circuit, publics), Ok(()));
+    let (circuit, publics) = sudoku(&relabelled, &SUDOKU);
+    assert!(mock(circuit.k(), &circuit, publics.clone()).is_err());
+    assert!(prove_and_verify(circuit.k(), &circuit, &publics).is_err());
+}
+
+#[test]
+fn sudoku_digits_are_1_to_9() {
+    // every digit one less is still a permutation in each group, of 0..8
+    let shifted = SUDOKU.map(|d| d - 1);
+    let (circuit, publics) = sudoku(&shifted, &shifted);
+    assert!(mock(circuit.k(), &circuit, publics).is_err());
+}
+
+#[test]
+fn sudoku_of_all_fives_under_gamma_minus_5() {
+    // with gamma = -5 every group holding a 5 has product 0, the same as prod(d + gamma)
+    // over 1..9, so a prover free to pick gamma could pass a grid of nothing but fives
+    let grid = [5; 81];
+    let prod = |digits: &[u64]| digits.iter().fold(Fp::one(), |p, &d| p * (f(d) - f(5)));
+    assert_eq!(prod(&[1, 2, 3, 4, 5, 6, 7, 8, 9]), Fp::zero());
+    assert_eq!(prod(&[5; 9]), Fp::zero());
+    // the circuit's gamma is the hash of the grid, and that is not -5
+    let gamma = challenge(&grid.map(f));
+    assert_ne!(gamma, -f(5));
+    let (circuit, publics) = sudoku(&grid, &grid);
+    assert!(mock(circuit.k(), &circuit, publics.clone()).is_err());
+    assert!(prove_and_verify(circuit.k(), &circuit, &publics).is_err());
+}
+
+#[test]
+fn sudoku_k() {
+    let (circuit, publics) = sudoku(&SUDOKU, &SUDOKU);
+    assert_k(circuit.k(), &circuit, publics);
+}
+
+fn rational(a: (u64, u64), b: (u64, u64), c: (u64, u64)) -> RationalCircuit {
+    let q = |(n, d): (u64, u64)| Some((f(n), f(d)));
+    RationalCircuit {
+        a: q(a),
+        b: q(b),
+        c: q(c),
+    }
+}
+
+#[test]
+fn a_half_plus_a_third() {
+    // 1/2 * 1/1 + 1/3 = 5/6
+    let circuit = rational((1, 2), (1, 1), (1, 3));
+    let k = RationalCircuit::k();
+    assert!(prove_and_verify(k, &circuit, &[f(5), f(6)]).is_ok());
+    assert!(prove_and_verify(k, &circuit, &[f(5), f(7)]).is_err());
+    assert!(prove_and_verify(k, &circuit, &[f(6), f(5)]).is_err());
+    // 2/3 * 3/4 + 1/4 = 3/4
+    let circuit = rational((2, 3), (3, 4), (1, 4));
+    assert_eq!(mock(k, &circuit, vec![f(3), f(4)]), Ok(()));
+}
+
+#[test]
+fn rationals_equal_up_to_scaling() {
+    let k = RationalCircuit::k();
+    // 2/4 in place of 1/2, and 5/6 published as 10/12 or 50/60
+    let circuit = rational((2, 4), (1, 1), (1, 3));
+    for (n, d) in [(5, 6), (10, 12), (50, 60)] {
+        assert_eq!(mock(k, &circuit, vec![f(n), f(d)]), Ok(()), "{}/{}", n, d);
+    }
+    assert!(mock(k, &circuit, vec![f(10), f(11)]).is_err());
+}
+
+#[test]
+fn rational_with_a_zero_denominator() {
+    let k = RationalCircuit::k();
+    for circuit in [
+        rational((1, 0), (1, 1), (1, 3)),
+        rational((1, 2), (1, 0), (1, 3)),
+        rational((1, 2), (1, 1), (0, 0)),
+    ] {
+        assert!(matches!(
+            synthesis_error(k, &circuit, vec![f(5), f(6)]),
+            Some(Error::Synthesis)
+        ));
+    }
+}
+
+#[test]
+fn rational_k() {
+    assert_k(
+        RationalCircuit::k(),
+        &rational((1, 2), (1, 1), (1, 3)),
+        vec![f(5), f(6)],
+    );
+}
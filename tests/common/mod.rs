@@ -0,0 +1,291 @@
+// shared by the integration tests, not every test binary uses all of it
+#![allow(dead_code)]
+
+use halo2::proof::{create_proof_bytes, keygen, verify_proof_bytes, VerifyError};
+use halo2_proofs::{
+    arithmetic::Field,
+    dev::{MockProver, VerifyFailure},
+    pasta::Fp,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+
+// a circuit over MyCircuit's columns whose synthesize runs the body with the chip, the
+// layouter, the private inputs w and a parameter n
+#[macro_export]
+macro_rules! gadget_circuit {
+    ($name:ident, |$chip:ident, $layouter:ident, $w:ident, $n:ident| $body:block) => {
+        #[derive(Clone, Default)]
+        struct $name {
+            w: Vec<Option<halo2_proofs::pasta::Fp>>,
+            n: usize,
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            fn new(w: &[u64], n: usize) -> Self {
+                Self {
+                    w: w.iter()
+                        .map(|&v| Some(halo2_proofs::pasta::Fp::from(v)))
+                        .collect(),
+                    n,
+                }
+            }
+
+            fn with_fields(w: &[halo2_proofs::pasta::Fp], n: usize) -> Self {
+                Self {
+                    w: w.iter().copied().map(Some).collect(),
+                    n,
+                }
+            }
+        }
+
+        impl halo2_proofs::plonk::Circuit<halo2_proofs::pasta::Fp> for $name {
+            type Config = halo2::chip::MyConfig;
+            type FloorPlanner = halo2_proofs::circuit::SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    w: vec![None; self.w.len()],
+                    n: self.n,
+                }
+            }
+
+            fn configure(
+                meta: &mut halo2_proofs::plonk::ConstraintSystem<halo2_proofs::pasta::Fp>,
+            ) -> Self::Config {
+                halo2::circuit::MyCircuit::configure(meta)
+            }
+
+            #[allow(unused_variables, unused_mut, unused_imports)]
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut $layouter: impl halo2_proofs::circuit::Layouter<halo2_proofs::pasta::Fp>,
+            ) -> Result<(), halo2_proofs::plonk::Error> {
+                use halo2::chip::Ops;
+                let $chip = halo2::chip::MyChip::new(config);
+                let $w = &self.w;
+                let $n = self.n;
+                $body
+            }
+        }
+    };
+}
+
+pub fn f(v: u64) -> Fp {
+    Fp::from(v)
+}
+
+// runs the mock prover, any error from synthesis is a test failure
+pub fn mock<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+    publics: Vec<Fp>,
+) -> Result<(), Vec<VerifyFailure>> {
+    MockProver::run(k, circuit, vec![publics]).unwrap().verify()
+}
+
+// true if verification failed and one of the failures is a constraint of the named gate
+pub fn fails_gate(result: Result<(), Vec<VerifyFailure>>, gate: &str) -> bool {
+    // "Constraint 0 in gate 3 ('name') is not satisfied in Region 1 ('region') at offset 0"
+    fails_with(result, &format!("('{}') is not satisfied", gate))
+}
+
+// true if one of the failed constraints sits in a region of that name
+pub fn fails_in_region(result: Result<(), Vec<VerifyFailure>>, region: &str) -> bool {
+    fails_with(result, &format!("('{}') at offset", region))
+}
+
+fn fails_with(result: Result<(), Vec<VerifyFailure>>, needle: &str) -> bool {
+    match result {
+        Ok(()) => false,
+        Err(failures) => failures.iter().any(|e| e.to_string().contains(needle)),
+    }
+}
+
+// the error synthesis stopped with, for gadgets that refuse a witness up front
+pub fn synthesis_error<C: Circuit<Fp>>(k: u32, circuit: &C, publics: Vec<Fp>) -> Option<Error> {
+    MockProver::run(k, circuit, vec![publics]).err()
+}
+
+// keygen on the circuit without witnesses, a real proof and its verification
+pub fn prove_and_verify<C: Circuit<Fp>>(
+    k: u32,
+    circuit: &C,
+    publics: &[Fp],
+) -> Result<(), VerifyError> {
+    let (params, pk) = keygen(k, &circuit.without_witnesses()).unwrap();
+    let proof = create_proof_bytes(&params, &pk, circuit, publics).unwrap();
+    verify_proof_bytes(&params, pk.get_vk(), &proof, publics)
+}
+
+// the rows a circuit's regions, constants and tables take, measured by laying it out
+pub fn rows<C: Circuit<Fp>>(circuit: &C, publics: &[Fp]) -> usize {
+    count(circuit, publics).rows
+}
+
+// the smallest k the measured rows fit in, blinding rows included, the same way the circuits
+// work out their own k() from a row count
+pub fn measured_k<C: Circuit<Fp>>(circuit: &C, publics: &[Fp]) -> u32 {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let rows = rows(circuit, publics) + cs.minimum_rows();
+    (1..).find(|k| (1 << k) >= rows).unwrap()
+}
+
+// a circuit's own k() against the layout, it has to be exactly the measured k and the circuit
+// has to pass the mock prover there
+pub fn assert_k<C: Circuit<Fp>>(k: u32, circuit: &C, publics: Vec<Fp>) {
+    assert_eq!(
+        k,
+        measured_k(circuit, &publics),
+        "k() is not the measured k"
+    );
+    assert_eq!(mock(k, circuit, publics), Ok(()));
+}
+
+// the copy constraints the layout asks for, constants and public inputs included
+pub fn copies<C: Circuit<Fp>>(circuit: &C, publics: &[Fp]) -> usize {
+    count(circuit, publics).copies
+}
+
+// the regions the layout enters, tables not counted
+pub fn regions<C: Circuit<Fp>>(circuit: &C, publics: &[Fp]) -> usize {
+    count(circuit, publics).regions
+}
+
+fn count<C: Circuit<Fp>>(circuit: &C, publics: &[Fp]) -> RowCounter {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let mut counter = RowCounter {
+        instance: publics.to_vec(),
+        rows: 0,
+        copies: 0,
+        regions: 0,
+    };
+    C::FloorPlanner::synthesize(&mut counter, circuit, config, constant_columns(&cs)).unwrap();
+    counter
+}
+
+// the fixed columns enabled for constants. the constraint system keeps them private, so they
+// are read off its pinned debug output and rebuilt by allocating fixed columns up to each index
+fn constant_columns(cs: &ConstraintSystem<Fp>) -> Vec<Column<Fixed>> {
+    let pinned = format!("{:?}", cs.pinned());
+    let list = pinned.split("constants: [").nth(1).unwrap();
+    let list = &list[..list.find(']').unwrap()];
+    list.split("index: ")
+        .skip(1)
+        .map(|s| {
+            let index: usize = s[..s.find(',').unwrap()].parse().unwrap();
+            let mut fresh = ConstraintSystem::<Fp>::default();
+            (0..=index).map(|_| fresh.fixed_column()).last().unwrap()
+        })
+        .collect()
+}
+
+struct RowCounter {
+    instance: Vec<Fp>,
+    rows: usize,
+    copies: usize,
+    regions: usize,
+}
+
+impl RowCounter {
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl Assignment<Fp> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.regions += 1;
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, row: usize) -> Result<Option<Fp>, Error> {
+        Ok(self.instance.get(row).copied())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // the value has to be computed, the layouter reads it back for the assigned cell
+        to()?;
+        self.touch(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        to()?;
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        self.copies += 1;
+        Ok(())
+    }
+
+    // pads a lookup table down to the end, the table itself ends at row
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Option<Assigned<Fp>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// x ^ n for building expected values
+pub fn pow(x: Fp, n: u64) -> Fp {
+    x.pow_vartime([n])
+}
@@ -0,0 +1,211 @@
+// the variants of MyCircuit's equation, x^3 + x + constant with different parts made public
+mod common;
+
+use common::{assert_k, copies, f, mock, prove_and_verify, rows};
+use halo2::{
+    circuit::{
+        commit, BatchCircuit, CommitmentCircuit, EquationSystemCircuit, MyCircuit,
+        PublicConstantCircuit,
+    },
+    proof::{create_proof_bytes, keygen, verify_proof_bytes},
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+fn cubic(x: u64, c: u64) -> Fp {
+    f(x * x * x + x + c)
+}
+
+#[test]
+fn my_circuit_k() {
+    let circuit = MyCircuit {
+        constant: f(5),
+        x: Some(f(3)),
+    };
+    assert_k(MyCircuit::k(), &circuit, vec![f(35)]);
+}
+
+fn public_constant(x: u64) -> PublicConstantCircuit {
+    PublicConstantCircuit { x: Some(f(x)) }
+}
+
+#[test]
+fn public_constant_keys_serve_every_constant() {
+    // made once, without any constant in them
+    let (params, pk) = keygen(4, &PublicConstantCircuit::default()).unwrap();
+    for (x, c) in [(3, 5), (2, 100), (7, 0)] {
+        let publics = [cubic(x, c), f(c)];
+        let proof = create_proof_bytes(&params, &pk, &public_constant(x), &publics).unwrap();
+        assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &publics).is_ok());
+        // the same proof under another constant
+        let other = [cubic(x, c), f(c + 1)];
+        assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &other).is_err());
+    }
+}
+
+#[test]
+fn public_constant_has_to_match_the_witness() {
+    // 3^3 + 3 + 5 = 35, and not with c = 6
+    assert_eq!(mock(4, &public_constant(3), vec![f(35), f(5)]), Ok(()));
+    assert!(mock(4, &public_constant(3), vec![f(35), f(6)]).is_err());
+    assert!(prove_and_verify(4, &public_constant(3), &[f(35), f(6)]).is_err());
+    // the order is [r, c]
+    assert!(mock(4, &public_constant(3), vec![f(5), f(35)]).is_err());
+}
+
+#[test]
+fn public_constant_k() {
+    assert_k(4, &public_constant(3), vec![f(35), f(5)]);
+}
+
+fn batch(xs: &[u64]) -> (BatchCircuit, Vec<Fp>) {
+    let circuit = BatchCircuit {
+        constant: f(5),
+        xs: xs.iter().map(|&x| Some(f(x))).collect(),
+        n: xs.len(),
+    };
+    (circuit, xs.iter().map(|&x| cubic(x, 5)).collect())
+}
+
+#[test]
+fn batch_of_1_8_and_64() {
+    for n in [1, 8, 64] {
+        let xs: Vec<u64> = (0..n).collect();
+        let (circuit, results) = batch(&xs);
+        assert!(
+            prove_and_verify(circuit.k(), &circuit, &results).is_ok(),
+            "n = {}",
+            n
+        );
+    }
+}
+
+#[test]
+fn batch_with_any_one_result_wrong() {
+    let xs: Vec<u64> = (0..8).collect();
+    let (circuit, results) = batch(&xs);
+    let (params, pk) = keygen(circuit.k(), &circuit.without_witnesses()).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &results).unwrap();
+    for i in 0..8 {
+        let mut wrong = results.clone();
+        wrong[i] += f(1);
+        assert!(
+            verify_proof_bytes(&params, pk.get_vk(), &proof, &wrong).is_err(),
+            "{}",
+            i
+        );
+    }
+}
+
+#[test]
+fn batch_loads_the_constant_once() {
+    // one more x costs the same rows and copies whatever n is, none of it for the constant
+    let xs: Vec<u64> = (0..9).collect();
+    let (eight, eight_results) = batch(&xs[..8]);
+    let (nine, nine_results) = batch(&xs);
+    let (one, one_results) = batch(&xs[..1]);
+    let (two, two_results) = batch(&xs[..2]);
+    assert_eq!(
+        rows(&nine, &nine_results) - rows(&eight, &eight_results),
+        rows(&two, &two_results) - rows(&one, &one_results)
+    );
+    assert_eq!(
+        copies(&nine, &nine_results) - copies(&eight, &eight_results),
+        copies(&two, &two_results) - copies(&one, &one_results)
+    );
+}
+
+#[test]
+fn batch_k() {
+    for n in [1, 8, 64, 100] {
+        let xs: Vec<u64> = (0..n).collect();
+        let (circuit, results) = batch(&xs);
+        assert_k(circuit.k(), &circuit, results);
+    }
+}
+
+fn commitment(x: u64, r: Fp) -> CommitmentCircuit {
+    CommitmentCircuit {
+        constant: f(5),
+        x: Some(f(x)),
+        r: Some(r),
+    }
+}
+
+#[test]
+fn commitment_opens_and_the_predicate_holds() {
+    let r = f(0xdead_beef);
+    let publics = [commit(f(3), r), cubic(3, 5)];
+    let circuit = commitment(3, r);
+    assert!(prove_and_verify(CommitmentCircuit::k(), &circuit, &publics).is_ok());
+    // the commitment hides x, another r gives another commitment to the same 3
+    assert_ne!(commit(f(3), r), commit(f(3), r + f(1)));
+}
+
+#[test]
+fn commitment_opens_but_the_predicate_fails() {
+    // 4 is committed to honestly, but 4^3 + 4 + 5 is not 35
+    let r = f(0xdead_beef);
+    let publics = [commit(f(4), r), cubic(3, 5)];
+    let circuit = commitment(4, r);
+    assert!(mock(CommitmentCircuit::k(), &circuit, publics.to_vec()).is_err());
+    assert!(prove_and_verify(CommitmentCircuit::k(), &circuit, &publics).is_err());
+}
+
+#[test]
+fn commitment_with_the_wrong_randomness() {
+    let r = f(0xdead_beef);
+    let publics = [commit(f(3), r), cubic(3, 5)];
+    let circuit = commitment(3, r + f(1));
+    assert!(mock(CommitmentCircuit::k(), &circuit, publics.to_vec()).is_err());
+    assert!(prove_and_verify(CommitmentCircuit::k(), &circuit, &publics).is_err());
+}
+
+#[test]
+fn commitment_k() {
+    let r = f(7);
+    let circuit = commitment(3, r);
+    assert_k(
+        CommitmentCircuit::k(),
+        &circuit,
+        vec![commit(f(3), r), cubic(3, 5)],
+    );
+}
+
+fn equation_system(x: Fp) -> EquationSystemCircuit {
+    EquationSystemCircuit { x: Some(x) }
+}
+
+#[test]
+fn equation_system_with_both_results() {
+    // 3^3 + 3 + 5 = 35 and 2 * 3^2 - 3 = 15
+    let k = EquationSystemCircuit::k();
+    assert!(prove_and_verify(k, &equation_system(f(3)), &[f(35), f(15)]).is_ok());
+}
+
+#[test]
+fn equation_system_satisfying_only_one() {
+    let k = EquationSystemCircuit::k();
+    // -3 gives the same 2x^2 - 3 = 15, but -25 for the cubic
+    assert!(mock(k, &equation_system(-f(3)), vec![f(35), f(15)]).is_err());
+    assert_eq!(
+        mock(k, &equation_system(-f(3)), vec![-f(25), f(15)]),
+        Ok(())
+    );
+    // 3 solves the cubic, but not 2x^2 - 3 = 16
+    assert!(prove_and_verify(k, &equation_system(f(3)), &[f(35), f(16)]).is_err());
+}
+
+#[test]
+fn equation_system_in_the_wrong_order() {
+    let k = EquationSystemCircuit::k();
+    assert!(prove_and_verify(k, &equation_system(f(3)), &[f(15), f(35)]).is_err());
+}
+
+#[test]
+fn equation_system_k() {
+    assert_k(
+        EquationSystemCircuit::k(),
+        &equation_system(f(3)),
+        vec![f(35), f(15)],
+    );
+}
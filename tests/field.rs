@@ -0,0 +1,170 @@
+// gadgets about field elements themselves, inverses, square roots and private powers
+#[macro_use]
+mod common;
+
+use common::{f, fails_gate, mock, pow, prove_and_verify, synthesis_error};
+use halo2_proofs::{arithmetic::Field, circuit::Chip, pasta::Fp, plonk::Error};
+use rand::{rngs::StdRng, SeedableRng};
+
+// batch_invert of every w, the inverses published in order
+gadget_circuit!(BatchInvertCircuit, |chip, layouter, w, n| {
+    let values = chip.load_private_many(layouter.namespace(|| "values"), w)?;
+    let inverses = chip.batch_invert(layouter.namespace(|| "inverses"), &values)?;
+    chip.expose_public_many(layouter.namespace(|| "inverses"), &inverses, 0)?;
+    Ok(())
+});
+
+fn inverses(values: &[Fp]) -> Vec<Fp> {
+    values.iter().map(|v| v.invert().unwrap()).collect()
+}
+
+#[test]
+fn batch_invert_five_values() {
+    let mut rng = StdRng::seed_from_u64(51);
+    let mut values: Vec<Fp> = (0..3).map(|_| Fp::random(&mut rng)).collect();
+    values.extend([f(1), -f(1)]);
+    let circuit = BatchInvertCircuit::with_fields(&values, 0);
+    assert_eq!(mock(5, &circuit, inverses(&values)), Ok(()));
+    assert!(prove_and_verify(5, &circuit, &inverses(&values)).is_ok());
+    let mut swapped = inverses(&values);
+    swapped.swap(0, 1);
+    assert!(prove_and_verify(5, &circuit, &swapped).is_err());
+}
+
+#[test]
+fn batch_invert_refuses_zero() {
+    let circuit = BatchInvertCircuit::new(&[3, 0, 5], 0);
+    assert!(matches!(
+        synthesis_error(5, &circuit, vec![f(0); 3]),
+        Some(Error::Synthesis)
+    ));
+    assert_eq!(mock(5, &BatchInvertCircuit::new(&[], 0), vec![]), Ok(()));
+}
+
+// batch_invert's region with the inverses picked by hand, w = [values, inverses]
+gadget_circuit!(ForgedBatchInvertCircuit, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    let half = w.len() / 2;
+    layouter.assign_region(
+        || "batch invert",
+        |mut region| {
+            for i in 0..half {
+                config.s_nonzero.enable(&mut region, i)?;
+                region.assign_advice(
+                    || "v",
+                    config.advice[0],
+                    i,
+                    || w[i].ok_or(Error::Synthesis),
+                )?;
+                region.assign_advice(
+                    || "1 / v",
+                    config.advice[1],
+                    i,
+                    || w[half + i].ok_or(Error::Synthesis),
+                )?;
+            }
+            Ok(())
+        },
+    )
+});
+
+#[test]
+fn batch_invert_rejects_a_wrong_inverse() {
+    let values = [f(2), f(3), f(5)];
+    let mut w = values.to_vec();
+    w.extend(inverses(&values));
+    assert_eq!(
+        mock(5, &ForgedBatchInvertCircuit::with_fields(&w, 0), vec![]),
+        Ok(())
+    );
+    w[4] = f(3);
+    assert!(fails_gate(
+        mock(5, &ForgedBatchInvertCircuit::with_fields(&w, 0), vec![]),
+        "nonzero"
+    ));
+}
+
+// y = w[1] witnessed as a square root of x = w[0], x published
+gadget_circuit!(SqrtCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    chip.sqrt_witness(layouter.namespace(|| "sqrt"), w[1], x.clone())?;
+    chip.expose_public(layouter.namespace(|| "x"), x, 0)
+});
+
+#[test]
+fn sqrt_witness_of_a_residue() {
+    let x = Fp::random(StdRng::seed_from_u64(52)).square();
+    let y = x.sqrt().unwrap();
+    for root in [y, -y] {
+        let circuit = SqrtCircuit::with_fields(&[x, root], 0);
+        assert_eq!(mock(4, &circuit, vec![x]), Ok(()));
+    }
+    assert!(prove_and_verify(4, &SqrtCircuit::with_fields(&[x, y], 0), &[x]).is_ok());
+    assert_eq!(mock(4, &SqrtCircuit::new(&[49, 7], 0), vec![f(49)]), Ok(()));
+}
+
+#[test]
+fn sqrt_witness_refuses_a_non_residue() {
+    let x = (2..)
+        .map(f)
+        .find(|x| bool::from(x.sqrt().is_none()))
+        .unwrap();
+    let circuit = SqrtCircuit::with_fields(&[x, f(1)], 0);
+    assert!(matches!(
+        synthesis_error(4, &circuit, vec![x]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn sqrt_witness_rejects_a_tampered_root() {
+    let circuit = SqrtCircuit::new(&[49, 8], 0);
+    assert!(fails_gate(mock(4, &circuit, vec![f(49)]), "square"));
+    // a root of a different value does not help either
+    let circuit = SqrtCircuit::new(&[49, 6], 0);
+    assert!(fails_gate(mock(4, &circuit, vec![f(49)]), "square"));
+}
+
+// x = w[0] raised to the little endian bits w[1..], the power published
+gadget_circuit!(PowPrivateCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let bits = chip.load_private_many(layouter.namespace(|| "e bits"), &w[1..])?;
+    let y = chip.pow_private(layouter.namespace(|| "x^e"), x, &bits)?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+fn pow_private(x: Fp, e: u64, n_bits: usize) -> PowPrivateCircuit {
+    let mut w = vec![x];
+    w.extend((0..n_bits).map(|i| f((e >> i) & 1)));
+    PowPrivateCircuit::with_fields(&w, 0)
+}
+
+#[test]
+fn pow_private_matches_fp_pow() {
+    let mut rng = StdRng::seed_from_u64(53);
+    for (e, n_bits) in [(0xa7, 8), (0xff, 8), (0, 8), (0xbeef, 16), (0x8001, 16)] {
+        let x = Fp::random(&mut rng);
+        let circuit = pow_private(x, e, n_bits);
+        assert_eq!(mock(7, &circuit, vec![pow(x, e)]), Ok(()), "e = {:#x}", e);
+        assert!(
+            mock(7, &circuit, vec![pow(x, e + 1)]).is_err(),
+            "e = {:#x}",
+            e
+        );
+    }
+}
+
+#[test]
+fn pow_private_proves_and_verifies() {
+    let x = Fp::random(StdRng::seed_from_u64(53));
+    let circuit = pow_private(x, 0xbeef, 16);
+    assert!(prove_and_verify(7, &circuit, &[pow(x, 0xbeef)]).is_ok());
+    assert!(prove_and_verify(7, &circuit, &[pow(x, 0xbeee)]).is_err());
+}
+
+#[test]
+fn pow_private_needs_boolean_bits() {
+    // a bit of 2 would take x^2 steps the public exponent can't describe
+    let circuit = PowPrivateCircuit::new(&[3, 2, 0], 0);
+    assert!(fails_gate(mock(5, &circuit, vec![f(9)]), "pow step"));
+}
@@ -0,0 +1,358 @@
+// the in-crate mimc hash, in circuit against the native digest
+mod common;
+
+use common::{assert_k, f, mock, prove_and_verify};
+use halo2::{
+    chip::{mimc_hash, MiMCChip, MiMCConfig, MyChip, MyConfig, Ops, MIMC_ROUNDS},
+    circuit::{
+        hash_chain, poseidon_hash, state_commitment, HashChainCircuit, MyCircuit, PoseidonCircuit,
+        TransferCircuit,
+    },
+    proof::{create_proof_bytes, keygen, verify_proof_bytes, VerifyError},
+};
+use halo2_gadgets::poseidon::primitives::{ConstantLength, Hash, P128Pow5T3};
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Chip, Layouter, SimpleFloorPlanner},
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+// MIMC_ROUNDS rounds, the final addition and the loads
+const K: u32 = 8;
+
+// mimc of x with key k, the digest published. with corrupt set, the region is laid out by hand
+// with that round's constant replaced and the witness worked out from the replaced constant
+#[derive(Clone, Default)]
+struct MiMCCircuit {
+    x: Option<Fp>,
+    k: Option<Fp>,
+    corrupt: Option<(usize, Fp)>,
+}
+
+// the round constants the chip uses, i^7
+fn round_constants() -> Vec<Fp> {
+    (0..MIMC_ROUNDS as u64)
+        .map(|i| f(i).pow_vartime([7]))
+        .collect()
+}
+
+impl Circuit<Fp> for MiMCCircuit {
+    type Config = (MyConfig, MiMCConfig);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            corrupt: self.corrupt,
+            ..Self::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let mimc = MiMCChip::configure(meta, [config.advice[0], config.advice[1]], config.coeff);
+        (config, mimc)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let mimc = MiMCChip::new(config.1);
+        let inputs = chip.load_private_many(layouter.namespace(|| "x, k"), &[self.x, self.k])?;
+        let (x, k) = (inputs[0].clone(), inputs[1].clone());
+        let digest = match self.corrupt {
+            None => mimc.hash(layouter.namespace(|| "mimc"), x, k)?,
+            Some((round, c)) => {
+                let config = mimc.config().clone();
+                let mut constants = round_constants();
+                constants[round] = c;
+                layouter.assign_region(
+                    || "mimc",
+                    |mut region| {
+                        let mut x_cell = x.copy_advice(|| "x", &mut region, config.advice[0], 0)?;
+                        k.copy_advice(|| "k", &mut region, config.advice[1], 0)?;
+                        for (i, c) in constants.iter().enumerate() {
+                            config.s_round.enable(&mut region, i)?;
+                            region.assign_fixed(|| "c", config.round_constants, i, || Ok(*c))?;
+                            let v = x_cell
+                                .value()
+                                .and_then(|x| k.value().map(|k| (*x + *k + c).cube()));
+                            x_cell = region.assign_advice(
+                                || "x",
+                                config.advice[0],
+                                i + 1,
+                                || v.ok_or(Error::Synthesis),
+                            )?;
+                            region.assign_advice(
+                                || "k",
+                                config.advice[1],
+                                i + 1,
+                                || k.value().copied().ok_or(Error::Synthesis),
+                            )?;
+                        }
+                        config.s_final.enable(&mut region, MIMC_ROUNDS)?;
+                        let v = x_cell.value().and_then(|x| k.value().map(|k| *x + *k));
+                        region.assign_advice(
+                            || "digest",
+                            config.advice[0],
+                            MIMC_ROUNDS + 1,
+                            || v.ok_or(Error::Synthesis),
+                        )
+                    },
+                )?
+            }
+        };
+        chip.expose_public(layouter.namespace(|| "digest"), digest, 0)
+    }
+}
+
+fn mimc(x: Fp, k: Fp) -> MiMCCircuit {
+    MiMCCircuit {
+        x: Some(x),
+        k: Some(k),
+        corrupt: None,
+    }
+}
+
+#[test]
+fn mimc_matches_the_native_digest() {
+    let mut rng = StdRng::seed_from_u64(67);
+    for _ in 0..3 {
+        let (x, k) = (Fp::random(&mut rng), Fp::random(&mut rng));
+        let digest = mimc_hash(x, k);
+        assert_eq!(mock(K, &mimc(x, k), vec![digest]), Ok(()));
+        assert!(mock(K, &mimc(x, k), vec![digest + f(1)]).is_err());
+        // swapping the message and the key is a different hash
+        assert!(mock(K, &mimc(k, x), vec![digest]).is_err());
+    }
+    assert_eq!(
+        mock(K, &mimc(f(0), f(0)), vec![mimc_hash(f(0), f(0))]),
+        Ok(())
+    );
+}
+
+#[test]
+fn mimc_constants_are_the_chips() {
+    // the by hand layout with nothing corrupted is the chip's own circuit
+    let (x, k) = (f(3), f(5));
+    let same = MiMCCircuit {
+        corrupt: Some((7, round_constants()[7])),
+        ..mimc(x, k)
+    };
+    let (_, honest) = keygen(K, &mimc(x, k).without_witnesses()).unwrap();
+    let (_, by_hand) = keygen(K, &same.without_witnesses()).unwrap();
+    assert_eq!(
+        format!("{:?}", honest.get_vk().pinned()),
+        format!("{:?}", by_hand.get_vk().pinned())
+    );
+}
+
+#[test]
+fn mimc_rejects_a_corrupted_round_constant() {
+    let (x, k) = (f(3), f(5));
+    let corrupted = MiMCCircuit {
+        corrupt: Some((7, f(12345))),
+        ..mimc(x, k)
+    };
+    let (params, honest) = keygen(K, &mimc(x, k).without_witnesses()).unwrap();
+    let (_, bad) = keygen(K, &corrupted.without_witnesses()).unwrap();
+    // the constants are fixed columns, so they end up in the verifying key
+    assert_ne!(
+        format!("{:?}", honest.get_vk().pinned()),
+        format!("{:?}", bad.get_vk().pinned())
+    );
+    // a proof with the corrupted keys doesn't pass the honest key, whatever digest it claims
+    let digest = [mimc_hash(x, k)];
+    let proof = create_proof_bytes(&params, &bad, &corrupted, &digest).unwrap();
+    assert!(verify_proof_bytes(&params, honest.get_vk(), &proof, &digest).is_err());
+    // and the corrupted digest is not the real one either
+    assert!(mock(K, &corrupted, digest.to_vec()).is_err());
+}
+
+fn chain(seed: Fp, n: usize) -> HashChainCircuit {
+    HashChainCircuit {
+        seed: Some(seed),
+        n,
+    }
+}
+
+// the chain worked out link by link, separately from hash_chain
+fn native_chain(seed: Fp, n: usize) -> Fp {
+    let mut h = seed;
+    for _ in 0..n {
+        h = mimc_hash(h, Fp::zero());
+    }
+    h
+}
+
+#[test]
+fn hash_chain_of_1_and_16() {
+    let seed = f(0x5eed);
+    for n in [1, 16] {
+        let digest = native_chain(seed, n);
+        assert_eq!(hash_chain(seed, n), digest);
+        let circuit = chain(seed, n);
+        assert!(
+            prove_and_verify(circuit.k(), &circuit, &[digest]).is_ok(),
+            "n = {}",
+            n
+        );
+        // one link short or one too many
+        assert!(mock(circuit.k(), &circuit, vec![native_chain(seed, n - 1)]).is_err());
+        assert!(mock(circuit.k(), &circuit, vec![native_chain(seed, n + 1)]).is_err());
+    }
+}
+
+#[test]
+fn hash_chain_from_the_wrong_seed() {
+    let digest = native_chain(f(0x5eed), 16);
+    let circuit = chain(f(0x5eee), 16);
+    assert!(prove_and_verify(circuit.k(), &circuit, &[digest]).is_err());
+}
+
+#[test]
+fn hash_chain_of_0_is_the_seed() {
+    let circuit = chain(f(0x5eed), 0);
+    assert!(prove_and_verify(circuit.k(), &circuit, &[f(0x5eed)]).is_ok());
+    assert!(mock(circuit.k(), &circuit, vec![native_chain(f(0x5eed), 1)]).is_err());
+}
+
+#[test]
+fn hash_chain_k() {
+    for n in [0, 1, 16] {
+        let circuit = chain(f(0x5eed), n);
+        assert_k(circuit.k(), &circuit, vec![native_chain(f(0x5eed), n)]);
+    }
+}
+
+fn transfer(sender: u64, receiver: u64, amount: u64) -> TransferCircuit {
+    TransferCircuit {
+        sender_before: Some(f(sender)),
+        receiver_before: Some(f(receiver)),
+        amount: Some(f(amount)),
+        salt_before: Some(f(0x5a17)),
+        salt_after: Some(f(0x5a18)),
+    }
+}
+
+// the [before, after] commitments worked out natively, after by field arithmetic so an
+// overdraft wraps instead of failing here
+fn states(sender: u64, receiver: u64, amount: u64) -> Vec<Fp> {
+    let (sender, receiver, amount) = (f(sender), f(receiver), f(amount));
+    vec![
+        state_commitment(sender, receiver, f(0x5a17)),
+        state_commitment(sender - amount, receiver + amount, f(0x5a18)),
+    ]
+}
+
+#[test]
+fn transfer_keeps_the_balances() {
+    let k = TransferCircuit::k();
+    let circuit = transfer(100, 50, 30);
+    assert!(prove_and_verify(k, &circuit, &states(100, 50, 30)).is_ok());
+    // the whole balance and nothing at all are both valid
+    for (sender, receiver, amount) in [(30, 50, 30), (100, 50, 0), (u64::MAX, 0, u64::MAX)] {
+        let circuit = transfer(sender, receiver, amount);
+        assert_eq!(
+            mock(k, &circuit, states(sender, receiver, amount)),
+            Ok(()),
+            "{} -> {} of {}",
+            sender,
+            receiver,
+            amount
+        );
+    }
+}
+
+#[test]
+fn transfer_rejects_an_overdraft() {
+    let k = TransferCircuit::k();
+    for (sender, amount) in [(30, 31), (0, 1), (100, u64::MAX)] {
+        let circuit = transfer(sender, 50, amount);
+        assert!(mock(k, &circuit, states(sender, 50, amount)).is_err());
+    }
+    let circuit = transfer(30, 50, 31);
+    assert!(prove_and_verify(k, &circuit, &states(30, 50, 31)).is_err());
+}
+
+#[test]
+fn transfer_rejects_a_receiver_past_u64() {
+    let circuit = transfer(100, u64::MAX, 1);
+    assert!(mock(TransferCircuit::k(), &circuit, states(100, u64::MAX, 1)).is_err());
+}
+
+#[test]
+fn transfer_against_tampered_commitments() {
+    let k = TransferCircuit::k();
+    let circuit = transfer(100, 50, 30);
+    let honest = states(100, 50, 30);
+    let tampered = [
+        // swapped, or the after state of a different amount, receiver or salt
+        vec![honest[1], honest[0]],
+        vec![honest[0], states(100, 50, 31)[1]],
+        vec![honest[0], states(100, 51, 30)[1]],
+        vec![honest[0], state_commitment(f(70), f(80), f(0x5a19))],
+        vec![honest[0] + Fp::one(), honest[1]],
+    ];
+    for publics in tampered {
+        assert!(matches!(
+            prove_and_verify(k, &circuit, &publics),
+            Err(VerifyError::Unsatisfied)
+        ));
+    }
+}
+
+#[test]
+fn transfer_k() {
+    assert_k(
+        TransferCircuit::k(),
+        &transfer(100, 50, 30),
+        states(100, 50, 30),
+    );
+}
+
+// the digest straight from halo2_gadgets with the same parameters, apart from poseidon_hash
+fn native_poseidon(x: Fp) -> Fp {
+    Hash::<_, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([x, Fp::zero()])
+}
+
+#[test]
+fn poseidon_matches_the_native_digest() {
+    let k = PoseidonCircuit::k();
+    for x in [Fp::zero(), f(1), f(35), -Fp::one()] {
+        let digest = native_poseidon(x);
+        assert_eq!(poseidon_hash(x), digest);
+        let circuit = PoseidonCircuit { x: Some(x) };
+        assert!(prove_and_verify(k, &circuit, &[digest]).is_ok());
+    }
+    // the digest depends on the input
+    assert_ne!(native_poseidon(f(1)), native_poseidon(f(2)));
+}
+
+#[test]
+fn poseidon_rejects_a_wrong_digest() {
+    let k = PoseidonCircuit::k();
+    let circuit = PoseidonCircuit { x: Some(f(35)) };
+    let digest = native_poseidon(f(35));
+    for wrong in [native_poseidon(f(36)), digest + Fp::one(), f(35)] {
+        assert!(mock(k, &circuit, vec![wrong]).is_err());
+        assert!(matches!(
+            prove_and_verify(k, &circuit, &[wrong]),
+            Err(VerifyError::Unsatisfied)
+        ));
+    }
+}
+
+#[test]
+fn poseidon_k() {
+    let x = f(35);
+    assert_k(
+        PoseidonCircuit::k(),
+        &PoseidonCircuit { x: Some(x) },
+        vec![native_poseidon(x)],
+    );
+}
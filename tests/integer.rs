@@ -0,0 +1,631 @@
+// machine integer gadgets, range checked against the byte table so the field never wraps
+#[macro_use]
+mod common;
+
+use common::{f, mock, synthesis_error};
+use halo2::chip::{U256Chip, MAX_BITS};
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::Chip,
+    pasta::Fp,
+    plonk::Error,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+// every circuit here loads the byte table, which alone takes 256 rows
+const K: u32 = 10;
+
+fn load_table(
+    chip: &halo2::chip::MyChip,
+    layouter: impl halo2_proofs::circuit::Layouter<Fp>,
+) -> Result<(), Error> {
+    halo2::chip::ByteTableChip::new(chip.config().byte_table.clone()).load(layouter)
+}
+
+// add_u64 of w[0] and w[1], the sum and the carry published
+gadget_circuit!(AddU64Circuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let b = chip.load_private(layouter.namespace(|| "b"), w[1])?;
+    let (sum, carry) = chip.add_u64(layouter.namespace(|| "a + b"), a, b)?;
+    chip.expose_public_many(layouter.namespace(|| "out"), &[sum, carry], 0)?;
+    Ok(())
+});
+
+// add_u64 laid out with the sum and carry picked by hand, w = [a, b, sum, carry]
+gadget_circuit!(ForgedAddU64Circuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let cells = chip.load_private_many(layouter.namespace(|| "a, b, sum, carry"), w)?;
+    let (a, b, sum, carry) = (
+        cells[0].clone(),
+        cells[1].clone(),
+        cells[2].clone(),
+        cells[3].clone(),
+    );
+    chip.range_check(layouter.namespace(|| "a"), a.clone(), 64)?;
+    chip.range_check(layouter.namespace(|| "b"), b.clone(), 64)?;
+    chip.range_check(layouter.namespace(|| "sum"), sum.clone(), 64)?;
+    chip.assert_boolean(layouter.namespace(|| "carry"), carry.clone())?;
+    let total = chip.add(layouter.namespace(|| "a + b"), a, b)?;
+    let wrapped = chip.linear_combination(
+        layouter.namespace(|| "sum + carry * 2^64"),
+        &[(Fp::one(), sum), (Fp::from_u128(1 << 64), carry)],
+    )?;
+    chip.assert_equal(layouter.namespace(|| "no overflow lost"), total, wrapped)
+});
+
+#[test]
+fn add_u64_without_carry() {
+    let circuit = AddU64Circuit::new(&[40, 2], 0);
+    assert_eq!(mock(K, &circuit, vec![f(42), f(0)]), Ok(()));
+    assert!(mock(K, &circuit, vec![f(42), f(1)]).is_err());
+    let circuit = AddU64Circuit::new(&[u64::MAX - 1, 1], 0);
+    assert_eq!(mock(K, &circuit, vec![f(u64::MAX), f(0)]), Ok(()));
+}
+
+#[test]
+fn add_u64_with_carry() {
+    // u64::MAX + 1 wraps to 0 with the carry set
+    let circuit = AddU64Circuit::new(&[u64::MAX, 1], 0);
+    assert_eq!(mock(K, &circuit, vec![f(0), f(1)]), Ok(()));
+    assert!(mock(K, &circuit, vec![f(0), f(0)]).is_err());
+    let circuit = AddU64Circuit::new(&[u64::MAX, u64::MAX], 0);
+    assert_eq!(mock(K, &circuit, vec![f(u64::MAX - 1), f(1)]), Ok(()));
+}
+
+#[test]
+fn add_u64_rejects_inputs_over_64_bits() {
+    // 2^64 + 0 would fit the sum and carry, but a is out of range
+    let circuit = AddU64Circuit::with_fields(&[Fp::from_u128(1 << 64), f(0)], 0);
+    assert!(mock(K, &circuit, vec![f(0), f(1)]).is_err());
+}
+
+#[test]
+fn add_u64_rejects_a_forged_carry() {
+    let honest = ForgedAddU64Circuit::new(&[u64::MAX, 1, 0, 1], 0);
+    assert_eq!(mock(K, &honest, vec![]), Ok(()));
+    // dropping the carry loses 2^64
+    let forged = ForgedAddU64Circuit::new(&[u64::MAX, 1, 0, 0], 0);
+    assert!(mock(K, &forged, vec![]).is_err());
+    // a carry on a sum that did not overflow, with the sum adjusted by 2^64 in the field
+    let forged =
+        ForgedAddU64Circuit::with_fields(&[f(40), f(2), f(42) - Fp::from_u128(1 << 64), f(1)], 0);
+    assert!(mock(K, &forged, vec![]).is_err());
+    // a carry of 2 paid for with a sum wrapped below zero
+    let forged = ForgedAddU64Circuit::with_fields(
+        &[
+            f(u64::MAX),
+            f(u64::MAX),
+            f(u64::MAX - 1) - Fp::from_u128(1 << 64),
+            f(2),
+        ],
+        0,
+    );
+    assert!(mock(K, &forged, vec![]).is_err());
+}
+
+// divrem_u64 of w[0] by w[1], q and r published
+gadget_circuit!(DivRemCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let b = chip.load_private(layouter.namespace(|| "b"), w[1])?;
+    let (q, r) = chip.divrem_u64(layouter.namespace(|| "a / b"), a, b)?;
+    chip.expose_public_many(layouter.namespace(|| "out"), &[q, r], 0)?;
+    Ok(())
+});
+
+// divrem_u64 laid out with q and r picked by hand, w = [a, b, q, r]
+gadget_circuit!(ForgedDivRemCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let cells = chip.load_private_many(layouter.namespace(|| "a, b, q, r"), w)?;
+    let (a, b, q, r) = (
+        cells[0].clone(),
+        cells[1].clone(),
+        cells[2].clone(),
+        cells[3].clone(),
+    );
+    for (name, cell) in [("a", &a), ("b", &b), ("q", &q), ("r", &r)] {
+        chip.range_check(layouter.namespace(|| name), cell.clone(), 64)?;
+    }
+    let qb_r = chip.mul_add(layouter.namespace(|| "q * b + r"), q, b.clone(), r.clone())?;
+    chip.assert_equal(layouter.namespace(|| "q * b + r == a"), qb_r, a)?;
+    let r_lt_b = chip.lt(layouter.namespace(|| "r < b"), r, b, 64)?;
+    chip.assert_equals_constant(layouter.namespace(|| "r < b holds"), r_lt_b, Fp::one())
+});
+
+#[test]
+fn divrem_u64_exact() {
+    let circuit = DivRemCircuit::new(&[42, 7], 0);
+    assert_eq!(mock(K, &circuit, vec![f(6), f(0)]), Ok(()));
+    assert!(mock(K, &circuit, vec![f(5), f(7)]).is_err());
+    let circuit = DivRemCircuit::new(&[0, 9], 0);
+    assert_eq!(mock(K, &circuit, vec![f(0), f(0)]), Ok(()));
+}
+
+#[test]
+fn divrem_u64_with_a_remainder() {
+    let circuit = DivRemCircuit::new(&[47, 7], 0);
+    assert_eq!(mock(K, &circuit, vec![f(6), f(5)]), Ok(()));
+    let circuit = DivRemCircuit::new(&[5, 7], 0);
+    assert_eq!(mock(K, &circuit, vec![f(0), f(5)]), Ok(()));
+    let (a, b) = (u64::MAX, 1 << 32);
+    let circuit = DivRemCircuit::new(&[a, b], 0);
+    assert_eq!(mock(K, &circuit, vec![f(a / b), f(a % b)]), Ok(()));
+}
+
+#[test]
+fn divrem_u64_by_one() {
+    for a in [0, 1, 12345, u64::MAX] {
+        let circuit = DivRemCircuit::new(&[a, 1], 0);
+        assert_eq!(mock(K, &circuit, vec![f(a), f(0)]), Ok(()), "{}", a);
+    }
+}
+
+#[test]
+fn divrem_u64_by_zero_is_refused() {
+    let circuit = DivRemCircuit::new(&[42, 0], 0);
+    assert!(matches!(
+        synthesis_error(K, &circuit, vec![f(0), f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn divrem_u64_rejects_a_remainder_not_below_b() {
+    assert_eq!(
+        mock(K, &ForgedDivRemCircuit::new(&[47, 7, 6, 5], 0), vec![]),
+        Ok(())
+    );
+    // 47 = 5 * 7 + 12 holds but 12 >= 7
+    assert!(mock(K, &ForgedDivRemCircuit::new(&[47, 7, 5, 12], 0), vec![]).is_err());
+    // r == b is the boundary, 42 = 5 * 7 + 7
+    assert!(mock(K, &ForgedDivRemCircuit::new(&[42, 7, 5, 7], 0), vec![]).is_err());
+    // and with b = 0 every a is 0 * 0 + a
+    assert!(mock(K, &ForgedDivRemCircuit::new(&[42, 0, 0, 42], 0), vec![]).is_err());
+}
+
+// parity of w[0] as an n bit value, the bit published
+gadget_circuit!(ParityCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let bit = chip.parity(layouter.namespace(|| "a & 1"), a, n)?;
+    chip.expose_public(layouter.namespace(|| "bit"), bit, 0)
+});
+
+// parity laid out with half and bit picked by hand, w = [a, half, bit] and half range checked
+// to n bits
+gadget_circuit!(ForgedParityCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let cells = chip.load_private_many(layouter.namespace(|| "a, half, bit"), w)?;
+    let (a, half, bit) = (cells[0].clone(), cells[1].clone(), cells[2].clone());
+    chip.assert_boolean(layouter.namespace(|| "bit"), bit.clone())?;
+    chip.range_check(layouter.namespace(|| "half"), half.clone(), n as u8)?;
+    let sum = chip.linear_combination(
+        layouter.namespace(|| "2 * half + bit"),
+        &[(Fp::from(2), half), (Fp::one(), bit)],
+    )?;
+    chip.assert_equal(layouter.namespace(|| "sum == a"), sum, a)
+});
+
+#[test]
+fn parity_of_even_and_odd_values() {
+    for (a, bit) in [(0, 0), (1, 1), (42, 0), (43, 1), (u64::MAX, 1)] {
+        let circuit = ParityCircuit::new(&[a], 64);
+        assert_eq!(mock(K, &circuit, vec![f(bit)]), Ok(()), "{}", a);
+        assert!(mock(K, &circuit, vec![f(1 - bit)]).is_err(), "{}", a);
+    }
+    assert_eq!(mock(K, &ParityCircuit::new(&[1], 1), vec![f(1)]), Ok(()));
+}
+
+#[test]
+fn parity_refuses_n_bits_past_max_bits() {
+    for n_bits in [0, MAX_BITS + 1, 256] {
+        let circuit = ParityCircuit::new(&[2], n_bits);
+        assert!(
+            matches!(
+                synthesis_error(K, &circuit, vec![f(0)]),
+                Some(Error::Synthesis)
+            ),
+            "{} bits",
+            n_bits
+        );
+    }
+    assert_eq!(
+        mock(K, &ParityCircuit::new(&[2], MAX_BITS), vec![f(0)]),
+        Ok(())
+    );
+}
+
+#[test]
+fn parity_rejects_a_flipped_bit() {
+    // 0 = 2 * ((p - 1) / 2) + 1 in the field
+    let half = -Fp::one() * Fp::from(2).invert().unwrap();
+    let flipped = [f(0), half, f(1)];
+    assert_eq!(
+        mock(
+            K,
+            &ForgedParityCircuit::new(&[0, 0, 0], MAX_BITS - 1),
+            vec![]
+        ),
+        Ok(())
+    );
+    // MAX_BITS - 1 bits of half is the most parity allows, and (p - 1) / 2 does not fit
+    let forged = ForgedParityCircuit::with_fields(&flipped, MAX_BITS - 1);
+    assert!(mock(K, &forged, vec![]).is_err());
+    // one bit more and the forgery would go through, which is why n_bits stops at MAX_BITS
+    let forged = ForgedParityCircuit::with_fields(&flipped, MAX_BITS);
+    assert_eq!(mock(K, &forged, vec![]), Ok(()));
+}
+
+fn limbs(w: &[Option<Fp>]) -> Option<[u64; 4]> {
+    let mut out = [0; 4];
+    for (o, v) in out.iter_mut().zip(w) {
+        *o = v.map(|v| v.get_lower_128() as u64)?;
+    }
+    Some(out)
+}
+
+// add_u256 of w[0..4] and w[4..8], the four sum limbs and the overflow bit published
+gadget_circuit!(AddU256Circuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let u256 = U256Chip::new(chip.config().clone());
+    let a = u256.load(layouter.namespace(|| "a"), limbs(&w[..4]))?;
+    let b = u256.load(layouter.namespace(|| "b"), limbs(&w[4..]))?;
+    let (sum, overflow) = u256.add_u256(layouter.namespace(|| "a + b"), &a, &b)?;
+    let mut out = sum.limbs.to_vec();
+    out.push(overflow);
+    chip.expose_public_many(layouter.namespace(|| "out"), &out, 0)?;
+    Ok(())
+});
+
+// add_u256 laid out limb by limb with the sums and carries picked by hand,
+// w = [a limbs, b limbs, sum limbs, carries]
+gadget_circuit!(ForgedAddU256Circuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let cells = chip.load_private_many(layouter.namespace(|| "witness"), w)?;
+    let mut carry: Option<halo2_proofs::circuit::AssignedCell<Fp, Fp>> = None;
+    for i in 0..4 {
+        let (a, b, sum, carry_out) = (&cells[i], &cells[4 + i], &cells[8 + i], &cells[12 + i]);
+        for cell in [a, b, sum] {
+            chip.range_check(layouter.namespace(|| "limb"), cell.clone(), 64)?;
+        }
+        chip.assert_boolean(layouter.namespace(|| "carry"), carry_out.clone())?;
+        let mut total = chip.add(layouter.namespace(|| "a + b"), a.clone(), b.clone())?;
+        if let Some(carry) = carry {
+            total = chip.add(layouter.namespace(|| "+ carry"), total, carry)?;
+        }
+        let wrapped = chip.linear_combination(
+            layouter.namespace(|| "sum + carry * 2^64"),
+            &[
+                (Fp::one(), sum.clone()),
+                (Fp::from_u128(1 << 64), carry_out.clone()),
+            ],
+        )?;
+        chip.assert_equal(layouter.namespace(|| "limb sum"), total, wrapped)?;
+        carry = Some(carry_out.clone());
+    }
+    Ok(())
+});
+
+// little endian limbs as (low, high) u128 halves
+fn halves(v: [u64; 4]) -> (u128, u128) {
+    (
+        v[0] as u128 | (v[1] as u128) << 64,
+        v[2] as u128 | (v[3] as u128) << 64,
+    )
+}
+
+// the 256 bit sum worked out on u128 halves, its limbs, the carries into each limb's
+// successor and the overflow
+fn add_u256_native(a: [u64; 4], b: [u64; 4]) -> ([u64; 4], [u64; 4]) {
+    let ((a_lo, a_hi), (b_lo, b_hi)) = (halves(a), halves(b));
+    let (lo, c) = a_lo.overflowing_add(b_lo);
+    let (hi, c1) = a_hi.overflowing_add(b_hi);
+    let (hi, c2) = hi.overflowing_add(c as u128);
+    let sum = [lo as u64, (lo >> 64) as u64, hi as u64, (hi >> 64) as u64];
+    // a carry leaves limb i when the limbs up to i overflow 64 * (i + 1) bits
+    let mut carries = [0; 4];
+    let mut carry = 0u128;
+    for i in 0..4 {
+        let total = a[i] as u128 + b[i] as u128 + carry;
+        carry = total >> 64;
+        carries[i] = carry as u64;
+    }
+    assert_eq!(carries[3], (c1 || c2) as u64);
+    (sum, carries)
+}
+
+fn u256_publics(sum: [u64; 4], overflow: u64) -> Vec<Fp> {
+    sum.iter().chain([overflow].iter()).map(|&v| f(v)).collect()
+}
+
+#[test]
+fn add_u256_matches_native_sums() {
+    let mut rng = StdRng::seed_from_u64(47);
+    let mut cases: Vec<([u64; 4], [u64; 4])> = (0..4).map(|_| (rng.gen(), rng.gen())).collect();
+    // a carry running through every limb into the overflow bit
+    cases.push(([u64::MAX; 4], [1, 0, 0, 0]));
+    cases.push(([0; 4], [0; 4]));
+    for (a, b) in cases {
+        let (sum, carries) = add_u256_native(a, b);
+        let w: Vec<u64> = a.iter().chain(b.iter()).copied().collect();
+        let circuit = AddU256Circuit::new(&w, 0);
+        let publics = u256_publics(sum, carries[3]);
+        assert_eq!(mock(K, &circuit, publics), Ok(()), "{:?} + {:?}", a, b);
+        let wrong = u256_publics(sum, 1 - carries[3]);
+        assert!(mock(K, &circuit, wrong).is_err());
+    }
+}
+
+#[test]
+fn add_u256_rejects_a_forged_carry() {
+    let a = [u64::MAX, 5, 0, 0];
+    let b = [1, 6, 0, 0];
+    let (sum, carries) = add_u256_native(a, b);
+    let witness = |sum: [u64; 4], carries: [u64; 4]| -> Vec<u64> {
+        [a, b, sum, carries].iter().flatten().copied().collect()
+    };
+    let honest = ForgedAddU256Circuit::new(&witness(sum, carries), 0);
+    assert_eq!(mock(K, &honest, vec![]), Ok(()));
+    // the carry out of limb 0 dropped
+    let forged = ForgedAddU256Circuit::new(&witness([0, 12, 0, 0], [0, 0, 0, 0]), 0);
+    assert!(mock(K, &forged, vec![]).is_err());
+    // a carry out of limb 1 that never happened
+    let forged = ForgedAddU256Circuit::new(&witness(sum, [1, 1, 0, 0]), 0);
+    assert!(mock(K, &forged, vec![]).is_err());
+}
+
+// mul_fixed_point of w[0] and w[1] with n fractional bits, the product published
+gadget_circuit!(FixedPointCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let b = chip.load_private(layouter.namespace(|| "b"), w[1])?;
+    let out = chip.mul_fixed_point(layouter.namespace(|| "a * b"), a, b, n)?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+// v as a fixed point number with 16 fractional bits
+fn fixed(v: f64) -> u64 {
+    (v * 65536.0) as u64
+}
+
+#[test]
+fn mul_fixed_point_at_scale_16() {
+    let circuit = FixedPointCircuit::new(&[fixed(1.5), fixed(2.25)], 16);
+    assert_eq!(fixed(3.375), 221184);
+    assert_eq!(mock(K, &circuit, vec![f(fixed(3.375))]), Ok(()));
+    assert!(mock(K, &circuit, vec![f(fixed(3.375) + 1)]).is_err());
+    assert!(common::prove_and_verify(K, &circuit, &[f(fixed(3.375))]).is_ok());
+}
+
+#[test]
+fn mul_fixed_point_truncates() {
+    // the smallest step squared is 2^-32, which truncates to 0
+    let circuit = FixedPointCircuit::new(&[1, 1], 16);
+    assert_eq!(mock(K, &circuit, vec![f(0)]), Ok(()));
+    // 1/3 * 1/3 is 0.1111..., truncated not rounded to the nearest step
+    let third = 65536 / 3;
+    let circuit = FixedPointCircuit::new(&[third, third], 16);
+    assert_eq!(mock(K, &circuit, vec![f((third * third) >> 16)]), Ok(()));
+    // scale 0 is plain multiplication
+    let circuit = FixedPointCircuit::new(&[6, 7], 0);
+    assert_eq!(mock(K, &circuit, vec![f(42)]), Ok(()));
+}
+
+#[test]
+fn mul_fixed_point_rejects_overflow() {
+    // a wider than 64 bits could make the product wrap the field
+    let circuit = FixedPointCircuit::with_fields(&[Fp::from_u128(1 << 64), f(2)], 16);
+    assert!(mock(K, &circuit, vec![f(1 << 49)]).is_err());
+    // the largest inputs still fit, their product is below 2^128
+    let circuit = FixedPointCircuit::new(&[u64::MAX, u64::MAX], 64);
+    assert_eq!(mock(K, &circuit, vec![f(u64::MAX - 1)]), Ok(()));
+    let circuit = FixedPointCircuit::new(&[1, 1], 65);
+    assert!(matches!(
+        synthesis_error(K, &circuit, vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+// w[0] mod n with q range checked to 64 bits, the remainder published
+gadget_circuit!(ModConstCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let r = chip.mod_const(layouter.namespace(|| "a mod m"), a, n as u64, 64)?;
+    chip.expose_public(layouter.namespace(|| "r"), r, 0)
+});
+
+// mod_const laid out with q and r picked by hand, w = [a, q, r] and m = n
+gadget_circuit!(ForgedModConstCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let cells = chip.load_private_many(layouter.namespace(|| "a, q, r"), w)?;
+    let (a, q, r) = (cells[0].clone(), cells[1].clone(), cells[2].clone());
+    let m = Fp::from(n as u64);
+    chip.range_check(layouter.namespace(|| "q"), q.clone(), 64)?;
+    chip.range_check(layouter.namespace(|| "r < 2^64"), r.clone(), 64)?;
+    let qm = chip.mul_by_constant(layouter.namespace(|| "q * m"), q, m)?;
+    let qm_r = chip.add(layouter.namespace(|| "q * m + r"), qm, r.clone())?;
+    chip.assert_equal(layouter.namespace(|| "q * m + r == a"), qm_r, a)?;
+    let m = chip.load_constant(layouter.namespace(|| "m"), m)?;
+    let r_lt_m = chip.lt(layouter.namespace(|| "r < m"), r, m, 64)?;
+    chip.assert_equals_constant(layouter.namespace(|| "r < m holds"), r_lt_m, Fp::one())
+});
+
+#[test]
+fn mod_const_by_two_and_ten() {
+    for (a, m) in [(0, 2), (7, 2), (8, 2), (12347, 10), (u64::MAX, 10)] {
+        let circuit = ModConstCircuit::new(&[a], m);
+        let r = a % m as u64;
+        assert_eq!(mock(K, &circuit, vec![f(r)]), Ok(()), "{} mod {}", a, m);
+        assert!(
+            mock(K, &circuit, vec![f(r + 1)]).is_err(),
+            "{} mod {}",
+            a,
+            m
+        );
+    }
+}
+
+#[test]
+fn mod_const_remainder_boundaries() {
+    // r = 0 and r = m - 1
+    assert_eq!(
+        mock(K, &ModConstCircuit::new(&[40], 10), vec![f(0)]),
+        Ok(())
+    );
+    assert_eq!(
+        mock(K, &ModConstCircuit::new(&[49], 10), vec![f(9)]),
+        Ok(())
+    );
+    assert_eq!(mock(K, &ModConstCircuit::new(&[9], 10), vec![f(9)]), Ok(()));
+    assert!(matches!(
+        synthesis_error(K, &ModConstCircuit::new(&[9], 0), vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn mod_const_rejects_a_forged_remainder() {
+    assert_eq!(
+        mock(K, &ForgedModConstCircuit::new(&[47, 4, 7], 10), vec![]),
+        Ok(())
+    );
+    // 47 = 3 * 10 + 17 and 47 = 4 * 10 + 7 both hold, only the second has r < m
+    assert!(mock(K, &ForgedModConstCircuit::new(&[47, 3, 17], 10), vec![]).is_err());
+    // r = m, 50 = 4 * 10 + 10
+    assert!(mock(K, &ForgedModConstCircuit::new(&[50, 4, 10], 10), vec![]).is_err());
+    // a remainder wrapped below zero, 47 = 5 * 10 - 3
+    let forged = ForgedModConstCircuit::with_fields(&[f(47), f(5), -f(3)], 10);
+    assert!(mock(K, &forged, vec![]).is_err());
+}
+
+// to_signed of (w[0], w[1]) and of (w[2], w[3]), whether the first is below the second published
+gadget_circuit!(SignedLtCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let cells = chip.load_private_many(layouter.namespace(|| "magnitudes, signs"), w)?;
+    let a = chip.to_signed(
+        layouter.namespace(|| "a"),
+        cells[0].clone(),
+        cells[1].clone(),
+    )?;
+    let b = chip.to_signed(
+        layouter.namespace(|| "b"),
+        cells[2].clone(),
+        cells[3].clone(),
+    )?;
+    let lt = chip.signed_lt(layouter.namespace(|| "a < b"), a, b)?;
+    chip.expose_public(layouter.namespace(|| "a < b"), lt, 0)
+});
+
+fn signed(a: i64, b: i64) -> SignedLtCircuit {
+    let parts = |v: i64| [v.unsigned_abs(), (v < 0) as u64];
+    let w: Vec<u64> = parts(a).iter().chain(parts(b).iter()).copied().collect();
+    SignedLtCircuit::new(&w, 0)
+}
+
+#[test]
+fn signed_lt() {
+    for (a, b) in [
+        (-5, 3),
+        (-5, -2),
+        (3, 5),
+        (0, 1),
+        (-1, 0),
+        (i64::MIN, i64::MAX),
+    ] {
+        assert_eq!(mock(K, &signed(a, b), vec![f(1)]), Ok(()), "{} < {}", a, b);
+        assert_eq!(mock(K, &signed(b, a), vec![f(0)]), Ok(()), "{} < {}", b, a);
+        assert!(mock(K, &signed(a, b), vec![f(0)]).is_err());
+    }
+}
+
+#[test]
+fn signed_lt_of_equal_values() {
+    for v in [0, 3, -5] {
+        assert_eq!(mock(K, &signed(v, v), vec![f(0)]), Ok(()), "{}", v);
+        assert!(mock(K, &signed(v, v), vec![f(1)]).is_err(), "{}", v);
+    }
+    // -0 is the same field element as 0
+    let circuit = SignedLtCircuit::new(&[0, 1, 0, 0], 0);
+    assert_eq!(mock(K, &circuit, vec![f(0)]), Ok(()));
+}
+
+#[test]
+fn to_signed_enforces_the_magnitude_bound() {
+    // a magnitude of 2^64 would let a negative value pass for a positive one
+    let too_big = SignedLtCircuit::with_fields(&[Fp::from_u128(1 << 64), f(1), f(0), f(0)], 0);
+    assert!(mock(K, &too_big, vec![f(1)]).is_err());
+    // and the sign has to be a bit
+    assert!(mock(K, &SignedLtCircuit::new(&[5, 2, 3, 0], 0), vec![f(1)]).is_err());
+}
+
+#[test]
+fn signed_lt_proves_and_verifies() {
+    assert!(common::prove_and_verify(K, &signed(-5, 3), &[f(1)]).is_ok());
+    assert!(common::prove_and_verify(K, &signed(-5, 3), &[f(0)]).is_err());
+}
+
+// isqrt of w[0] as an n bit value, the root published
+gadget_circuit!(IsqrtCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let v = chip.load_private(layouter.namespace(|| "n"), w[0])?;
+    let s = chip.isqrt(layouter.namespace(|| "isqrt"), v, n)?;
+    chip.expose_public(layouter.namespace(|| "s"), s, 0)
+});
+
+// isqrt laid out with the root picked by hand, w = [n, s] and n bits wide
+gadget_circuit!(ForgedIsqrtCircuit, |chip, layouter, w, n| {
+    load_table(&chip, layouter.namespace(|| "byte table"))?;
+    let cells = chip.load_private_many(layouter.namespace(|| "n, s"), w)?;
+    let (v, s) = (cells[0].clone(), cells[1].clone());
+    chip.range_check(layouter.namespace(|| "n"), v.clone(), n as u8)?;
+    chip.range_check(layouter.namespace(|| "s"), s.clone(), n.div_ceil(2) as u8)?;
+    let s_sq = chip.square(layouter.namespace(|| "s * s"), s.clone())?;
+    let s_next = chip.add_constant(layouter.namespace(|| "s + 1"), s, Fp::one())?;
+    let s_next_sq = chip.square(layouter.namespace(|| "(s + 1)^2"), s_next)?;
+    let too_big = chip.lt(layouter.namespace(|| "n < s * s"), v.clone(), s_sq, n + 2)?;
+    let below = chip.lt(layouter.namespace(|| "n < (s + 1)^2"), v, s_next_sq, n + 2)?;
+    chip.assert_equals_constant(layouter.namespace(|| "s * s <= n"), too_big, Fp::zero())?;
+    chip.assert_equals_constant(layouter.namespace(|| "n < (s + 1)^2"), below, Fp::one())
+});
+
+#[test]
+fn isqrt_of_perfect_squares() {
+    for s in [0, 1, 7, 255, 65535] {
+        let circuit = IsqrtCircuit::new(&[s * s], 32);
+        assert_eq!(mock(K, &circuit, vec![f(s)]), Ok(()), "{}^2", s);
+    }
+    let s = u32::MAX as u64;
+    assert_eq!(
+        mock(K, &IsqrtCircuit::new(&[s * s], 64), vec![f(s)]),
+        Ok(())
+    );
+}
+
+#[test]
+fn isqrt_around_a_square() {
+    // 48 is just below 7^2, 50 just above it
+    for (v, s) in [(48, 6), (49, 7), (50, 7), (63, 7), (64, 8)] {
+        let circuit = IsqrtCircuit::new(&[v], 8);
+        assert_eq!(mock(K, &circuit, vec![f(s)]), Ok(()), "isqrt({})", v);
+        assert!(mock(K, &circuit, vec![f(s + 1)]).is_err(), "isqrt({})", v);
+    }
+    assert!(matches!(
+        synthesis_error(K, &IsqrtCircuit::new(&[4], 65), vec![f(2)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn isqrt_rejects_a_forged_root() {
+    assert_eq!(
+        mock(K, &ForgedIsqrtCircuit::new(&[50, 7], 8), vec![]),
+        Ok(())
+    );
+    // 8 * 8 > 50 breaks s * s <= n
+    assert!(mock(K, &ForgedIsqrtCircuit::new(&[50, 8], 8), vec![]).is_err());
+    // 50 >= 7 * 7 breaks n < (s + 1)^2 for s = 6
+    assert!(mock(K, &ForgedIsqrtCircuit::new(&[50, 6], 8), vec![]).is_err());
+    // on a square the root one below is off by exactly one
+    assert!(mock(K, &ForgedIsqrtCircuit::new(&[49, 6], 8), vec![]).is_err());
+}
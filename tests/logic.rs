@@ -0,0 +1,458 @@
+// the boolean and comparison gadgets of MyChip, including witnesses a dishonest prover could pick
+#[macro_use]
+mod common;
+
+use common::{f, fails_gate, fails_in_region, mock, prove_and_verify, synthesis_error};
+use halo2_proofs::{arithmetic::Field, circuit::Chip, dev::VerifyFailure, plonk::Error};
+
+// cond ? a : b for w = [cond, a, b]
+gadget_circuit!(SelectCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "cond, a, b"), w)?;
+    let out = chip.select(
+        layouter.namespace(|| "select"),
+        v[0].clone(),
+        v[1].clone(),
+        v[2].clone(),
+    )?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+#[test]
+fn select() {
+    assert_eq!(
+        mock(4, &SelectCircuit::new(&[1, 10, 20], 0), vec![f(10)]),
+        Ok(())
+    );
+    assert_eq!(
+        mock(4, &SelectCircuit::new(&[0, 10, 20], 0), vec![f(20)]),
+        Ok(())
+    );
+    assert!(mock(4, &SelectCircuit::new(&[1, 10, 20], 0), vec![f(20)]).is_err());
+    assert!(mock(4, &SelectCircuit::new(&[0, 10, 20], 0), vec![f(10)]).is_err());
+}
+
+#[test]
+fn select_rejects_a_non_boolean_condition() {
+    // 2 ? 10 : 20 follows the selection identity to 0, only booleanity rules it out
+    let circuit = SelectCircuit::new(&[2, 10, 20], 0);
+    assert!(fails_gate(mock(4, &circuit, vec![f(0)]), "select"));
+}
+
+gadget_circuit!(IsZeroCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let out = chip.is_zero(layouter.namespace(|| "a == 0"), a)?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+// a == 0 ? 100 : a, the is zero output driving select
+gadget_circuit!(DefaultIfZeroCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let fallback = chip.load_constant(layouter.namespace(|| "100"), f(100))?;
+    let zero = chip.is_zero(layouter.namespace(|| "a == 0"), a.clone())?;
+    let out = chip.select(layouter.namespace(|| "select"), zero, fallback, a)?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+// the is zero region with its witness picked by hand, w = [a, a_inv, out]
+gadget_circuit!(ForgedIsZeroCircuit, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    let out = layouter.assign_region(
+        || "is zero",
+        |mut region| {
+            config.s_is_zero.enable(&mut region, 0)?;
+            region.assign_advice(|| "a", config.advice[0], 0, || w[0].ok_or(Error::Synthesis))?;
+            region.assign_advice(
+                || "1 / a",
+                config.advice[1],
+                0,
+                || w[1].ok_or(Error::Synthesis),
+            )?;
+            region.assign_advice(
+                || "a == 0",
+                config.advice[0],
+                1,
+                || w[2].ok_or(Error::Synthesis),
+            )
+        },
+    )?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+#[test]
+fn is_zero() {
+    assert_eq!(mock(4, &IsZeroCircuit::new(&[0], 0), vec![f(1)]), Ok(()));
+    assert_eq!(mock(4, &IsZeroCircuit::new(&[7], 0), vec![f(0)]), Ok(()));
+    assert!(mock(4, &IsZeroCircuit::new(&[0], 0), vec![f(0)]).is_err());
+    assert!(mock(4, &IsZeroCircuit::new(&[7], 0), vec![f(1)]).is_err());
+}
+
+#[test]
+fn is_zero_composes_with_select() {
+    assert_eq!(
+        mock(4, &DefaultIfZeroCircuit::new(&[0], 0), vec![f(100)]),
+        Ok(())
+    );
+    assert_eq!(
+        mock(4, &DefaultIfZeroCircuit::new(&[7], 0), vec![f(7)]),
+        Ok(())
+    );
+    assert!(mock(4, &DefaultIfZeroCircuit::new(&[7], 0), vec![f(100)]).is_err());
+}
+
+#[test]
+fn is_zero_rejects_a_malicious_witness() {
+    let inv7 = f(7).invert().unwrap();
+    let honest = ForgedIsZeroCircuit::with_fields(&[f(7), inv7, f(0)], 0);
+    assert_eq!(mock(4, &honest, vec![f(0)]), Ok(()));
+    // claiming 7 is zero, with the conventional 0 or the real inverse as a_inv
+    for a_inv in [f(0), inv7, f(1)] {
+        let forged = ForgedIsZeroCircuit::with_fields(&[f(7), a_inv, f(1)], 0);
+        assert!(fails_gate(mock(4, &forged, vec![f(1)]), "is zero"));
+    }
+    // and claiming 0 is not, whatever inverse is offered
+    for a_inv in [f(0), f(1), inv7] {
+        let forged = ForgedIsZeroCircuit::with_fields(&[f(0), a_inv, f(0)], 0);
+        assert!(fails_gate(mock(4, &forged, vec![f(0)]), "is zero"));
+    }
+}
+
+gadget_circuit!(IsEqualCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let b = chip.load_private(layouter.namespace(|| "b"), w[1])?;
+    let out = chip.is_equal(layouter.namespace(|| "a == b"), a, b)?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+// the secret x is one of the public [p, q]: hit = (x == p) + (x == q) is public and has to be
+// 1, and chosen = (x == p) ? p : q is published too
+gadget_circuit!(OneOfCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let p = chip.load_public(layouter.namespace(|| "p"), 0)?;
+    let q = chip.load_public(layouter.namespace(|| "q"), 1)?;
+    let is_p = chip.is_equal(layouter.namespace(|| "x == p"), x.clone(), p.clone())?;
+    let is_q = chip.is_equal(layouter.namespace(|| "x == q"), x, q.clone())?;
+    let hit = chip.add(layouter.namespace(|| "hit"), is_p.clone(), is_q)?;
+    chip.expose_public(layouter.namespace(|| "hit"), hit, 2)?;
+    let chosen = chip.select(layouter.namespace(|| "chosen"), is_p, p, q)?;
+    chip.expose_public(layouter.namespace(|| "chosen"), chosen, 3)
+});
+
+#[test]
+fn is_equal() {
+    assert_eq!(
+        mock(4, &IsEqualCircuit::new(&[9, 9], 0), vec![f(1)]),
+        Ok(())
+    );
+    assert_eq!(
+        mock(4, &IsEqualCircuit::new(&[9, 8], 0), vec![f(0)]),
+        Ok(())
+    );
+    assert!(mock(4, &IsEqualCircuit::new(&[9, 9], 0), vec![f(0)]).is_err());
+    assert!(mock(4, &IsEqualCircuit::new(&[9, 8], 0), vec![f(1)]).is_err());
+}
+
+#[test]
+fn is_equal_composes_with_add_and_select() {
+    let circuit = OneOfCircuit::new(&[20], 0);
+    assert_eq!(mock(5, &circuit, vec![f(10), f(20), f(1), f(20)]), Ok(()));
+    assert!(prove_and_verify(5, &circuit, &[f(10), f(20), f(1), f(20)]).is_ok());
+    assert!(prove_and_verify(5, &circuit, &[f(10), f(20), f(1), f(10)]).is_err());
+    // a secret in neither slot can only claim a hit of 0
+    let outside = OneOfCircuit::new(&[30], 0);
+    assert!(mock(5, &outside, vec![f(10), f(20), f(1), f(20)]).is_err());
+    assert_eq!(mock(5, &outside, vec![f(10), f(20), f(0), f(20)]), Ok(()));
+}
+
+// x * x computed by square and by mul of x and y, then pinned together
+gadget_circuit!(AssertEqualCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let y = chip.load_private(layouter.namespace(|| "y"), w[1])?;
+    let squared = chip.square(layouter.namespace(|| "x * x"), x.clone())?;
+    let product = chip.mul(layouter.namespace(|| "x * y"), x, y)?;
+    chip.assert_equal(layouter.namespace(|| "same"), squared, product)
+});
+
+#[test]
+fn assert_equal() {
+    assert_eq!(
+        mock(4, &AssertEqualCircuit::new(&[6, 6], 0), vec![]),
+        Ok(())
+    );
+    let failures = mock(4, &AssertEqualCircuit::new(&[6, 7], 0), vec![]).unwrap_err();
+    assert!(!failures.is_empty());
+    assert!(failures
+        .iter()
+        .all(|e| matches!(e, VerifyFailure::Permutation { .. })));
+}
+
+gadget_circuit!(NonzeroCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    chip.assert_nonzero(layouter.namespace(|| "a != 0"), a)
+});
+
+// the assert nonzero region with a chosen inverse, w = [a, a_inv]
+gadget_circuit!(ForgedNonzeroCircuit, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    layouter.assign_region(
+        || "assert nonzero",
+        |mut region| {
+            config.s_nonzero.enable(&mut region, 0)?;
+            region.assign_advice(|| "a", config.advice[0], 0, || w[0].ok_or(Error::Synthesis))?;
+            region.assign_advice(
+                || "1 / a",
+                config.advice[1],
+                0,
+                || w[1].ok_or(Error::Synthesis),
+            )?;
+            Ok(())
+        },
+    )
+});
+
+#[test]
+fn assert_nonzero() {
+    assert_eq!(mock(4, &NonzeroCircuit::new(&[7], 0), vec![]), Ok(()));
+    // zero is refused while synthesizing instead of leaving an unsatisfiable witness
+    assert!(matches!(
+        synthesis_error(4, &NonzeroCircuit::new(&[0], 0), vec![]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn assert_nonzero_rejects_a_forced_zero() {
+    let inv7 = f(7).invert().unwrap();
+    assert_eq!(
+        mock(
+            4,
+            &ForgedNonzeroCircuit::with_fields(&[f(7), inv7], 0),
+            vec![]
+        ),
+        Ok(())
+    );
+    for a_inv in [f(0), f(1), inv7] {
+        let forged = ForgedNonzeroCircuit::with_fields(&[f(0), a_inv], 0);
+        assert!(fails_gate(mock(4, &forged, vec![]), "nonzero"));
+    }
+    // a wrong inverse for a nonzero value fails as well
+    let forged = ForgedNonzeroCircuit::with_fields(&[f(7), f(7)], 0);
+    assert!(fails_gate(mock(4, &forged, vec![]), "nonzero"));
+}
+
+// cond_swap on w = [cond, a, b], both outputs copied on through an add so they are used downstream
+gadget_circuit!(CondSwapCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "cond, a, b"), w)?;
+    let (left, right) = chip.cond_swap(
+        layouter.namespace(|| "swap"),
+        v[0].clone(),
+        v[1].clone(),
+        v[2].clone(),
+    )?;
+    let left = chip.add_constant(layouter.namespace(|| "left + 0"), left, f(0))?;
+    let right = chip.add_constant(layouter.namespace(|| "right + 0"), right, f(0))?;
+    chip.expose_public_many(layouter.namespace(|| "out"), &[left, right], 0)?;
+    Ok(())
+});
+
+#[test]
+fn cond_swap() {
+    let keep = CondSwapCircuit::new(&[0, 10, 20], 0);
+    assert_eq!(mock(4, &keep, vec![f(10), f(20)]), Ok(()));
+    assert!(mock(4, &keep, vec![f(20), f(10)]).is_err());
+    let swap = CondSwapCircuit::new(&[1, 10, 20], 0);
+    assert_eq!(mock(4, &swap, vec![f(20), f(10)]), Ok(()));
+    assert!(mock(4, &swap, vec![f(10), f(20)]).is_err());
+}
+
+#[test]
+fn cond_swap_rejects_a_non_boolean_condition() {
+    let circuit = CondSwapCircuit::new(&[2, 10, 20], 0);
+    assert!(fails_gate(
+        mock(4, &circuit, vec![f(20), f(10)]),
+        "cond swap"
+    ));
+}
+
+// n picks the op: 0 is a & b, 1 is a | b, 2 is !a
+gadget_circuit!(BoolOpCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "a, b"), w)?;
+    let out = match n {
+        0 => chip.and(layouter.namespace(|| "and"), v[0].clone(), v[1].clone())?,
+        1 => chip.or(layouter.namespace(|| "or"), v[0].clone(), v[1].clone())?,
+        _ => chip.not(layouter.namespace(|| "not"), v[0].clone())?,
+    };
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+// (x == y & z == 0) | !b, a single public boolean
+gadget_circuit!(PredicateCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "x, y, z, b"), w)?;
+    let eq = chip.is_equal(layouter.namespace(|| "x == y"), v[0].clone(), v[1].clone())?;
+    let zero = chip.is_zero(layouter.namespace(|| "z == 0"), v[2].clone())?;
+    let both = chip.and(layouter.namespace(|| "and"), eq, zero)?;
+    let not_b = chip.not(layouter.namespace(|| "!b"), v[3].clone())?;
+    let out = chip.or(layouter.namespace(|| "or"), both, not_b)?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+#[test]
+fn bool_truth_tables() {
+    for a in 0..2u64 {
+        assert_eq!(
+            mock(4, &BoolOpCircuit::new(&[a], 2), vec![f(1 - a)]),
+            Ok(())
+        );
+        assert!(mock(4, &BoolOpCircuit::new(&[a], 2), vec![f(a)]).is_err());
+        for b in 0..2u64 {
+            let (and, or) = (a & b, a | b);
+            assert_eq!(
+                mock(4, &BoolOpCircuit::new(&[a, b], 0), vec![f(and)]),
+                Ok(())
+            );
+            assert!(mock(4, &BoolOpCircuit::new(&[a, b], 0), vec![f(1 - and)]).is_err());
+            assert_eq!(
+                mock(4, &BoolOpCircuit::new(&[a, b], 1), vec![f(or)]),
+                Ok(())
+            );
+            assert!(mock(4, &BoolOpCircuit::new(&[a, b], 1), vec![f(1 - or)]).is_err());
+        }
+    }
+}
+
+#[test]
+fn bool_ops_reject_non_boolean_inputs() {
+    // 2 & 1 = 2 and 2 | 0 = 2 follow the formulas, booleanity of the inputs is what fails
+    assert!(fails_gate(
+        mock(4, &BoolOpCircuit::new(&[2, 1], 0), vec![f(2)]),
+        "and"
+    ));
+    assert!(fails_gate(
+        mock(4, &BoolOpCircuit::new(&[2, 0], 1), vec![f(2)]),
+        "or"
+    ));
+    let minus_one = -f(1);
+    assert!(fails_gate(
+        mock(4, &BoolOpCircuit::new(&[2], 2), vec![minus_one]),
+        "not"
+    ));
+}
+
+#[test]
+fn bool_predicate_proves_and_verifies() {
+    let circuit = PredicateCircuit::new(&[5, 5, 0, 1], 0);
+    assert!(prove_and_verify(5, &circuit, &[f(1)]).is_ok());
+    assert!(prove_and_verify(5, &circuit, &[f(0)]).is_err());
+    let circuit = PredicateCircuit::new(&[5, 6, 0, 1], 0);
+    assert_eq!(mock(5, &circuit, vec![f(0)]), Ok(()));
+    let circuit = PredicateCircuit::new(&[5, 6, 0, 0], 0);
+    assert_eq!(mock(5, &circuit, vec![f(1)]), Ok(()));
+}
+
+// w[1] + w[0] * w[2], b added only when cond is set
+gadget_circuit!(CondAddCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "cond, a, b"), w)?;
+    let out = chip.cond_add(
+        layouter.namespace(|| "cond add"),
+        v[0].clone(),
+        v[1].clone(),
+        v[2].clone(),
+    )?;
+    chip.expose_public(layouter.namespace(|| "out"), out, 0)
+});
+
+#[test]
+fn cond_add() {
+    for cond in 0..2u64 {
+        for b in [0, 5] {
+            let circuit = CondAddCircuit::new(&[cond, 10, b], 0);
+            let out = 10 + cond * b;
+            assert_eq!(
+                mock(4, &circuit, vec![f(out)]),
+                Ok(()),
+                "cond {} b {}",
+                cond,
+                b
+            );
+            assert!(mock(4, &circuit, vec![f(out + 1)]).is_err());
+        }
+    }
+    assert!(prove_and_verify(4, &CondAddCircuit::new(&[1, 10, 5], 0), &[f(15)]).is_ok());
+}
+
+#[test]
+fn cond_add_rejects_a_non_boolean_condition() {
+    // 10 + 2 * 5 holds on the sum, booleanity of cond does not
+    let circuit = CondAddCircuit::new(&[2, 10, 5], 0);
+    assert!(fails_gate(mock(4, &circuit, vec![f(20)]), "cond add"));
+}
+
+// asserts w[0] is a bit, n = 1 goes through into_bit and publishes the checked cell
+gadget_circuit!(BooleanCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    if n == 1 {
+        let bit = chip.into_bit(layouter.namespace(|| "bit"), a)?;
+        chip.expose_public(layouter.namespace(|| "bit"), bit.cell().clone(), 0)
+    } else {
+        chip.assert_boolean(layouter.namespace(|| "bit"), a)
+    }
+});
+
+#[test]
+fn assert_boolean() {
+    assert_eq!(mock(4, &BooleanCircuit::new(&[0], 0), vec![]), Ok(()));
+    assert_eq!(mock(4, &BooleanCircuit::new(&[1], 0), vec![]), Ok(()));
+    let two = BooleanCircuit::new(&[2], 0);
+    assert!(fails_gate(mock(4, &two, vec![]), "boolean"));
+    assert!(fails_in_region(mock(4, &two, vec![]), "assert boolean"));
+    // one row and the copy of a into it
+    assert_eq!(common::copies(&BooleanCircuit::new(&[1], 0), &[]), 1);
+}
+
+#[test]
+fn into_bit() {
+    assert_eq!(mock(4, &BooleanCircuit::new(&[1], 1), vec![f(1)]), Ok(()));
+    assert!(mock(4, &BooleanCircuit::new(&[1], 1), vec![f(0)]).is_err());
+    assert!(fails_gate(
+        mock(4, &BooleanCircuit::new(&[2], 1), vec![f(2)]),
+        "boolean"
+    ));
+}
+
+// w[0] pinned to the constant n
+gadget_circuit!(EqualsConstantCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    chip.assert_equals_constant(layouter.namespace(|| "a == k"), a, f(n as u64))
+});
+
+// the same check through load_constant and assert_equal
+gadget_circuit!(LoadedConstantCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let k = chip.load_constant(layouter.namespace(|| "k"), f(n as u64))?;
+    chip.assert_equal(layouter.namespace(|| "a == k"), a, k)
+});
+
+#[test]
+fn assert_equals_constant() {
+    for k in [0, 1, 42] {
+        let circuit = EqualsConstantCircuit::new(&[k], k as usize);
+        assert_eq!(mock(4, &circuit, vec![]), Ok(()), "k = {}", k);
+        let circuit = EqualsConstantCircuit::new(&[k + 1], k as usize);
+        assert!(
+            fails_gate(mock(4, &circuit, vec![]), "equals constant"),
+            "k = {}",
+            k
+        );
+    }
+    // a field element that is not a small integer
+    let circuit = EqualsConstantCircuit::with_fields(&[-f(1)], 0);
+    assert!(fails_gate(mock(4, &circuit, vec![]), "equals constant"));
+    assert!(prove_and_verify(4, &EqualsConstantCircuit::new(&[7], 7), &[]).is_ok());
+}
+
+#[test]
+fn assert_equals_constant_is_cheaper_than_loading_the_constant() {
+    let pinned = EqualsConstantCircuit::new(&[7], 7);
+    let loaded = LoadedConstantCircuit::new(&[7], 7);
+    assert!(common::copies(&pinned, &[]) < common::copies(&loaded, &[]));
+    assert!(common::regions(&pinned, &[]) <= common::regions(&loaded, &[]));
+}
@@ -0,0 +1,632 @@
+// gadgets that look their witness up in a fixed table instead of constraining it with a gate
+#[macro_use]
+mod common;
+
+use common::{f, mock, synthesis_error};
+use halo2::{
+    chip::{
+        MyChip, MyConfig, Ops, RangeChip, RangeConfig, SboxChip, SboxConfig, XorChip, XorConfig,
+    },
+    circuit::{MyCircuit, XorCircuit},
+};
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{Chip, Layouter, SimpleFloorPlanner},
+    dev::{MockProver, VerifyFailure},
+    pasta::{group::ff::PrimeField, Fp},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+// XorCircuit with the output cell witnessed directly instead of computed by xor_u8
+#[derive(Default)]
+struct ForgedXorCircuit {
+    a: u64,
+    b: u64,
+    out: u64,
+}
+
+impl Circuit<Fp> for ForgedXorCircuit {
+    type Config = (MyConfig, XorConfig);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        XorCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let xor = XorChip::new(config.1);
+        xor.load(layouter.namespace(|| "xor table"))?;
+        let config = xor.config().clone();
+        let out = layouter.assign_region(
+            || "xor",
+            |mut region| {
+                config.s_xor.enable(&mut region, 0)?;
+                region.assign_advice(|| "a", config.advice[0], 0, || Ok(f(self.a)))?;
+                region.assign_advice(|| "b", config.advice[1], 0, || Ok(f(self.b)))?;
+                region.assign_advice(|| "a ^ b", config.advice[2], 0, || Ok(f(self.out)))
+            },
+        )?;
+        chip.expose_public(layouter.namespace(|| "out"), out, 0)
+    }
+}
+
+fn xor(a: u64, b: u64) -> XorCircuit {
+    XorCircuit {
+        a: Some(f(a)),
+        b: Some(f(b)),
+    }
+}
+
+// xor_u8 over every pair against one table, each output on its own instance row. a mock run at
+// k = 17 is slow, so the pairs share a circuit
+#[derive(Default)]
+struct XorPairsCircuit {
+    pairs: Vec<(u64, u64)>,
+}
+
+impl Circuit<Fp> for XorPairsCircuit {
+    type Config = (MyConfig, XorConfig);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        XorCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let xor = XorChip::new(config.1);
+        xor.load(layouter.namespace(|| "xor table"))?;
+        for (i, (a, b)) in self.pairs.iter().enumerate() {
+            let a = chip.load_private(layouter.namespace(|| "a"), Some(f(*a)))?;
+            let b = chip.load_private(layouter.namespace(|| "b"), Some(f(*b)))?;
+            let out = xor.xor_u8(layouter.namespace(|| "a ^ b"), a, b)?;
+            chip.expose_public(layouter.namespace(|| "out"), out, i)?;
+        }
+        Ok(())
+    }
+}
+
+fn is_lookup_failure(result: Result<(), Vec<VerifyFailure>>) -> bool {
+    match result {
+        Ok(()) => false,
+        Err(failures) => failures
+            .iter()
+            .any(|e| matches!(e, VerifyFailure::Lookup { .. })),
+    }
+}
+
+#[test]
+fn xor_u8() {
+    let pairs = vec![
+        (0, 0),
+        (0xff, 0),
+        (0xff, 0xff),
+        (0b1010, 0b0110),
+        (200, 55),
+        (1, 128),
+    ];
+    let mut outputs: Vec<Fp> = pairs.iter().map(|(a, b)| f(a ^ b)).collect();
+    let circuit = XorPairsCircuit { pairs };
+    assert_eq!(mock(XorCircuit::k(), &circuit, outputs.clone()), Ok(()));
+    outputs[4] += f(1);
+    assert!(mock(XorCircuit::k(), &circuit, outputs).is_err());
+    assert_eq!(mock(XorCircuit::k(), &xor(3, 5), vec![f(6)]), Ok(()));
+}
+
+#[test]
+fn xor_k_fits_the_table() {
+    // 2^16 table rows and the blinding rows on top push it to 17
+    assert_eq!(XorCircuit::k(), 17);
+    assert!(matches!(
+        synthesis_error(16, &xor(1, 2), vec![f(3)]),
+        Some(Error::NotEnoughRowsAvailable { .. })
+    ));
+}
+
+#[test]
+fn xor_rejects_a_lying_witness() {
+    let k = XorCircuit::k();
+    let lie = ForgedXorCircuit {
+        a: 12,
+        b: 10,
+        out: 7,
+    };
+    assert!(is_lookup_failure(mock(k, &lie, vec![f(7)])));
+    // not bytes, 256 ^ 0 = 256 is not in the table
+    let wide = ForgedXorCircuit {
+        a: 256,
+        b: 0,
+        out: 256,
+    };
+    assert!(is_lookup_failure(mock(k, &wide, vec![f(256)])));
+}
+
+// x fits in n bits and x^3 + x + 5 is public, the range check next to the existing gates
+gadget_circuit!(RangeCubicCircuit, |chip, layouter, w, n| {
+    let table = halo2::chip::ByteTableChip::new(chip.config().byte_table.clone());
+    table.load(layouter.namespace(|| "byte table"))?;
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    chip.range_check(layouter.namespace(|| "x < 2^n"), x.clone(), n as u8)?;
+    let x3 = chip.cube(layouter.namespace(|| "x3"), x.clone())?;
+    let y = chip.add(layouter.namespace(|| "x3 + x"), x3, x)?;
+    let y = chip.add_constant(layouter.namespace(|| "+ 5"), y, f(5))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// every w[i] range checked to n bits against the one table
+gadget_circuit!(RangeManyCircuit, |chip, layouter, w, n| {
+    let table = halo2::chip::ByteTableChip::new(chip.config().byte_table.clone());
+    table.load(layouter.namespace(|| "byte table"))?;
+    let values = chip.load_private_many(layouter.namespace(|| "values"), w)?;
+    for (i, v) in values.into_iter().enumerate() {
+        chip.range_check(layouter.namespace(|| format!("check {}", i)), v, n as u8)?;
+    }
+    Ok(())
+});
+
+// a one chunk range check laid out by hand with the chunk equal to a, w = [a]
+gadget_circuit!(ForgedRangeCircuit, |chip, layouter, w, n| {
+    let table = halo2::chip::ByteTableChip::new(chip.config().byte_table.clone());
+    table.load(layouter.namespace(|| "byte table"))?;
+    let config = chip.config().clone();
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    layouter.assign_region(
+        || "range check",
+        |mut region| {
+            config.s_range.enable(&mut region, 0)?;
+            config.s_range_acc.enable(&mut region, 0)?;
+            region.assign_advice_from_constant(|| "zero", config.advice[0], 0, Fp::zero())?;
+            region.assign_advice(
+                || "chunk",
+                config.advice[1],
+                0,
+                || w[0].ok_or(Error::Synthesis),
+            )?;
+            let acc = region.assign_advice(
+                || "acc",
+                config.advice[0],
+                1,
+                || w[0].ok_or(Error::Synthesis),
+            )?;
+            region.constrain_equal(acc.cell(), a.cell())
+        },
+    )
+});
+
+// the byte table alone is 256 rows
+const RANGE_K: u32 = 9;
+
+#[test]
+fn range_check_composes_with_the_cubic() {
+    let circuit = RangeCubicCircuit::new(&[3], 8);
+    assert_eq!(mock(RANGE_K, &circuit, vec![f(35)]), Ok(()));
+    assert!(mock(RANGE_K, &circuit, vec![f(36)]).is_err());
+    assert!(common::prove_and_verify(RANGE_K, &circuit, &[f(35)]).is_ok());
+    // x = 300 satisfies nothing here, but it is the range check failing
+    let wide = RangeCubicCircuit::new(&[300], 8);
+    let y = f(300 * 300 * 300 + 300 + 5);
+    assert!(mock(RANGE_K, &wide, vec![y]).is_err());
+    assert_eq!(
+        mock(RANGE_K, &RangeCubicCircuit::new(&[300], 9), vec![y]),
+        Ok(())
+    );
+}
+
+#[test]
+fn range_check_widths() {
+    // whole chunks, a narrower top chunk, and a width under a single chunk
+    for (bits, max) in [
+        (8, 255u64),
+        (16, 65535),
+        (12, 4095),
+        (4, 15),
+        (1, 1),
+        (64, u64::MAX),
+    ] {
+        let ok = RangeManyCircuit::new(&[0, max], bits);
+        assert_eq!(mock(RANGE_K, &ok, vec![]), Ok(()), "{} bits", bits);
+        if bits < 64 {
+            let over = RangeManyCircuit::new(&[max + 1], bits);
+            assert!(mock(RANGE_K, &over, vec![]).is_err(), "{} bits", bits);
+        }
+    }
+}
+
+#[test]
+fn range_check_loads_the_table_once() {
+    let one = RangeManyCircuit::new(&[1], 8);
+    let many = RangeManyCircuit::new(&[1, 2, 3, 4, 5, 6, 7, 8], 8);
+    assert_eq!(mock(RANGE_K, &many, vec![]), Ok(()));
+    // eight checks still fit next to a single 256 row table
+    assert_eq!(common::rows(&one, &[]), common::rows(&many, &[]));
+}
+
+#[test]
+fn range_check_lookup_rejects_a_wide_chunk() {
+    assert_eq!(
+        mock(RANGE_K, &ForgedRangeCircuit::new(&[255], 0), vec![]),
+        Ok(())
+    );
+    // the recomposition holds with a chunk of 256, only the table lookup catches it
+    assert!(is_lookup_failure(mock(
+        RANGE_K,
+        &ForgedRangeCircuit::new(&[256], 0),
+        vec![]
+    )));
+}
+
+// two gadgets registering lookups against the one byte table: w[0] is range checked to 8 bits
+// and w[1] split into two 12 bit limbs, which are published. n = 0 skips loading the table
+gadget_circuit!(SharedTableCircuit, |chip, layouter, w, n| {
+    if n != 0 {
+        let table = halo2::chip::ByteTableChip::new(chip.config().byte_table.clone());
+        table.load(layouter.namespace(|| "byte table"))?;
+    }
+    let v = chip.load_private_many(layouter.namespace(|| "values"), w)?;
+    chip.range_check(layouter.namespace(|| "byte"), v[0].clone(), 8)?;
+    let limbs = chip.decompose_limbs(layouter.namespace(|| "limbs"), v[1].clone(), 12, 2)?;
+    chip.expose_public_many(layouter.namespace(|| "limbs"), &limbs, 0)?;
+    Ok(())
+});
+
+#[test]
+fn byte_table_is_shared_by_two_gadgets() {
+    let circuit = SharedTableCircuit::new(&[200, 0xabc_def], 1);
+    assert_eq!(mock(RANGE_K, &circuit, vec![f(0xdef), f(0xabc)]), Ok(()));
+    assert!(mock(RANGE_K, &circuit, vec![f(0xabc), f(0xdef)]).is_err());
+    // a byte that isn't one fails whichever gadget it goes through
+    let circuit = SharedTableCircuit::new(&[256, 0xabc_def], 1);
+    assert!(mock(RANGE_K, &circuit, vec![f(0xdef), f(0xabc)]).is_err());
+    // without the table loaded there is nothing to look the chunks up in
+    let unloaded = SharedTableCircuit::new(&[200, 0xabc_def], 0);
+    assert!(is_lookup_failure(mock(
+        RANGE_K,
+        &unloaded,
+        vec![f(0xdef), f(0xabc)]
+    )));
+}
+
+// w[0] split into n limbs of 64 bits, the limbs published in order
+gadget_circuit!(LimbsCircuit, |chip, layouter, w, n| {
+    let table = halo2::chip::ByteTableChip::new(chip.config().byte_table.clone());
+    table.load(layouter.namespace(|| "byte table"))?;
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let limbs = chip.decompose_limbs(layouter.namespace(|| "limbs"), a, 64, n)?;
+    chip.expose_public_many(layouter.namespace(|| "limbs"), &limbs, 0)?;
+    Ok(())
+});
+
+// decompose_limbs laid out with the limbs picked by hand, w = [a, limb 0, limb 1, ..] with
+// 64 bit limbs
+gadget_circuit!(ForgedLimbsCircuit, |chip, layouter, w, n| {
+    let table = halo2::chip::ByteTableChip::new(chip.config().byte_table.clone());
+    table.load(layouter.namespace(|| "byte table"))?;
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let limbs = chip.load_private_many(layouter.namespace(|| "limbs"), &w[1..])?;
+    let mut terms = vec![];
+    let mut k = Fp::one();
+    for limb in limbs.iter() {
+        chip.range_check(layouter.namespace(|| "limb"), limb.clone(), 64)?;
+        terms.push((k, limb.clone()));
+        k *= Fp::from_u128(1 << 64);
+    }
+    let sum = chip.linear_combination(layouter.namespace(|| "recompose"), &terms)?;
+    chip.assert_equal(layouter.namespace(|| "sum == a"), sum, a)
+});
+
+// the little endian 64 bit limbs of a field element
+fn limbs_of(a: Fp) -> Vec<Fp> {
+    a.to_repr()
+        .chunks(8)
+        .map(|c| f(u64::from_le_bytes(c.try_into().unwrap())))
+        .collect()
+}
+
+#[test]
+fn decompose_limbs_of_a_random_field_element() {
+    let a = Fp::random(StdRng::seed_from_u64(41));
+    let limbs = limbs_of(a);
+    let circuit = LimbsCircuit::with_fields(&[a], 4);
+    assert_eq!(mock(RANGE_K, &circuit, limbs.clone()), Ok(()));
+    let mut swapped = limbs;
+    swapped.swap(0, 3);
+    assert!(mock(RANGE_K, &circuit, swapped).is_err());
+}
+
+#[test]
+fn decompose_limbs_refuses_values_that_do_not_fit() {
+    // 2^64 needs a second limb
+    let circuit = LimbsCircuit::with_fields(&[Fp::from_u128(1 << 64)], 1);
+    assert!(matches!(
+        synthesis_error(RANGE_K, &circuit, vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+    // five 64 bit limbs are more than the field holds
+    let circuit = LimbsCircuit::new(&[1], 5);
+    assert!(matches!(
+        synthesis_error(RANGE_K, &circuit, vec![f(1), f(0), f(0), f(0), f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn decompose_limbs_rejects_a_broken_recomposition() {
+    let a = Fp::random(StdRng::seed_from_u64(41));
+    let mut witness = vec![a];
+    witness.extend(limbs_of(a));
+    assert_eq!(
+        mock(
+            RANGE_K,
+            &ForgedLimbsCircuit::with_fields(&witness, 0),
+            vec![]
+        ),
+        Ok(())
+    );
+    witness[2] += f(1);
+    let failures = mock(
+        RANGE_K,
+        &ForgedLimbsCircuit::with_fields(&witness, 0),
+        vec![],
+    )
+    .unwrap_err();
+    assert!(failures
+        .iter()
+        .all(|e| matches!(e, VerifyFailure::Permutation { .. })));
+}
+
+// range_check_wide of w[0] to n bits against a table W bits wide
+#[derive(Default)]
+struct WideRangeCircuit<const W: usize> {
+    a: Option<Fp>,
+    total_bits: usize,
+}
+
+impl<const W: usize> Circuit<Fp> for WideRangeCircuit<W> {
+    type Config = RangeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            a: None,
+            total_bits: self.total_bits,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column()];
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+        let coeff = meta.fixed_column();
+        RangeChip::configure(meta, advice, coeff, W)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = RangeChip::new(config.clone());
+        chip.load(layouter.namespace(|| "range table"))?;
+        let a = layouter.assign_region(
+            || "a",
+            |mut region| {
+                region.assign_advice(
+                    || "a",
+                    config.advice[0],
+                    0,
+                    || self.a.ok_or(Error::Synthesis),
+                )
+            },
+        )?;
+        chip.range_check_wide(layouter.namespace(|| "a"), a, self.total_bits)
+    }
+}
+
+fn wide<const W: usize>(a: Fp, total_bits: usize) -> Result<(), Vec<VerifyFailure>> {
+    let circuit = WideRangeCircuit::<W> {
+        a: Some(a),
+        total_bits,
+    };
+    // the table takes 2^W rows, and there is no instance column
+    MockProver::run(W as u32 + 1, &circuit, vec![])
+        .unwrap()
+        .verify()
+}
+
+#[test]
+fn range_check_wide_at_widths_8_and_12() {
+    for total_bits in [13, 16, 64] {
+        let max = Fp::from_u128((1 << total_bits) - 1);
+        let over = max + Fp::one();
+        assert_eq!(wide::<8>(max, total_bits), Ok(()), "{} bits", total_bits);
+        assert_eq!(wide::<12>(max, total_bits), Ok(()), "{} bits", total_bits);
+        assert_eq!(wide::<8>(f(0), total_bits), Ok(()), "{} bits", total_bits);
+        assert!(wide::<8>(over, total_bits).is_err(), "{} bits", total_bits);
+        assert!(wide::<12>(over, total_bits).is_err(), "{} bits", total_bits);
+    }
+    // 13 bits is one chunk and one bit at width 12, the top bit alone is shifted up
+    assert_eq!(wide::<12>(f(1 << 12), 13), Ok(()));
+    assert!(wide::<12>(f(1 << 13), 13).is_err());
+}
+
+#[test]
+fn range_chip_refuses_a_zero_width_table() {
+    let circuit = WideRangeCircuit::<0> {
+        a: Some(f(0)),
+        total_bits: 8,
+    };
+    // no instance column, so the mock prover is run directly
+    assert!(matches!(
+        MockProver::run(4, &circuit, vec![]).err(),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn range_chip_refuses_a_table_past_max_k() {
+    // refused before the table's 2^64 rows are assigned
+    let circuit = WideRangeCircuit::<64> {
+        a: Some(f(0)),
+        total_bits: 64,
+    };
+    // no instance column, so the mock prover is run directly
+    assert!(matches!(
+        MockProver::run(4, &circuit, vec![]).err(),
+        Some(Error::Synthesis)
+    ));
+}
+
+// the aes s-box, FIPS-197 figure 7
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+// every input run through one s-box table, the outputs published in order. forged replaces
+// the first output with a value picked by hand
+#[derive(Default)]
+struct SboxCircuit {
+    sbox: Vec<u8>,
+    inputs: Vec<u64>,
+    forged: Option<u64>,
+}
+
+impl Circuit<Fp> for SboxCircuit {
+    type Config = (MyConfig, SboxConfig);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            sbox: self.sbox.clone(),
+            ..Self::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let config = MyCircuit::configure(meta);
+        let sbox = SboxChip::configure(meta, [config.advice[0], config.advice[1]]);
+        (config, sbox)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config.0);
+        let sbox = SboxChip::new(config.1.clone(), self.sbox.clone().try_into().unwrap());
+        sbox.load(layouter.namespace(|| "sbox table"))?;
+        for (i, a) in self.inputs.iter().enumerate() {
+            let a = chip.load_private(layouter.namespace(|| "a"), Some(f(*a)))?;
+            let out = match self.forged.filter(|_| i == 0) {
+                Some(out) => layouter.assign_region(
+                    || "sbox",
+                    |mut region| {
+                        config.1.s_sbox.enable(&mut region, 0)?;
+                        a.copy_advice(|| "a", &mut region, config.1.advice[0], 0)?;
+                        region.assign_advice(|| "sbox[a]", config.1.advice[1], 0, || Ok(f(out)))
+                    },
+                )?,
+                None => sbox.sbox(layouter.namespace(|| "sbox[a]"), a)?,
+            };
+            chip.expose_public(layouter.namespace(|| "out"), out, i)?;
+        }
+        Ok(())
+    }
+}
+
+// the table is 256 rows and the extra zero row
+const SBOX_K: u32 = 9;
+
+fn sbox(table: &[u8; 256], inputs: &[u64]) -> (SboxCircuit, Vec<Fp>) {
+    let circuit = SboxCircuit {
+        sbox: table.to_vec(),
+        inputs: inputs.to_vec(),
+        forged: None,
+    };
+    let outputs = inputs
+        .iter()
+        .map(|&a| f(table[a as usize] as u64))
+        .collect();
+    (circuit, outputs)
+}
+
+#[test]
+fn sbox_identity_table() {
+    let identity: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let (circuit, outputs) = sbox(&identity, &[0, 1, 0x53, 0xff]);
+    assert_eq!(outputs, vec![f(0), f(1), f(0x53), f(0xff)]);
+    assert_eq!(mock(SBOX_K, &circuit, outputs), Ok(()));
+}
+
+#[test]
+fn sbox_aes_constants() {
+    // a few entries from the standard, the table is shared by every call
+    let (circuit, outputs) = sbox(&AES_SBOX, &[0x00, 0x01, 0x53, 0xff, 0x00]);
+    assert_eq!(outputs, vec![f(0x63), f(0x7c), f(0xed), f(0x16), f(0x63)]);
+    assert_eq!(mock(SBOX_K, &circuit, outputs.clone()), Ok(()));
+    let mut wrong = outputs;
+    wrong[2] = f(0x53);
+    assert!(mock(SBOX_K, &circuit, wrong).is_err());
+    assert!(common::prove_and_verify(
+        SBOX_K,
+        &circuit,
+        &[f(0x63), f(0x7c), f(0xed), f(0x16), f(0x63)]
+    )
+    .is_ok());
+}
+
+#[test]
+fn sbox_rejects_a_pair_outside_the_table() {
+    let forged = |input: u64, out: u64| SboxCircuit {
+        sbox: AES_SBOX.to_vec(),
+        inputs: vec![input],
+        forged: Some(out),
+    };
+    assert_eq!(mock(SBOX_K, &forged(0x53, 0xed), vec![f(0xed)]), Ok(()));
+    assert!(is_lookup_failure(mock(
+        SBOX_K,
+        &forged(0x53, 0x53),
+        vec![f(0x53)]
+    )));
+    // 0 maps to 0x63, the zero row of the table is only there for unselected rows
+    assert!(is_lookup_failure(mock(SBOX_K, &forged(0, 0), vec![f(0)])));
+    // not a byte, 256 is no input of the table whatever the output
+    assert!(is_lookup_failure(mock(
+        SBOX_K,
+        &forged(256, 0x63),
+        vec![f(0x63)]
+    )));
+}
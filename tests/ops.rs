@@ -0,0 +1,750 @@
+// the arithmetic instructions of MyChip, each through a small circuit over MyCircuit's columns
+#[macro_use]
+mod common;
+
+use common::{copies, f, fails_gate, mock, prove_and_verify, regions, rows, synthesis_error};
+use halo2::{
+    chip::{MyChip, MyConfig, Ops},
+    circuit::MyCircuit,
+    proof::{create_proof_bytes, keygen, verify_proof_bytes},
+};
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Chip, Layouter, SimpleFloorPlanner},
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+// x ^ 3 - x + 5 for the private x
+gadget_circuit!(SubCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x3 = chip.cube(layouter.namespace(|| "x3"), x.clone())?;
+    let d = chip.sub(layouter.namespace(|| "x3 - x"), x3, x)?;
+    let r = chip.add_constant(layouter.namespace(|| "+ 5"), d, f(5))?;
+    chip.expose_public(layouter.namespace(|| "r"), r, 0)
+});
+
+#[test]
+fn sub() {
+    // 27 - 3 + 5
+    let circuit = SubCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &circuit, vec![f(29)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(30)]).is_err());
+    assert!(mock(4, &circuit, vec![f(28)]).is_err());
+}
+
+#[test]
+fn sub_wraps_below_zero() {
+    // 0 - 0 + 5 and 1 - 1 + 5, and for x = 2 the field value 8 - 2 + 5
+    for (x, r) in [(0, 5), (1, 5), (2, 11)] {
+        assert_eq!(mock(4, &SubCircuit::new(&[x], 0), vec![f(r)]), Ok(()));
+    }
+}
+
+// -x + 10
+gadget_circuit!(NegCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let neg = chip.neg(layouter.namespace(|| "-x"), x)?;
+    let r = chip.add_constant(layouter.namespace(|| "+ 10"), neg, f(10))?;
+    chip.expose_public(layouter.namespace(|| "r"), r, 0)
+});
+
+#[test]
+fn neg() {
+    let circuit = NegCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &circuit, vec![f(7)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(13)]).is_err());
+    // -0 is 0
+    assert_eq!(mock(4, &NegCircuit::new(&[0], 0), vec![f(10)]), Ok(()));
+}
+
+#[test]
+fn neg_proves_and_verifies() {
+    let circuit = NegCircuit::new(&[3], 0);
+    assert!(prove_and_verify(4, &circuit, &[f(7)]).is_ok());
+    assert!(prove_and_verify(4, &circuit, &[f(8)]).is_err());
+}
+
+gadget_circuit!(DivCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let b = chip.load_private(layouter.namespace(|| "b"), w[1])?;
+    let q = chip.div(layouter.namespace(|| "a / b"), a, b)?;
+    chip.expose_public(layouter.namespace(|| "q"), q, 0)
+});
+
+// the div region laid out by hand with the witness [a, b, q, 1 / b] instead of the real one
+gadget_circuit!(ForgedDivCircuit, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    let q = layouter.assign_region(
+        || "div",
+        |mut region| {
+            config.s_div.enable(&mut region, 0)?;
+            region.assign_advice(
+                || "lhs",
+                config.advice[0],
+                0,
+                || w[0].ok_or(Error::Synthesis),
+            )?;
+            region.assign_advice(
+                || "rhs",
+                config.advice[1],
+                0,
+                || w[1].ok_or(Error::Synthesis),
+            )?;
+            region.assign_advice(
+                || "1 / b",
+                config.advice[1],
+                1,
+                || w[3].ok_or(Error::Synthesis),
+            )?;
+            region.assign_advice(
+                || "a / b",
+                config.advice[0],
+                1,
+                || w[2].ok_or(Error::Synthesis),
+            )
+        },
+    )?;
+    chip.expose_public(layouter.namespace(|| "q"), q, 0)
+});
+
+#[test]
+fn div() {
+    let circuit = DivCircuit::new(&[12, 4], 0);
+    assert_eq!(mock(4, &circuit, vec![f(3)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(4)]).is_err());
+    // not an integer division, 7 / 2 is the field element q with 2 * q = 7
+    let q = f(7) * f(2).invert().unwrap();
+    assert_eq!(mock(4, &DivCircuit::new(&[7, 2], 0), vec![q]), Ok(()));
+}
+
+#[test]
+fn div_by_zero_is_a_synthesis_error() {
+    let circuit = DivCircuit::new(&[12, 0], 0);
+    assert!(matches!(
+        synthesis_error(4, &circuit, vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+#[test]
+fn div_rejects_a_malicious_quotient() {
+    let inv4 = f(4).invert().unwrap();
+    let honest = ForgedDivCircuit::with_fields(&[f(12), f(4), f(3), inv4], 0);
+    assert_eq!(mock(4, &honest, vec![f(3)]), Ok(()));
+    // q = 5 for 12 / 4
+    let forged = ForgedDivCircuit::with_fields(&[f(12), f(4), f(5), inv4], 0);
+    assert!(fails_gate(mock(4, &forged, vec![f(5)]), "div"));
+    // b = 0 makes b * q = a hold for a = 0 and any q, the inverse rule still catches it
+    let forged = ForgedDivCircuit::with_fields(&[f(0), f(0), f(5), f(1)], 0);
+    assert!(fails_gate(mock(4, &forged, vec![f(5)]), "div"));
+}
+
+gadget_circuit!(SquareCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x2 = chip.square(layouter.namespace(|| "x * x"), x)?;
+    chip.expose_public(layouter.namespace(|| "x2"), x2, 0)
+});
+
+// x^2 the way MyCircuit used to do it, the same cell on both sides of a mul
+gadget_circuit!(MulSquareCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x2 = chip.mul(layouter.namespace(|| "x * x"), x.clone(), x)?;
+    chip.expose_public(layouter.namespace(|| "x2"), x2, 0)
+});
+
+#[test]
+fn square() {
+    let circuit = SquareCircuit::new(&[7], 0);
+    assert_eq!(mock(4, &circuit, vec![f(49)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(14)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(49)]).is_ok());
+}
+
+#[test]
+fn square_is_cheaper_than_mul() {
+    let square = SquareCircuit::new(&[7], 0);
+    let mul = MulSquareCircuit::new(&[7], 0);
+    assert_eq!(mock(4, &mul, vec![f(49)]), Ok(()));
+    // both regions are two rows, square saves the second copy of x
+    assert!(rows(&square, &[f(49)]) <= rows(&mul, &[f(49)]));
+    assert_eq!(copies(&square, &[f(49)]) + 1, copies(&mul, &[f(49)]));
+}
+
+gadget_circuit!(PowCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let y = chip.pow(layouter.namespace(|| "x^n"), x, n as u64)?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// x^n as n - 1 chained muls
+gadget_circuit!(NaivePowCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let mut y = x.clone();
+    for i in 1..n {
+        y = chip.mul(layouter.namespace(|| format!("mul {}", i)), y, x.clone())?;
+    }
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn pow() {
+    let circuit = PowCircuit::new(&[3], 10);
+    assert_eq!(mock(5, &circuit, vec![f(59049)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(59048)]).is_err());
+    assert!(prove_and_verify(5, &circuit, &[f(59049)]).is_ok());
+    for n in 2..20 {
+        let expected = common::pow(f(3), n as u64);
+        assert_eq!(
+            mock(6, &PowCircuit::new(&[3], n), vec![expected]),
+            Ok(()),
+            "n = {}",
+            n
+        );
+    }
+}
+
+#[test]
+fn pow_edge_cases() {
+    // x^0 is the constant 1 whatever x is, x^1 is x itself
+    assert_eq!(mock(4, &PowCircuit::new(&[3], 0), vec![f(1)]), Ok(()));
+    assert_eq!(mock(4, &PowCircuit::new(&[0], 0), vec![f(1)]), Ok(()));
+    assert_eq!(mock(4, &PowCircuit::new(&[3], 1), vec![f(3)]), Ok(()));
+    // no region at all for x^1, only the load and the public copy
+    let one = PowCircuit::new(&[3], 1);
+    assert_eq!(copies(&one, &[f(3)]), 1);
+}
+
+#[test]
+fn pow_uses_fewer_rows_than_a_mul_chain() {
+    let x10 = common::pow(f(3), 10);
+    let fast = PowCircuit::new(&[3], 10);
+    let naive = NaivePowCircuit::new(&[3], 10);
+    assert_eq!(mock(6, &naive, vec![x10]), Ok(()));
+    // 10 = 0b1010, three squares and one mul against nine muls
+    assert!(rows(&fast, &[x10]) < rows(&naive, &[x10]));
+    assert!(copies(&fast, &[x10]) < copies(&naive, &[x10]));
+}
+
+// 3 * x + 5
+gadget_circuit!(MulConstantCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x3 = chip.mul_by_constant(layouter.namespace(|| "3 * x"), x, f(3))?;
+    let y = chip.add_constant(layouter.namespace(|| "+ 5"), x3, f(5))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// 3 * x + 5 with the 3 loaded as a constant cell and a full mul
+gadget_circuit!(LoadedConstantCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let three = chip.load_constant(layouter.namespace(|| "3"), f(3))?;
+    let x3 = chip.mul(layouter.namespace(|| "3 * x"), three, x)?;
+    let y = chip.add_constant(layouter.namespace(|| "+ 5"), x3, f(5))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn mul_by_constant() {
+    let circuit = MulConstantCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &circuit, vec![f(14)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(15)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(14)]).is_ok());
+}
+
+#[test]
+fn mul_by_constant_is_cheaper_than_load_constant_and_mul() {
+    let fast = MulConstantCircuit::new(&[3], 0);
+    let loaded = LoadedConstantCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &loaded, vec![f(14)]), Ok(()));
+    assert!(rows(&fast, &[f(14)]) < rows(&loaded, &[f(14)]));
+    assert!(copies(&fast, &[f(14)]) < copies(&loaded, &[f(14)]));
+}
+
+// x + n, the constant is on the circuit rather than the witness
+gadget_circuit!(AddConstantCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let y = chip.add_constant(layouter.namespace(|| "x + n"), x, f(n as u64))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// the add constant region with a witnessed output, w = [x, out]
+gadget_circuit!(ForgedAddConstantCircuit, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    let y = layouter.assign_region(
+        || "add constant",
+        |mut region| {
+            config.s_addc.enable(&mut region, 0)?;
+            region.assign_advice(
+                || "lhs",
+                config.advice[0],
+                0,
+                || w[0].ok_or(Error::Synthesis),
+            )?;
+            region.assign_fixed(|| "k", config.coeff, 0, || Ok(f(n as u64)))?;
+            region.assign_advice(
+                || "a + k",
+                config.advice[0],
+                1,
+                || w[1].ok_or(Error::Synthesis),
+            )
+        },
+    )?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn add_constant() {
+    assert_eq!(
+        mock(4, &AddConstantCircuit::new(&[30], 5), vec![f(35)]),
+        Ok(())
+    );
+    // same witness and public input under a different constant
+    assert!(mock(4, &AddConstantCircuit::new(&[30], 6), vec![f(35)]).is_err());
+    assert!(mock(4, &AddConstantCircuit::new(&[30], 5), vec![f(36)]).is_err());
+    assert!(prove_and_verify(4, &AddConstantCircuit::new(&[30], 5), &[f(35)]).is_ok());
+}
+
+#[test]
+fn add_constant_is_checked_against_the_fixed_column() {
+    let honest = ForgedAddConstantCircuit::new(&[30, 35], 5);
+    assert_eq!(mock(4, &honest, vec![f(35)]), Ok(()));
+    let forged = ForgedAddConstantCircuit::new(&[30, 36], 5);
+    assert!(fails_gate(mock(4, &forged, vec![f(36)]), "add constant"));
+}
+
+#[test]
+fn add_constant_saves_the_constant_region() {
+    let circuit = AddConstantCircuit::new(&[30], 5);
+    // a load_constant + add version would be a constant copy and two more advice copies
+    assert_eq!(copies(&circuit, &[f(35)]), 2);
+}
+
+// x^3 + x with a single mul add after the cube
+gadget_circuit!(MulAddCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x2 = chip.square(layouter.namespace(|| "x2"), x.clone())?;
+    let y = chip.mul_add(layouter.namespace(|| "x2 * x + x"), x2, x.clone(), x)?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// x^3 + x as a mul region and an add region
+gadget_circuit!(MulThenAddCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x2 = chip.square(layouter.namespace(|| "x2"), x.clone())?;
+    let x3 = chip.mul(layouter.namespace(|| "x3"), x2, x.clone())?;
+    let y = chip.add(layouter.namespace(|| "x3 + x"), x3, x)?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn mul_add() {
+    let circuit = MulAddCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &circuit, vec![f(30)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(29)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(30)]).is_ok());
+}
+
+#[test]
+fn mul_add_is_smaller_than_mul_then_add() {
+    let fused = MulAddCircuit::new(&[3], 0);
+    let split = MulThenAddCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &split, vec![f(30)]), Ok(()));
+    assert!(rows(&fused, &[f(30)]) < rows(&split, &[f(30)]));
+}
+
+// n * w[0] + (n - 1) * w[1] + ... + 1 * w[n - 1], so three terms are 3a + 2b + c
+gadget_circuit!(LinearCombinationCircuit, |chip, layouter, w, n| {
+    let values = chip.load_private_many(layouter.namespace(|| "terms"), w)?;
+    let terms: Vec<_> = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| (f((n - i) as u64), v))
+        .collect();
+    let y = chip.linear_combination(layouter.namespace(|| "lc"), &terms)?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn linear_combination() {
+    // 3 * 4 + 2 * 5 + 6
+    let circuit = LinearCombinationCircuit::new(&[4, 5, 6], 3);
+    assert_eq!(mock(4, &circuit, vec![f(28)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(27)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(28)]).is_ok());
+}
+
+#[test]
+fn linear_combination_edge_cases() {
+    assert_eq!(
+        mock(4, &LinearCombinationCircuit::new(&[], 0), vec![f(0)]),
+        Ok(())
+    );
+    assert!(mock(4, &LinearCombinationCircuit::new(&[], 0), vec![f(1)]).is_err());
+    assert_eq!(
+        mock(4, &LinearCombinationCircuit::new(&[7], 1), vec![f(7)]),
+        Ok(())
+    );
+    // a dozen terms is still one degree 2 gate per row
+    let values: Vec<u64> = (1..=12).collect();
+    let expected: u64 = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (12 - i as u64) * v)
+        .sum();
+    let circuit = LinearCombinationCircuit::new(&values, 12);
+    assert_eq!(mock(5, &circuit, vec![f(expected)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(expected + 1)]).is_err());
+}
+
+gadget_circuit!(SumCircuit, |chip, layouter, w, n| {
+    let values = chip.load_private_many(layouter.namespace(|| "values"), w)?;
+    let total = chip.sum(layouter.namespace(|| "sum"), &values)?;
+    chip.expose_public(layouter.namespace(|| "total"), total, 0)
+});
+
+// the same sum as a chain of add regions
+gadget_circuit!(AddChainCircuit, |chip, layouter, w, n| {
+    let values = chip.load_private_many(layouter.namespace(|| "values"), w)?;
+    let mut total = values[0].clone();
+    for (i, v) in values[1..].iter().enumerate() {
+        total = chip.add(
+            layouter.namespace(|| format!("add {}", i)),
+            total,
+            v.clone(),
+        )?;
+    }
+    chip.expose_public(layouter.namespace(|| "total"), total, 0)
+});
+
+#[test]
+fn sum_of_eight() {
+    let values = [3, 1, 4, 1, 5, 9, 2, 6];
+    let circuit = SumCircuit::new(&values, 0);
+    assert_eq!(mock(5, &circuit, vec![f(31)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(30)]).is_err());
+    assert!(prove_and_verify(5, &circuit, &[f(31)]).is_ok());
+    let chain = AddChainCircuit::new(&values, 0);
+    assert_eq!(mock(5, &chain, vec![f(31)]), Ok(()));
+    assert!(rows(&circuit, &[f(31)]) < rows(&chain, &[f(31)]));
+}
+
+#[test]
+fn sum_edge_cases() {
+    assert_eq!(mock(4, &SumCircuit::new(&[], 0), vec![f(0)]), Ok(()));
+    assert!(mock(4, &SumCircuit::new(&[], 0), vec![f(1)]).is_err());
+    assert_eq!(mock(4, &SumCircuit::new(&[7], 0), vec![f(7)]), Ok(()));
+    assert!(mock(4, &SumCircuit::new(&[7], 0), vec![f(8)]).is_err());
+}
+
+gadget_circuit!(ProductCircuit, |chip, layouter, w, n| {
+    let values = chip.load_private_many(layouter.namespace(|| "values"), w)?;
+    let product = chip.product(layouter.namespace(|| "product"), &values)?;
+    chip.expose_public(layouter.namespace(|| "product"), product, 0)
+});
+
+#[test]
+fn product_of_four() {
+    let circuit = ProductCircuit::new(&[2, 3, 5, 7], 0);
+    assert_eq!(mock(4, &circuit, vec![f(210)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(211)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(210)]).is_ok());
+}
+
+#[test]
+fn product_edge_cases() {
+    assert_eq!(mock(4, &ProductCircuit::new(&[], 0), vec![f(1)]), Ok(()));
+    assert!(mock(4, &ProductCircuit::new(&[], 0), vec![f(0)]).is_err());
+    assert_eq!(mock(4, &ProductCircuit::new(&[7], 0), vec![f(7)]), Ok(()));
+    // a zero anywhere zeroes the rest of the chain
+    let circuit = ProductCircuit::new(&[2, 0, 5, 7], 0);
+    assert_eq!(mock(4, &circuit, vec![f(0)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(70)]).is_err());
+}
+
+// w[..n] . w[n..]
+gadget_circuit!(DotProductCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private_many(layouter.namespace(|| "a"), &w[..n])?;
+    let b = chip.load_private_many(layouter.namespace(|| "b"), &w[n..])?;
+    let dot = chip.dot_product(layouter.namespace(|| "a . b"), &a, &b)?;
+    chip.expose_public(layouter.namespace(|| "dot"), dot, 0)
+});
+
+#[test]
+fn dot_product() {
+    // [1, 2, 3, 4, 5] . [6, 7, 8, 9, 10]
+    let circuit = DotProductCircuit::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 5);
+    assert_eq!(mock(5, &circuit, vec![f(130)]), Ok(()));
+    assert!(mock(5, &circuit, vec![f(131)]).is_err());
+    assert!(prove_and_verify(5, &circuit, &[f(130)]).is_ok());
+    // orthogonal to a public vector
+    let circuit = DotProductCircuit::new(&[2, 0, 5, 0, 7, 0], 3);
+    assert_eq!(mock(4, &circuit, vec![f(0)]), Ok(()));
+}
+
+#[test]
+fn dot_product_lengths() {
+    assert_eq!(mock(4, &DotProductCircuit::new(&[], 0), vec![f(0)]), Ok(()));
+    assert!(mock(4, &DotProductCircuit::new(&[], 0), vec![f(1)]).is_err());
+    assert_eq!(
+        mock(4, &DotProductCircuit::new(&[6, 7], 1), vec![f(42)]),
+        Ok(())
+    );
+    assert!(mock(4, &DotProductCircuit::new(&[6, 7], 1), vec![f(13)]).is_err());
+    let mismatched = DotProductCircuit::new(&[1, 2, 3], 2);
+    assert!(matches!(
+        synthesis_error(4, &mismatched, vec![f(0)]),
+        Some(Error::Synthesis)
+    ));
+}
+
+// sum(coeffs[i] * x^i) through eval_poly, the coefficients are part of the circuit
+#[derive(Clone, Default)]
+struct HornerCircuit {
+    coeffs: Vec<Fp>,
+    x: Option<Fp>,
+}
+
+impl Circuit<Fp> for HornerCircuit {
+    type Config = MyConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            coeffs: self.coeffs.clone(),
+            x: None,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        MyCircuit::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MyChip::new(config);
+        let x = chip.load_private(layouter.namespace(|| "x"), self.x)?;
+        let y = chip.eval_poly(layouter.namespace(|| "p(x)"), &self.coeffs, x)?;
+        chip.expose_public(layouter.namespace(|| "y"), y, 0)
+    }
+}
+
+fn horner(coeffs: &[u64], x: u64) -> HornerCircuit {
+    HornerCircuit {
+        coeffs: coeffs.iter().map(|c| f(*c)).collect(),
+        x: Some(f(x)),
+    }
+}
+
+#[test]
+fn eval_poly_is_the_cubic() {
+    // 5 + x + x^3, MyCircuit's equation
+    let circuit = horner(&[5, 1, 0, 1], 3);
+    assert_eq!(mock(4, &circuit, vec![f(35)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(36)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(35)]).is_ok());
+}
+
+#[test]
+fn eval_poly_degrees() {
+    // a constant ignores x
+    assert_eq!(mock(4, &horner(&[7], 3), vec![f(7)]), Ok(()));
+    assert!(mock(4, &horner(&[7], 3), vec![f(3)]).is_err());
+    // 2 + 4x
+    assert_eq!(mock(4, &horner(&[2, 4], 3), vec![f(14)]), Ok(()));
+    assert!(mock(4, &horner(&[2, 4], 3), vec![f(15)]).is_err());
+    // 1 + 2x + ... + 11x^10 at x = 2
+    let coeffs: Vec<u64> = (1..=11).collect();
+    let expected: u64 = coeffs.iter().enumerate().map(|(i, c)| c << i).sum();
+    assert_eq!(mock(5, &horner(&coeffs, 2), vec![f(expected)]), Ok(()));
+    assert!(mock(5, &horner(&coeffs, 2), vec![f(expected - 1)]).is_err());
+}
+
+#[test]
+fn eval_poly_rows_grow_with_the_degree() {
+    let rows_of = |degree: u64| {
+        let coeffs: Vec<u64> = (0..=degree).collect();
+        let circuit = horner(&coeffs, 2);
+        let y: u64 = coeffs.iter().enumerate().map(|(i, c)| c << i).sum();
+        rows(&circuit, &[f(y)])
+    };
+    // one region, a row per coefficient
+    assert_eq!(rows_of(10) - rows_of(5), 5);
+}
+
+// 2 * x + 5
+gadget_circuit!(DoubleCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x2 = chip.double(layouter.namespace(|| "2 * x"), x)?;
+    let y = chip.add_constant(layouter.namespace(|| "+ 5"), x2, f(5))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// 2 * x + 5 with x added to itself
+gadget_circuit!(AddSelfCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x2 = chip.add(layouter.namespace(|| "x + x"), x.clone(), x)?;
+    let y = chip.add_constant(layouter.namespace(|| "+ 5"), x2, f(5))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn double() {
+    let circuit = DoubleCircuit::new(&[7], 0);
+    assert_eq!(mock(4, &circuit, vec![f(19)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(18)]).is_err());
+    assert!(prove_and_verify(4, &circuit, &[f(19)]).is_ok());
+}
+
+#[test]
+fn double_needs_one_copy_less_than_add() {
+    let double = DoubleCircuit::new(&[7], 0);
+    let add = AddSelfCircuit::new(&[7], 0);
+    assert_eq!(mock(4, &add, vec![f(19)]), Ok(()));
+    assert_eq!(copies(&double, &[f(19)]) + 1, copies(&add, &[f(19)]));
+    assert_eq!(rows(&double, &[f(19)]), rows(&add, &[f(19)]));
+}
+
+// x^3 + x + 5 with the single region cube
+gadget_circuit!(CubeCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x3 = chip.cube(layouter.namespace(|| "x3"), x.clone())?;
+    let y = chip.add(layouter.namespace(|| "x3 + x"), x3, x)?;
+    let y = chip.add_constant(layouter.namespace(|| "+ 5"), y, f(5))?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// x^3 on its own, the cube against square and mul
+gadget_circuit!(CubeOnlyCircuit, |chip, layouter, w, n| {
+    let x = chip.load_private(layouter.namespace(|| "x"), w[0])?;
+    let x3 = if n == 0 {
+        chip.cube(layouter.namespace(|| "x3"), x)?
+    } else {
+        let x2 = chip.square(layouter.namespace(|| "x2"), x.clone())?;
+        chip.mul(layouter.namespace(|| "x3"), x2, x)?
+    };
+    chip.expose_public(layouter.namespace(|| "y"), x3, 0)
+});
+
+#[test]
+fn cube() {
+    let circuit = CubeCircuit::new(&[3], 0);
+    assert_eq!(mock(4, &circuit, vec![f(35)]), Ok(()));
+    assert!(mock(4, &circuit, vec![f(36)]).is_err());
+    // keygen is happy with the degree 3 gate at k = 4
+    assert!(halo2::proof::keygen(4, &circuit.without_witnesses()).is_ok());
+    assert!(prove_and_verify(4, &circuit, &[f(35)]).is_ok());
+    assert!(prove_and_verify(4, &circuit, &[f(36)]).is_err());
+}
+
+#[test]
+fn cube_is_one_region_and_one_copy() {
+    let cube = CubeOnlyCircuit::new(&[3], 0);
+    let chain = CubeOnlyCircuit::new(&[3], 1);
+    assert_eq!(mock(4, &chain, vec![f(27)]), Ok(()));
+    // the load, the cube and the public copy
+    assert_eq!(regions(&cube, &[f(27)]), 2);
+    assert_eq!(copies(&cube, &[f(27)]), 2);
+    assert!(rows(&cube, &[f(27)]) < rows(&chain, &[f(27)]));
+    assert!(copies(&cube, &[f(27)]) < copies(&chain, &[f(27)]));
+}
+
+// w[0] + k * w[1] with k = w[2], the scalar ends up in the fixed column
+gadget_circuit!(ScaledAddCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let b = chip.load_private(layouter.namespace(|| "b"), w[1])?;
+    let k = w[2].unwrap_or_else(Fp::zero);
+    let y = chip.scaled_add(layouter.namespace(|| "a + k * b"), a, b, k)?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// the same with mul_by_constant and add
+gadget_circuit!(MulThenAddConstantCircuit, |chip, layouter, w, n| {
+    let a = chip.load_private(layouter.namespace(|| "a"), w[0])?;
+    let b = chip.load_private(layouter.namespace(|| "b"), w[1])?;
+    let kb = chip.mul_by_constant(layouter.namespace(|| "k * b"), b, f(7))?;
+    let y = chip.add(layouter.namespace(|| "a + k * b"), a, kb)?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn scaled_add() {
+    let k = Fp::random(StdRng::seed_from_u64(36));
+    for (k, y) in [(f(0), f(10)), (f(1), f(13)), (k, f(10) + k * f(3))] {
+        let circuit = ScaledAddCircuit::with_fields(&[f(10), f(3), k], 0);
+        assert_eq!(mock(4, &circuit, vec![y]), Ok(()));
+        assert!(mock(4, &circuit, vec![y + f(1)]).is_err());
+        // keygen bakes k in, so the key is made from the circuit with k and without a and b
+        let (params, pk) = keygen(4, &circuit).unwrap();
+        let proof = create_proof_bytes(&params, &pk, &circuit, &[y]).unwrap();
+        assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &[y]).is_ok());
+        assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &[y + f(1)]).is_err());
+    }
+}
+
+#[test]
+fn scaled_add_is_smaller_than_mul_by_constant_and_add() {
+    let fused = ScaledAddCircuit::new(&[10, 3, 7], 0);
+    let split = MulThenAddConstantCircuit::new(&[10, 3], 0);
+    assert_eq!(mock(4, &split, vec![f(31)]), Ok(()));
+    // two regions of two rows each become one
+    assert_eq!(rows(&fused, &[f(31)]) + 2, rows(&split, &[f(31)]));
+}
+
+// w[0] * w[1] + w[2] * w[3]
+gadget_circuit!(MulMulAddCircuit, |chip, layouter, w, n| {
+    let v = chip.load_private_many(layouter.namespace(|| "a, b, c, d"), w)?;
+    let y = chip.mul_mul_add(
+        layouter.namespace(|| "a * b + c * d"),
+        v[0].clone(),
+        v[1].clone(),
+        v[2].clone(),
+        v[3].clone(),
+    )?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+// the mul mul add row with the output witnessed directly, w = [a, b, c, d, out]
+gadget_circuit!(ForgedMulMulAddCircuit, |chip, layouter, w, n| {
+    let config = chip.config().clone();
+    let y = layouter.assign_region(
+        || "mul mul add",
+        |mut region| {
+            config.s_mma.enable(&mut region, 0)?;
+            for (column, v) in config.advice.iter().zip(w) {
+                region.assign_advice(|| "in", *column, 0, || v.ok_or(Error::Synthesis))?;
+            }
+            region.assign_advice(
+                || "out",
+                config.advice[0],
+                1,
+                || w[4].ok_or(Error::Synthesis),
+            )
+        },
+    )?;
+    chip.expose_public(layouter.namespace(|| "y"), y, 0)
+});
+
+#[test]
+fn mul_mul_add_matches_native_arithmetic() {
+    let mut rng = StdRng::seed_from_u64(37);
+    for _ in 0..8 {
+        let v: Vec<Fp> = (0..4).map(|_| Fp::random(&mut rng)).collect();
+        let y = v[0] * v[1] + v[2] * v[3];
+        let circuit = MulMulAddCircuit::with_fields(&v, 0);
+        assert_eq!(mock(4, &circuit, vec![y]), Ok(()));
+        assert!(mock(4, &circuit, vec![y - f(1)]).is_err());
+    }
+    assert!(prove_and_verify(4, &MulMulAddCircuit::new(&[2, 3, 4, 5], 0), &[f(26)]).is_ok());
+}
+
+#[test]
+fn mul_mul_add_rejects_a_corrupted_output() {
+    let honest = ForgedMulMulAddCircuit::new(&[2, 3, 4, 5, 26], 0);
+    assert_eq!(mock(4, &honest, vec![f(26)]), Ok(()));
+    let corrupted = ForgedMulMulAddCircuit::new(&[2, 3, 4, 5, 27], 0);
+    assert!(fails_gate(mock(4, &corrupted, vec![f(27)]), "mul mul add"));
+}
@@ -0,0 +1,73 @@
+// the prove() helper, checked against keys made separately from the circuit shape
+use halo2::{
+    circuit::MyCircuit,
+    proof::{keygen, prove, verify, ProveError, MAX_K, MIN_K},
+};
+use halo2_proofs::pasta::Fp;
+
+#[test]
+fn proof_verifies() {
+    let (x, constant, result) = (Fp::from(3), Fp::from(5), Fp::from(35));
+    let proof = prove(x, constant, result, 4).unwrap();
+    let circuit = MyCircuit { constant, x: None };
+    let (params, pk) = keygen(4, &circuit).unwrap();
+    assert!(verify(&proof, result, &params, pk.get_vk()).is_ok());
+}
+
+#[test]
+fn larger_k_works() {
+    assert!(prove(Fp::from(3), Fp::from(5), Fp::from(35), 6).is_ok());
+}
+
+#[test]
+fn rejects_wrong_result() {
+    let err = prove(Fp::from(3), Fp::from(5), Fp::from(36), 4).unwrap_err();
+    match err {
+        ProveError::WrongResult { expected, got } => {
+            assert_eq!(expected, Fp::from(35));
+            assert_eq!(got, Fp::from(36));
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn rejects_small_k() {
+    for k in 0..MIN_K {
+        let err = prove(Fp::from(3), Fp::from(5), Fp::from(35), k).unwrap_err();
+        assert!(
+            matches!(err, ProveError::KTooSmall { min: 4, .. }),
+            "{:?}",
+            err
+        );
+    }
+    // MIN_K is tight, one less and the circuit does not fit
+    let circuit = MyCircuit {
+        constant: Fp::from(5),
+        x: None,
+    };
+    assert!(keygen(MIN_K - 1, &circuit).is_err());
+}
+
+#[test]
+fn rejects_large_k() {
+    for k in [MAX_K + 1, 64, u32::MAX] {
+        let err = prove(Fp::from(3), Fp::from(5), Fp::from(35), k).unwrap_err();
+        assert!(
+            matches!(err, ProveError::KTooLarge { max: MAX_K, .. }),
+            "{:?}",
+            err
+        );
+    }
+}
+
+#[test]
+fn errors_display() {
+    let err = prove(Fp::from(3), Fp::from(5), Fp::from(36), 4).unwrap_err();
+    assert!(err.to_string().contains("does not match"));
+    let err = prove(Fp::from(3), Fp::from(5), Fp::from(35), 2).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "k = 2 is too small, the circuit needs at least 4"
+    );
+}
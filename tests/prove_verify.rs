@@ -0,0 +1,111 @@
+// the end to end flow of the cubic circuit through the public api only
+use halo2::{
+    circuit::{MultiOutputCircuit, MyCircuit},
+    proof::{create_proof_bytes, cubic_demo, keygen, verify_proof_bytes},
+};
+use halo2_proofs::pasta::Fp;
+
+fn circuit(x: u64) -> MyCircuit {
+    MyCircuit {
+        constant: Fp::from(5),
+        x: Some(Fp::from(x)),
+    }
+}
+
+#[test]
+fn proves_and_verifies() {
+    // 3 ^ 3 + 3 + 5 = 35
+    let circuit = circuit(3);
+    let publics = [Fp::from(35)];
+    let (params, pk) = keygen(4, &circuit).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &publics).unwrap();
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &publics).is_ok());
+}
+
+#[test]
+fn rejects_wrong_public_inputs() {
+    let circuit = circuit(3);
+    let (params, pk) = keygen(4, &circuit).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &[Fp::from(35)]).unwrap();
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &[Fp::from(36)]).is_err());
+}
+
+#[test]
+fn wrong_witness_does_not_verify() {
+    // x = 4 does not solve the equation, the proof is still made but cannot verify
+    let circuit = circuit(4);
+    let publics = [Fp::from(35)];
+    let (params, pk) = keygen(4, &circuit).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &publics).unwrap();
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &publics).is_err());
+}
+
+#[test]
+fn only_the_result_is_public() {
+    // x2 = 9 would give away x = 3 up to its sign, so it is not an instance row any more
+    let circuit = circuit(3);
+    let (params, pk) = keygen(4, &circuit).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &[Fp::from(35)]).unwrap();
+    let publics = [Fp::from(9), Fp::from(35)];
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &publics).is_err());
+}
+
+fn multi_output(x: u64) -> MultiOutputCircuit {
+    MultiOutputCircuit {
+        constant: Fp::from(5),
+        x: Some(Fp::from(x)),
+    }
+}
+
+#[test]
+fn multi_output_publishes_every_output_in_order() {
+    let circuit = multi_output(3);
+    let publics = [Fp::from(9), Fp::from(27), Fp::from(35)];
+    let (params, pk) = keygen(4, &circuit).unwrap();
+    let proof = create_proof_bytes(&params, &pk, &circuit, &publics).unwrap();
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &publics).is_ok());
+    // the same values on the wrong rows
+    let shuffled = [Fp::from(27), Fp::from(9), Fp::from(35)];
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &shuffled).is_err());
+}
+
+#[test]
+fn multi_output_needs_every_row() {
+    let circuit = multi_output(3);
+    let (params, pk) = keygen(4, &circuit).unwrap();
+    let proof = create_proof_bytes(
+        &params,
+        &pk,
+        &circuit,
+        &[Fp::from(9), Fp::from(27), Fp::from(35)],
+    )
+    .unwrap();
+    // two elements leave the result row at zero
+    let publics = [Fp::from(9), Fp::from(27)];
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &publics).is_err());
+}
+
+#[test]
+fn both_fit_in_k_4_and_not_below() {
+    // the extra instance rows don't take any more rows of the layout
+    for k in [3, 4] {
+        assert_eq!(keygen(k, &circuit(3)).is_ok(), k == 4);
+        assert_eq!(keygen(k, &multi_output(3)).is_ok(), k == 4);
+    }
+}
+
+#[test]
+fn cubic_demo_verifies() {
+    // x = 3 with [x2, x3, result] public, against keys made here from the circuit shape
+    let proof = cubic_demo().unwrap();
+    let publics = [9, 27, 35].map(Fp::from);
+    let (params, pk) = keygen(
+        4,
+        &MultiOutputCircuit {
+            constant: Fp::from(5),
+            x: None,
+        },
+    )
+    .unwrap();
+    assert!(verify_proof_bytes(&params, pk.get_vk(), &proof, &publics).is_ok());
+}
@@ -0,0 +1,43 @@
+// one Prover, keygen done once, many proofs that all check against its verifying key
+use halo2::proof::{verify, ProveError, Prover};
+use halo2_proofs::{arithmetic::Field, pasta::Fp};
+
+fn result(x: Fp) -> Fp {
+    x.cube() + x + Fp::from(5)
+}
+
+#[test]
+fn proofs_verify_against_one_vk() {
+    let prover = Prover::setup(4, Fp::from(5)).unwrap();
+    assert_eq!(prover.k(), 4);
+    assert_eq!(prover.constant(), Fp::from(5));
+    let xs = [0, 1, 3, 10, 12345].map(Fp::from);
+    let proofs: Vec<_> = xs
+        .iter()
+        .map(|&x| prover.prove(x, result(x)).unwrap())
+        .collect();
+    for (x, proof) in xs.iter().zip(&proofs) {
+        assert!(verify(proof, result(*x), prover.params(), prover.vk()).is_ok());
+    }
+    // each proof is bound to its own result
+    assert!(verify(&proofs[0], result(xs[1]), prover.params(), prover.vk()).is_err());
+}
+
+#[test]
+fn prove_checks_the_result() {
+    let prover = Prover::setup(4, Fp::from(5)).unwrap();
+    let err = prover.prove(Fp::from(3), Fp::from(36)).unwrap_err();
+    assert!(matches!(err, ProveError::WrongResult { .. }), "{:?}", err);
+}
+
+#[test]
+fn setup_checks_k() {
+    assert!(matches!(
+        Prover::setup(3, Fp::from(5)),
+        Err(ProveError::KTooSmall { k: 3, min: 4 })
+    ));
+    assert!(matches!(
+        Prover::setup(32, Fp::from(5)),
+        Err(ProveError::KTooLarge { k: 32, max: 31 })
+    ));
+}
@@ -0,0 +1,123 @@
+// circuits picked by name and run from json, the way the binary runs them
+use halo2::{
+    circuit::{FibonacciCircuit, IntervalCircuit, MyCircuit},
+    proof::{VerifyError, MAX_K},
+    registry::{entries, find, run, RegistryError},
+};
+use serde_json::json;
+
+#[test]
+fn every_entry_round_trips() {
+    let cases = [
+        ("cubic", json!({"x": 3, "constant": 5}), json!([35])),
+        ("fibonacci", json!({"a": 1, "b": 1, "n": 10}), json!([89])),
+        ("range", json!({"x": 1990}), json!([1970, 2005])),
+    ];
+    // one case for every entry
+    let names: Vec<_> = entries().iter().map(|e| e.name).collect();
+    assert_eq!(names, cases.iter().map(|c| c.0).collect::<Vec<_>>());
+    for (name, witness, publics) in cases {
+        let entry = find(name).unwrap();
+        assert_eq!(entry.public_inputs.len(), publics.as_array().unwrap().len());
+        let result = run(name, &witness.to_string(), &publics.to_string());
+        assert!(result.is_ok(), "{}: {:?}", name, result);
+    }
+}
+
+#[test]
+fn wrong_public_inputs_carry_the_verify_error() {
+    for (name, witness, publics) in [
+        ("cubic", json!({"x": 3, "constant": 5}), json!([36])),
+        ("fibonacci", json!({"a": 1, "b": 1, "n": 10}), json!([88])),
+        ("range", json!({"x": 2006}), json!([1970, 2005])),
+    ] {
+        let err = run(name, &witness.to_string(), &publics.to_string()).unwrap_err();
+        assert!(
+            matches!(err, RegistryError::Verify(VerifyError::Unsatisfied)),
+            "{}: {:?}",
+            name,
+            err
+        );
+        assert_eq!(
+            err.to_string(),
+            "proof did not verify: proof does not satisfy the constraints"
+        );
+    }
+}
+
+#[test]
+fn the_entries_use_the_circuits_own_k() {
+    let k = |name: &str, witness| (find(name).unwrap().build)(&witness).unwrap().k;
+    assert_eq!(k("cubic", json!({"x": 3, "constant": 5})), MyCircuit::k());
+    assert_eq!(k("range", json!({"x": 1990})), IntervalCircuit::k());
+    for n in [1, 10, 100] {
+        let circuit = FibonacciCircuit {
+            a: None,
+            b: None,
+            n,
+        };
+        assert_eq!(k("fibonacci", json!({"a": 1, "b": 1, "n": n})), circuit.k());
+    }
+}
+
+#[test]
+fn unknown_circuit() {
+    let err = run("sha256", "{}", "[]").unwrap_err();
+    match &err {
+        RegistryError::UnknownCircuit { name, available } => {
+            assert_eq!(name, "sha256");
+            assert_eq!(available, &["cubic", "fibonacci", "range"]);
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+    assert_eq!(
+        err.to_string(),
+        "no circuit named \"sha256\", available are cubic, fibonacci, range"
+    );
+}
+
+#[test]
+fn too_many_rows_is_an_error_not_a_panic() {
+    for n in [1u64 << 31, 1 << 32, u64::MAX] {
+        let witness = json!({"a": 1, "b": 1, "n": n}).to_string();
+        let err = run("fibonacci", &witness, "[0]").unwrap_err();
+        assert!(
+            matches!(err, RegistryError::KTooLarge { k, max: MAX_K } if k > MAX_K),
+            "{}: {:?}",
+            n,
+            err
+        );
+    }
+}
+
+#[test]
+fn bad_json() {
+    let run = |witness, publics| run("cubic", witness, publics).unwrap_err();
+    assert!(matches!(run("{", "[35]"), RegistryError::Json(_)));
+    assert!(matches!(run("{}", "35"), RegistryError::Json(_)));
+    assert!(matches!(
+        run("{\"x\": 3}", "[35]"),
+        RegistryError::BadField("constant")
+    ));
+    assert!(matches!(
+        run("{\"x\": -3, \"constant\": 5}", "[35]"),
+        RegistryError::BadField("x")
+    ));
+    assert!(matches!(
+        run("{\"x\": 3, \"constant\": 5}", "[35, 1]"),
+        RegistryError::WrongPublicCount {
+            expected: 1,
+            got: 2
+        }
+    ));
+}
+
+#[test]
+fn values_past_u64_as_strings() {
+    // x^3 + x + 5 for x = 2^40 is past u64, so the result goes in as a decimal string
+    let x = 1u128 << 40;
+    let result = x * x * x + x + 5;
+    let witness = json!({"x": x as u64, "constant": 5}).to_string();
+    let publics = json!([result.to_string()]).to_string();
+    assert!(run("cubic", &witness, &publics).is_ok());
+}
@@ -0,0 +1,94 @@
+// a Verifier rebuilt from serialized params and constant, checking proofs from a Prover
+// that is gone by the time verification happens
+use halo2::proof::{ArtifactError, Prover, Verifier, VerifyError};
+use halo2_proofs::{arithmetic::Field, pasta::group::ff::PrimeField, pasta::Fp};
+
+// a proof with the result it is for
+type Proof = (Fp, Vec<u8>);
+
+// what a prover hands out, params bytes, constant bytes and proofs
+fn artifacts() -> (Vec<u8>, Vec<u8>, Vec<Proof>) {
+    let prover = Prover::setup(4, Fp::from(5)).unwrap();
+    let proofs = [1, 3, 42]
+        .map(Fp::from)
+        .into_iter()
+        .map(|x| {
+            let result = x.cube() + x + Fp::from(5);
+            (result, prover.prove(x, result).unwrap())
+        })
+        .collect();
+    let (params, constant) = Verifier::from_prover(&prover).to_params_and_constant();
+    (params, constant, proofs)
+}
+
+#[test]
+fn verifies_from_serialized_artifacts() {
+    let (params, constant, proofs) = artifacts();
+    let verifier = Verifier::from_params_and_constant(&params, &constant).unwrap();
+    assert_eq!(verifier.k(), 4);
+    for (result, proof) in &proofs {
+        assert!(verifier.verify(proof, &[*result]).is_ok());
+    }
+    let err = verifier.verify(&proofs[0].1, &[Fp::from(35)]).unwrap_err();
+    assert!(matches!(err, VerifyError::Unsatisfied), "{:?}", err);
+    let err = verifier
+        .verify(&proofs[0].1[..100], &[proofs[0].0])
+        .unwrap_err();
+    assert!(matches!(err, VerifyError::Malformed(_)), "{:?}", err);
+}
+
+#[test]
+fn another_constant_rejects() {
+    let (params, _, proofs) = artifacts();
+    let constant = Fp::from(6).to_repr().to_vec();
+    let verifier = Verifier::from_params_and_constant(&params, &constant).unwrap();
+    for (result, proof) in &proofs {
+        assert!(verifier.verify(proof, &[*result]).is_err());
+    }
+}
+
+#[test]
+fn rejects_bad_params() {
+    let (params, constant, _) = artifacts();
+    for bad in [&params[..0], &params[..3], &params[..params.len() - 1]] {
+        let err = Verifier::from_params_and_constant(bad, &constant)
+            .err()
+            .unwrap();
+        assert!(matches!(err, ArtifactError::Params(_)), "{:?}", err);
+    }
+    let mut long = params.clone();
+    long.push(0);
+    assert!(matches!(
+        Verifier::from_params_and_constant(&long, &constant),
+        Err(ArtifactError::Params(_))
+    ));
+    // a k far too big is caught before Params::read tries to shift by it
+    let mut huge_k = params.clone();
+    huge_k[..4].copy_from_slice(&200u32.to_le_bytes());
+    assert!(matches!(
+        Verifier::from_params_and_constant(&huge_k, &constant),
+        Err(ArtifactError::Params(_))
+    ));
+    // params for a k the circuit does not fit in
+    let mut small = Verifier::from_params_and_constant(&params, &constant)
+        .unwrap()
+        .to_params_and_constant()
+        .0;
+    small.truncate(4 + (2 * 8 + 2) * 32);
+    small[..4].copy_from_slice(&3u32.to_le_bytes());
+    assert!(matches!(
+        Verifier::from_params_and_constant(&small, &constant),
+        Err(ArtifactError::Keygen(_))
+    ));
+}
+
+#[test]
+fn rejects_bad_constant() {
+    let (params, constant, _) = artifacts();
+    for bad in [&constant[..31], &[0xff; 32][..], &[0; 33][..]] {
+        assert!(matches!(
+            Verifier::from_params_and_constant(&params, bad),
+            Err(ArtifactError::Constant)
+        ));
+    }
+}
@@ -0,0 +1,96 @@
+// verify() on proofs from prove(), good ones and ones broken in different ways
+use halo2::{
+    circuit::MyCircuit,
+    proof::{keygen, prove, verify, VerifyError},
+};
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::VerifyingKey,
+    poly::commitment::Params,
+};
+
+const K: u32 = 4;
+
+// a proof for x = 3 with x ^ 3 + x + 5 = 35 and the keys to check it with
+fn setup() -> (Vec<u8>, Params<EqAffine>, VerifyingKey<EqAffine>) {
+    let proof = prove(Fp::from(3), Fp::from(5), Fp::from(35), K).unwrap();
+    let circuit = MyCircuit {
+        constant: Fp::from(5),
+        x: None,
+    };
+    let (params, pk) = keygen(K, &circuit).unwrap();
+    (proof, params, pk.get_vk().clone())
+}
+
+#[test]
+fn good_proof() {
+    let (proof, params, vk) = setup();
+    assert!(verify(&proof, Fp::from(35), &params, &vk).is_ok());
+}
+
+#[test]
+fn truncated_proof() {
+    let (proof, params, vk) = setup();
+    for len in [0, 1, 32, proof.len() / 2, proof.len() - 1] {
+        let err = verify(&proof[..len], Fp::from(35), &params, &vk).unwrap_err();
+        assert!(
+            matches!(err, VerifyError::Malformed(_)),
+            "{}: {:?}",
+            len,
+            err
+        );
+    }
+}
+
+#[test]
+fn trailing_bytes() {
+    let (mut proof, params, vk) = setup();
+    proof.push(0);
+    let err = verify(&proof, Fp::from(35), &params, &vk).unwrap_err();
+    assert!(matches!(err, VerifyError::Malformed(_)), "{:?}", err);
+}
+
+#[test]
+fn bit_flipped_proof() {
+    let (proof, params, vk) = setup();
+    // the proof opens with a compressed point. the top bit of its last byte is the sign of y,
+    // flipping it gives the negated point which still decodes but is the wrong commitment
+    let mut flipped = proof.clone();
+    flipped[31] ^= 0x80;
+    let err = verify(&flipped, Fp::from(35), &params, &vk).unwrap_err();
+    assert!(matches!(err, VerifyError::Unsatisfied), "{:?}", err);
+    // the bit below pushes x past the modulus, which does not decode at all
+    let mut flipped = proof.clone();
+    flipped[31] ^= 0x40;
+    let err = verify(&flipped, Fp::from(35), &params, &vk).unwrap_err();
+    assert!(matches!(err, VerifyError::Malformed(_)), "{:?}", err);
+    // anywhere else it still fails one way or the other
+    for i in (0..proof.len()).step_by(97) {
+        let mut flipped = proof.clone();
+        flipped[i] ^= 1;
+        assert!(
+            verify(&flipped, Fp::from(35), &params, &vk).is_err(),
+            "{}",
+            i
+        );
+    }
+}
+
+#[test]
+fn wrong_public_input() {
+    let (proof, params, vk) = setup();
+    let err = verify(&proof, Fp::from(36), &params, &vk).unwrap_err();
+    assert!(matches!(err, VerifyError::Unsatisfied), "{:?}", err);
+}
+
+#[test]
+fn wrong_constant_in_keys() {
+    // keys for x ^ 3 + x + 6 do not accept a proof made with constant 5
+    let (proof, params, _) = setup();
+    let circuit = MyCircuit {
+        constant: Fp::from(6),
+        x: None,
+    };
+    let (_, pk) = keygen(K, &circuit).unwrap();
+    assert!(verify(&proof, Fp::from(35), &params, pk.get_vk()).is_err());
+}